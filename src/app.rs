@@ -1,13 +1,21 @@
-use crate::config::{RecentProjects, Settings};
+use crate::config::settings::{MainWindowState, WindowState};
+use crate::config::{RecentProjects, Settings, ThemeLibrary, Workspaces};
+use crate::constants::{
+    DEFAULT_SIDEBAR_WIDTH, DEFAULT_WINDOW_SIZE, DEFAULT_WORKSPACE, MAIN_WINDOW, MAX_SIDEBAR_WIDTH,
+    MIN_SIDEBAR_WIDTH,
+};
+use crate::git_fetch::GitFetcher;
 use crate::git_status::GitStatusCache;
 use crate::hotkeys::handle_keyboard_events;
 use crate::menu::apply_menu_style;
-use crate::system_monitor::{format_kb, SystemMonitor};
+use crate::system_monitor::{format_duration, format_kb, SystemMonitor, UsageAlert, UsageLimits};
 use crate::terminal::TabManager;
-use crate::theme::AppTheme;
+use crate::theme::{AppFonts, AppTheme};
+use crate::ui::sparkline::sparkline;
+use crate::ui::toasts::Toasts;
 use crate::ui::{
-    show_central_panel, show_debug_panel, show_left_panel, show_search_panel, GroupAction,
-    PanelActions, WindowActions, WindowManager,
+    show_central_panel, show_debug_panel, show_left_panel, show_search_panel, window_label,
+    CloseTarget, GroupAction, PanelActions, PendingClose, WindowActions, WindowManager,
 };
 use egui_term::BackendCommand;
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -19,22 +27,32 @@ pub struct App {
     tab_manager: TabManager,
     window_manager: WindowManager,
     recent_projects: RecentProjects,
+    workspaces: Workspaces,
+    /// Name typed in the Projects menu for a new or duplicated workspace.
+    workspace_name_input: String,
     egui_ctx: egui::Context,
     pub show_terminal_lines: bool,
     pub show_fps: bool,
     pub show_sidebar: bool,
+    pub compact_sidebar: bool,
+    /// Width of the expanded sidebar, remembered across restarts.
+    sidebar_width: f32,
     pub show_system_monitor: bool,
     pub show_tab_memory: bool,
     theme: AppTheme,
     cached_terminal_theme: egui_term::TerminalTheme,
     cached_terminal_font: egui_term::TerminalFont,
     git_cache: GitStatusCache,
+    git_fetcher: GitFetcher,
     enable_git_status: bool,
     system_monitor: SystemMonitor,
+    toasts: Toasts,
     /// When the theme settings window is open, this holds the live-preview theme
     /// so that `clear_color` can reflect opacity changes immediately.
     preview_theme: Option<AppTheme>,
     exit_confirmed: bool,
+    /// Programs that do not count as running when a tab is closed.
+    close_confirm_ignore: Vec<String>,
     /// Persisted copy of the last terminal content size [w, h], saved to
     /// settings so new terminals boot at the correct size on cold start.
     last_terminal_layout: Option<[f32; 2]>,
@@ -42,33 +60,56 @@ pub struct App {
     last_terminal_cell_metrics: Option<[f32; 2]>,
     /// Deadline to flush `last_terminal_layout` to disk (debounced during resize).
     terminal_layout_save_at: Option<std::time::Instant>,
+    /// Additional top-level windows, each showing the groups assigned to it.
+    windows: Vec<WindowState>,
+    /// Deadline to save moved or resized windows.
+    windows_save_at: Option<std::time::Instant>,
+    main_window: MainWindowState,
+    /// Whether the main window was checked against its saved monitor.
+    main_window_checked: bool,
+    started_at: std::time::Instant,
+    /// Time from launch to the end of the first frame, for the debug panel.
+    startup_time: Option<Duration>,
+    /// A group accent or icon changed live and is not saved yet.
+    group_edits_unsaved: bool,
 }
 
 fn setup_visuals(ctx: &egui::Context, theme: &AppTheme) {
     // Set both light and dark styles to the same look, then lock the active
-    // theme to Dark. This prevents macOS's light system theme from switching the
-    // UI to white after the first frame.
+    // theme to Dark. This prevents the system theme from switching the UI
+    // after the first frame; light themes bring their own light visuals.
     let visuals = theme.visuals();
     ctx.set_visuals_of(egui::Theme::Dark, visuals.clone());
     ctx.set_visuals_of(egui::Theme::Light, visuals);
     ctx.set_theme(egui::Theme::Dark);
 
-    // Force the native window chrome (title bar / traffic lights) to dark mode on macOS
-    // so it matches the rest of the UI instead of following the system light appearance.
-    ctx.send_viewport_cmd(egui::ViewportCommand::SetTheme(egui::SystemTheme::Dark));
+    // Match the native window chrome (title bar / traffic lights) on macOS to
+    // the theme instead of following the system appearance.
+    let system_theme = if theme.is_light() {
+        egui::SystemTheme::Light
+    } else {
+        egui::SystemTheme::Dark
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::SetTheme(system_theme));
 }
 
 impl App {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let started_at = std::time::Instant::now();
         let settings = Settings::load();
         let theme = settings.theme;
 
-        // Force dark theme on all platforms so the UI stays consistent
+        // Lock the theme on all platforms so the UI stays consistent
         // regardless of the system light/dark appearance.
         setup_visuals(&cc.egui_ctx, &theme);
 
-        // Setup fonts with optional system fallback
-        crate::font_setup::setup_fonts_with_fallback(&cc.egui_ctx);
+        // Setup fonts with optional system fallback, plus the chosen UI and
+        // terminal fonts.
+        let font_error =
+            crate::font_setup::setup_fonts(&cc.egui_ctx, &settings.font_faces, &theme.fonts).err();
+        if let Some(error) = &font_error {
+            log::warn!("{}", error);
+        }
 
         // Apply the configured font sizes on top of the default font definitions.
         theme.fonts.apply(&cc.egui_ctx);
@@ -86,56 +127,116 @@ impl App {
             .last_terminal_cell_metrics
             .map(|[w, h]| egui_term::Size::new(w, h));
 
+        // Agent commands are looked up with the cached login-shell PATH
+        // until this capture replaces it.
+        crate::shell_env::refresh(cc.egui_ctx.clone());
+
+        let workspaces = Workspaces::load();
+        let workspace = settings
+            .workspace
+            .clone()
+            .filter(|name| workspaces.contains(name))
+            .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string());
+        let groups_file = workspaces.groups_file(&workspace);
+
         let tab_manager = TabManager::new(
             command_sender_clone,
             cc,
+            workspace,
+            groups_file,
             settings.default_shell_cmd.clone(),
             settings.agents.clone(),
             settings.run_as_login_shell,
             settings.preload_tabs,
+            settings.preload_limits,
+            settings.lazy_tab_spawn,
             terminal_layout_hint,
             cell_metrics_hint,
         );
 
-        let window_manager = WindowManager::new(
+        let theme_library = ThemeLibrary::load();
+        let theme_name = settings
+            .theme_name
+            .clone()
+            .or_else(|| theme_library.name_of(&theme));
+
+        let mut window_manager = WindowManager::new(
             settings.default_shell_cmd.clone(),
             settings.agents.clone(),
             settings.run_as_login_shell,
             settings.enable_git_status,
             theme,
+            theme_library,
+            theme_name,
+            settings.font_faces.clone(),
             settings.preload_tabs,
+            settings.preload_limits,
+            settings.lazy_tab_spawn,
+            settings.keep_workspaces_alive,
+            settings.git_fetch_interval_secs,
+            settings.git_nested_depth,
+            settings.tab_alerts,
+            &settings.close_confirm_ignore,
         );
+        window_manager.font_error = font_error;
 
         let recent_projects = RecentProjects::load();
 
+        let mut system_monitor = SystemMonitor::new();
+        system_monitor.set_limits(settings.tab_alerts.limits());
+
         let git_cache = GitStatusCache::new(Duration::from_secs(5));
+        git_cache.set_nested_depth(settings.git_nested_depth);
+        let git_fetcher = GitFetcher::new(Duration::from_secs(settings.git_fetch_interval_secs));
 
         let cached_terminal_theme = theme.build_terminal_theme();
 
-        Self {
+        let mut app = Self {
             _command_sender: command_sender,
             command_receiver,
             tab_manager,
             window_manager,
             recent_projects,
+            workspaces,
+            workspace_name_input: String::new(),
             egui_ctx: cc.egui_ctx.clone(),
             show_terminal_lines: settings.show_terminal_lines,
             show_fps: settings.show_fps,
             show_sidebar: settings.show_sidebar,
+            compact_sidebar: settings.compact_sidebar,
+            // A missing settings file yields 0 here (`Settings::default`).
+            sidebar_width: if settings.sidebar_width > 0.0 {
+                settings.sidebar_width
+            } else {
+                DEFAULT_SIDEBAR_WIDTH
+            }
+            .clamp(MIN_SIDEBAR_WIDTH, MAX_SIDEBAR_WIDTH),
             show_system_monitor: settings.show_system_monitor,
             show_tab_memory: settings.show_tab_memory,
             theme,
             cached_terminal_theme,
             cached_terminal_font,
             git_cache,
+            git_fetcher,
             enable_git_status: settings.enable_git_status,
-            system_monitor: SystemMonitor::new(),
+            system_monitor,
+            toasts: Toasts::default(),
             preview_theme: None,
             exit_confirmed: false,
+            close_confirm_ignore: settings.close_confirm_ignore.clone(),
             last_terminal_layout: settings.last_terminal_layout,
             last_terminal_cell_metrics: settings.last_terminal_cell_metrics,
             terminal_layout_save_at: None,
-        }
+            windows: settings.windows.clone(),
+            windows_save_at: None,
+            main_window: settings.main_window,
+            main_window_checked: false,
+            started_at,
+            startup_time: None,
+            group_edits_unsaved: false,
+        };
+        app.sync_windows();
+        app
     }
 
     fn save_settings(&self) {
@@ -143,6 +244,8 @@ impl App {
             show_terminal_lines: self.show_terminal_lines,
             show_fps: self.show_fps,
             show_sidebar: self.show_sidebar,
+            compact_sidebar: self.compact_sidebar,
+            sidebar_width: self.sidebar_width,
             show_system_monitor: self.show_system_monitor,
             show_tab_memory: self.show_tab_memory,
             run_as_login_shell: self.window_manager.editing_run_as_login_shell,
@@ -150,10 +253,22 @@ impl App {
             agents: self.window_manager.editing_agents.clone(),
             legacy_default_agent_cmd: None,
             theme: self.theme,
+            font_faces: self.window_manager.saved_font_faces.clone(),
+            theme_name: self.window_manager.saved_theme_name.clone(),
             enable_git_status: self.window_manager.editing_enable_git_status,
             preload_tabs: self.window_manager.editing_preload_tabs,
+            preload_limits: self.window_manager.editing_preload_limits,
+            lazy_tab_spawn: self.window_manager.editing_lazy_tab_spawn,
+            workspace: Some(self.tab_manager.workspace.clone()),
+            keep_workspaces_alive: self.window_manager.editing_keep_workspaces_alive,
+            git_fetch_interval_secs: self.window_manager.editing_git_fetch_interval_secs,
+            git_nested_depth: self.window_manager.editing_git_nested_depth,
+            tab_alerts: self.window_manager.editing_tab_alerts,
+            close_confirm_ignore: self.close_confirm_ignore.clone(),
             last_terminal_layout: self.last_terminal_layout,
             last_terminal_cell_metrics: self.last_terminal_cell_metrics,
+            main_window: self.main_window,
+            windows: self.windows.clone(),
         };
         settings.save();
     }
@@ -162,11 +277,317 @@ impl App {
         self.recent_projects.save();
     }
 
+    fn switch_workspace(&mut self, name: String) {
+        let groups_file = self.workspaces.groups_file(&name);
+        self.tab_manager.switch_workspace(
+            name,
+            groups_file,
+            self.window_manager.saved_keep_workspaces_alive,
+            self.egui_ctx.clone(),
+        );
+        self.sync_windows();
+        self.save_settings();
+    }
+
+    /// Create a workspace named after the Projects menu input, empty or as a
+    /// copy of the open one, and switch to it.
+    fn create_workspace(&mut self, duplicate: bool) {
+        let result = if duplicate {
+            self.tab_manager.save_groups();
+            self.workspaces
+                .duplicate(&self.tab_manager.workspace, &self.workspace_name_input)
+        } else {
+            self.workspaces.create(&self.workspace_name_input)
+        };
+        match result {
+            Ok(name) => {
+                self.workspace_name_input.clear();
+                self.switch_workspace(name);
+            }
+            Err(error) => self.toasts.push(error),
+        }
+    }
+
+    /// Close the open workspace's terminals, delete it and go back to the
+    /// default workspace.
+    fn delete_workspace(&mut self) {
+        let name = self.tab_manager.workspace.clone();
+        let groups_file = self.workspaces.groups_file(DEFAULT_WORKSPACE);
+        self.tab_manager.switch_workspace(
+            DEFAULT_WORKSPACE.to_string(),
+            groups_file,
+            false,
+            self.egui_ctx.clone(),
+        );
+        if let Err(error) = self.workspaces.delete(&name) {
+            self.toasts.push(error);
+        }
+        self.sync_windows();
+        self.save_settings();
+    }
+
+    /// Reopen the windows that loaded groups are assigned to.
+    fn sync_windows(&mut self) {
+        for group in self.tab_manager.groups.values() {
+            if group.window != MAIN_WINDOW && !self.windows.iter().any(|w| w.id == group.window) {
+                self.windows.push(WindowState {
+                    id: group.window,
+                    pos: None,
+                    size: None,
+                });
+            }
+        }
+    }
+
+    fn open_window(&mut self) -> u64 {
+        let id = self
+            .windows
+            .iter()
+            .map(|w| w.id)
+            .max()
+            .unwrap_or(MAIN_WINDOW)
+            + 1;
+        self.windows.push(WindowState {
+            id,
+            pos: None,
+            size: None,
+        });
+        self.save_settings();
+        id
+    }
+
+    /// Close an additional window; its groups move to the main window.
+    fn close_window(&mut self, window: u64) {
+        self.tab_manager.move_window_groups(window, MAIN_WINDOW);
+        self.tab_manager.save_groups();
+        if self.window_manager.dialog_window == window {
+            self.window_manager.dialog_window = MAIN_WINDOW;
+        }
+        self.windows.retain(|w| w.id != window);
+        self.save_settings();
+    }
+
+    /// Draw an additional window: sidebar, search bar and terminal for the
+    /// groups assigned to it. Dialogs opened from it show in the main window.
+    fn show_extra_window(
+        &mut self,
+        ctx: &egui::Context,
+        window: u64,
+        window_ids: &[u64],
+        theme: &AppTheme,
+    ) {
+        let Some(state) = self.windows.iter().find(|w| w.id == window).copied() else {
+            return;
+        };
+        let mut builder = egui::ViewportBuilder::default()
+            .with_title(format!("YAAA byOrlov — {}", window_label(window)))
+            .with_app_id("yaaa")
+            .with_inner_size(state.size.unwrap_or(DEFAULT_WINDOW_SIZE))
+            .with_min_inner_size([300.0, 220.0]);
+        if let Some(pos) = state.pos {
+            builder = builder.with_position(pos);
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of(("window", window)),
+            builder,
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.close_window(window);
+                    return;
+                }
+                self.track_window_geometry(ctx, window);
+
+                self.tab_manager.focus_window(window);
+                let dialogs_open = self.window_manager.window_dialogs_open();
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE)
+                    .show(ctx, |ui| {
+                        let panel_actions = show_left_panel(
+                            ui,
+                            window,
+                            window_ids,
+                            &self.tab_manager,
+                            &mut self.window_manager,
+                            self.show_sidebar,
+                            self.compact_sidebar,
+                            self.sidebar_width,
+                            &self.tab_manager.agents,
+                            theme,
+                            &mut self.git_cache,
+                            &self.git_fetcher,
+                            self.enable_git_status,
+                            self.show_tab_memory,
+                            &mut self.system_monitor,
+                        );
+                        show_search_panel(ui, &mut self.tab_manager, theme);
+
+                        let (close_tab_id, add_tab_to_group, add_agent_tab_to_group) =
+                            self.handle_keyboard(ctx);
+                        self.handle_panel_actions(ctx, panel_actions);
+                        self.handle_hotkey_tabs(
+                            ctx,
+                            close_tab_id,
+                            add_tab_to_group,
+                            add_agent_tab_to_group,
+                        );
+
+                        self.tab_manager.spawn_active(ctx);
+                        show_central_panel(
+                            ui,
+                            &mut self.tab_manager,
+                            &mut self.window_manager,
+                            theme,
+                            &self.cached_terminal_theme,
+                            &self.cached_terminal_font,
+                        );
+                    });
+                if self.window_manager.window_dialogs_open() > dialogs_open {
+                    self.window_manager.dialog_window = window;
+                }
+                if self.window_manager.dialog_window == window {
+                    let actions = self.show_window_dialogs(ctx);
+                    self.handle_window_actions(actions);
+                }
+                self.tab_manager.focus_window(MAIN_WINDOW);
+            },
+        );
+    }
+
+    /// Draw the dialogs opened from the sidebar or a tab into the window
+    /// of `ctx`.
+    fn show_window_dialogs(&mut self, ctx: &egui::Context) -> WindowActions {
+        let actions = self.window_manager.show_window_dialogs(ctx);
+
+        let processes_root = self
+            .window_manager
+            .processes
+            .tab_id
+            .and_then(|tab_id| self.tab_manager.get_tab(tab_id))
+            .map(|tab| tab.backend.pty_id());
+        self.window_manager
+            .processes
+            .show(ctx, &mut self.system_monitor, processes_root);
+
+        if self.window_manager.ports.open {
+            let tabs: Vec<(u64, String)> = self
+                .tab_manager
+                .groups
+                .values()
+                .flat_map(|g| g.tabs.iter())
+                .map(|t| (t.id, t.display_name.clone()))
+                .collect();
+            self.window_manager
+                .ports
+                .show(ctx, &mut self.system_monitor, &tabs);
+        }
+
+        actions
+    }
+
+    /// The monitor the main window was saved on may be gone. If the window
+    /// opened on a monitor of another size, fit it there; if it opened off
+    /// the monitor, center it.
+    fn fit_main_window_to_monitor(&mut self, ctx: &egui::Context) {
+        if self.main_window_checked {
+            return;
+        }
+        let Some(monitor) = ctx.input(|i| i.viewport().monitor_size) else {
+            return;
+        };
+        self.main_window_checked = true;
+
+        let state = self.main_window;
+        if state.maximized || state.fullscreen {
+            return;
+        }
+        let same_monitor = state.monitor_size.is_none_or(|[width, height]| {
+            (width - monitor.x).abs() < 1.0 && (height - monitor.y).abs() < 1.0
+        });
+        if same_monitor && state.on_monitor([monitor.x, monitor.y]) {
+            return;
+        }
+        let [width, height] = state.size.unwrap_or(DEFAULT_WINDOW_SIZE);
+        let size = egui::vec2(width.min(monitor.x * 0.9), height.min(monitor.y * 0.9));
+        if !same_monitor {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(
+            ((monitor - size) / 2.0).max(egui::Vec2::ZERO).to_pos2(),
+        ));
+    }
+
+    /// Follow the main window so it reopens the same way. The normal
+    /// position and size are kept while it is maximized or fullscreen.
+    fn track_main_window(&mut self, ctx: &egui::Context) {
+        let mut state = self.main_window;
+        ctx.input(|i| {
+            let viewport = i.viewport();
+            state.maximized = viewport.maximized.unwrap_or(state.maximized);
+            state.fullscreen = viewport.fullscreen.unwrap_or(state.fullscreen);
+            if let Some(monitor) = viewport.monitor_size {
+                state.monitor_size = Some([monitor.x, monitor.y]);
+            }
+            if !state.maximized && !state.fullscreen && viewport.minimized != Some(true) {
+                if let Some(rect) = viewport.outer_rect {
+                    state.pos = Some([rect.min.x, rect.min.y]);
+                }
+                if let Some(rect) = viewport.inner_rect {
+                    state.size = Some([rect.width(), rect.height()]);
+                }
+            }
+        });
+        if state != self.main_window {
+            self.main_window = state;
+            self.windows_save_at = Some(std::time::Instant::now());
+        }
+    }
+
+    fn toggle_fullscreen(ctx: &egui::Context) {
+        let fullscreen = ctx.input(|i| i.viewport().fullscreen.unwrap_or(false));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(!fullscreen));
+    }
+
+    fn set_always_on_top(&mut self, ctx: &egui::Context, always_on_top: bool) {
+        self.main_window.always_on_top = always_on_top;
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(if always_on_top {
+            egui::viewport::WindowLevel::AlwaysOnTop
+        } else {
+            egui::viewport::WindowLevel::Normal
+        }));
+        self.save_settings();
+    }
+
+    /// Remember where an additional window is; saved after a short delay.
+    fn track_window_geometry(&mut self, ctx: &egui::Context, window: u64) {
+        let (pos, size) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (
+                viewport.outer_rect.map(|r| [r.min.x, r.min.y]),
+                viewport.inner_rect.map(|r| [r.width(), r.height()]),
+            )
+        });
+        let Some(state) = self.windows.iter_mut().find(|w| w.id == window) else {
+            return;
+        };
+        let pos = pos.or(state.pos);
+        let size = size.or(state.size);
+        if pos != state.pos || size != state.size {
+            state.pos = pos;
+            state.size = size;
+            self.windows_save_at = Some(std::time::Instant::now());
+        }
+    }
+
     fn handle_command_events(&mut self) {
         while let Ok((tab_id, event)) = self.command_receiver.try_recv() {
             match event {
                 egui_term::PtyEvent::Exit => {
-                    self.tab_manager.remove(tab_id);
+                    // A tab killed for exceeding a resource limit stays open
+                    // so the sidebar can say why it died.
+                    if self.system_monitor.exit_limit_kill(tab_id).is_none() {
+                        self.tab_manager.remove(tab_id);
+                    }
                     self.tab_manager.remove_preload_tab(tab_id);
                 }
                 egui_term::PtyEvent::Title(title) => {
@@ -237,6 +658,10 @@ impl App {
             }
         }
 
+        if events.toggle_fullscreen {
+            Self::toggle_fullscreen(ctx);
+        }
+
         if events.toggle_search {
             if let Some(tab) = self.tab_manager.get_active() {
                 tab.search_active = !tab.search_active;
@@ -255,6 +680,31 @@ impl App {
         )
     }
 
+    /// Open or close the tabs requested by hotkeys.
+    fn handle_hotkey_tabs(
+        &mut self,
+        ctx: &egui::Context,
+        close_tab_id: Option<u64>,
+        add_tab_to_group: Option<u64>,
+        add_agent_tab_to_group: Vec<(u64, usize)>,
+    ) {
+        if let Some(tab_id) = close_tab_id {
+            self.request_close(CloseTarget::Tab(tab_id));
+        }
+
+        if let Some(group_id) = add_tab_to_group {
+            self.tab_manager
+                .add_tab_to_group(group_id, ctx.clone(), None);
+            self.tab_manager.save_groups();
+        }
+
+        for (group_id, agent_index) in add_agent_tab_to_group {
+            self.tab_manager
+                .add_tab_to_group(group_id, ctx.clone(), Some(agent_index));
+            self.tab_manager.save_groups();
+        }
+    }
+
     fn handle_panel_actions(
         &mut self,
         ctx: &egui::Context,
@@ -283,26 +733,181 @@ impl App {
             self.tab_manager.save_groups();
         }
 
+        // Remember a resized sidebar once the drag is over, not on every frame of it.
+        if let Some(width) = actions.sidebar_width {
+            if (width - self.sidebar_width).abs() >= 1.0 && !ctx.input(|i| i.pointer.any_down()) {
+                self.sidebar_width = width;
+                self.save_settings();
+            }
+        }
+
         for (group_id, action) in actions.group_actions {
             match action {
                 GroupAction::RemoveGroup => {
-                    if let Some(group) = self.tab_manager.groups.get(&group_id) {
-                        self.recent_projects
-                            .add_project(group.name.clone(), group.path.clone());
-                        self.save_recent_projects();
-                    }
-                    self.tab_manager.remove_group(group_id);
+                    self.request_close(CloseTarget::Group(group_id));
+                }
+                GroupAction::SetAutoFetch(auto_fetch) => {
+                    self.tab_manager.set_group_auto_fetch(group_id, auto_fetch);
+                    self.tab_manager.save_groups();
+                }
+                GroupAction::SetAccent(accent) => {
+                    self.tab_manager.set_group_accent(group_id, accent);
+                    self.group_edits_unsaved = true;
+                }
+                GroupAction::SetIcon(icon) => {
+                    self.tab_manager.set_group_icon(group_id, icon);
+                    self.group_edits_unsaved = true;
+                }
+                GroupAction::SetCollapsed(collapsed) => {
+                    self.tab_manager.set_group_collapsed(group_id, collapsed);
+                    self.tab_manager.save_groups();
+                }
+                GroupAction::MoveToWindow(target) => {
+                    let window = target.unwrap_or_else(|| self.open_window());
+                    self.tab_manager.move_group_to_window(group_id, window);
+                    self.tab_manager.save_groups();
+                }
+                GroupAction::MoveTabToWindow(tab_id, target) => {
+                    let window = target.unwrap_or_else(|| self.open_window());
+                    self.tab_manager.move_tab_to_window(tab_id, window);
+                    self.tab_manager.save_groups();
+                }
+                GroupAction::SetTabPinned(tab_id, pinned) => {
+                    self.tab_manager.set_tab_pinned(tab_id, pinned);
                     self.tab_manager.save_groups();
                 }
                 GroupAction::SelectTab(tab_id) => {
                     self.tab_manager.set_active_tab(tab_id);
                 }
                 GroupAction::RemoveTab(tab_id) => {
-                    self.tab_manager.remove(tab_id);
-                    self.tab_manager.save_groups();
+                    self.request_close(CloseTarget::Tab(tab_id));
                 }
             }
         }
+
+        // Accent drags and icon typing apply every frame but are saved once
+        // they end, or once the menu closes with the icon field still focused.
+        if self.group_edits_unsaved && (actions.save_groups || !egui::Popup::is_any_open(ctx)) {
+            self.group_edits_unsaved = false;
+            self.tab_manager.save_groups();
+        }
+    }
+
+    /// One line per process that closing `tab_ids` would terminate, e.g.
+    /// "2. Terminal: npm run dev".
+    fn busy_commands(&mut self, tab_ids: &[u64]) -> Vec<String> {
+        const MAX_CHARS: usize = 100;
+        let mut commands = Vec::new();
+        for info in self.tab_manager.groups.values().flat_map(|g| g.tabs.iter()) {
+            if !tab_ids.contains(&info.id) {
+                continue;
+            }
+            let Some(tab) = self.tab_manager.get_tab(info.id) else {
+                continue;
+            };
+            let processes = self.system_monitor.busy_processes(
+                tab.backend.pty_id(),
+                info.is_agent,
+                &self.close_confirm_ignore,
+            );
+            for process in processes {
+                let mut cmd: String = process.cmd.chars().take(MAX_CHARS).collect();
+                if cmd.len() < process.cmd.len() {
+                    cmd.push('…');
+                }
+                commands.push(format!("{}: {}", info.display_name, cmd));
+            }
+        }
+        commands
+    }
+
+    /// Close a tab or group right away if nothing runs in it besides idle
+    /// shells, otherwise ask first.
+    fn request_close(&mut self, target: CloseTarget) {
+        let (name, tab_ids): (String, Vec<u64>) = match target {
+            CloseTarget::Tab(tab_id) => {
+                let name = self
+                    .tab_manager
+                    .groups
+                    .values()
+                    .flat_map(|g| g.tabs.iter())
+                    .find(|t| t.id == tab_id)
+                    .map(|t| t.display_name.clone())
+                    .unwrap_or_default();
+                (name, vec![tab_id])
+            }
+            CloseTarget::Group(group_id) => match self.tab_manager.groups.get(&group_id) {
+                Some(group) => (group.name.clone(), group.tabs.iter().map(|t| t.id).collect()),
+                None => return,
+            },
+        };
+
+        let commands = self.busy_commands(&tab_ids);
+        if commands.is_empty() {
+            self.close_target(target);
+        } else {
+            self.window_manager.pending_close = Some(PendingClose {
+                target,
+                name,
+                commands,
+            });
+        }
+    }
+
+    fn close_target(&mut self, target: CloseTarget) {
+        match target {
+            CloseTarget::Tab(tab_id) => {
+                self.tab_manager.remove(tab_id);
+            }
+            CloseTarget::Group(group_id) => {
+                if let Some(group) = self.tab_manager.groups.get(&group_id) {
+                    self.recent_projects
+                        .add_project(group.name.clone(), group.path.clone());
+                    self.save_recent_projects();
+                }
+                self.tab_manager.remove_group(group_id);
+            }
+        }
+        self.tab_manager.save_groups();
+    }
+
+    fn apply_theme(&mut self, theme: AppTheme) {
+        self.theme = theme;
+        self.preview_theme = None;
+        let ctx = self.egui_ctx.clone();
+        self.rebuild_terminal_cache(&ctx);
+        setup_visuals(&self.egui_ctx, &self.theme);
+        self.theme.fonts.apply(&self.egui_ctx);
+        self.egui_ctx.request_repaint();
+        self.window_manager.last_applied_opacity = self.theme.app_bg_opacity;
+    }
+
+    /// Apply and save a theme library entry straight from the menu. The
+    /// current font sizes are kept.
+    fn switch_theme(&mut self, name: String, mut theme: AppTheme) {
+        theme.fonts = self.theme.fonts;
+        let wm = &mut self.window_manager;
+        wm.editing_theme = theme;
+        wm.saved_theme = theme;
+        wm.editing_theme_name = Some(name.clone());
+        wm.saved_theme_name = Some(name);
+        self.apply_theme(theme);
+        self.save_settings();
+    }
+
+    /// Apply font sizes, spacing and the font faces being edited in the font
+    /// settings window.
+    fn apply_fonts(&mut self, fonts: AppFonts) {
+        self.theme.fonts = fonts;
+        let faces = &self.window_manager.editing_font_faces;
+        self.window_manager.font_error =
+            crate::font_setup::setup_fonts(&self.egui_ctx, faces, &fonts).err();
+        let ctx = self.egui_ctx.clone();
+        self.rebuild_terminal_cache(&ctx);
+        self.theme.fonts.apply(&self.egui_ctx);
+        // The new font definitions are only used from the next frame on;
+        // measure the terminal cells again then.
+        self.last_terminal_cell_metrics = None;
     }
 
     fn rebuild_terminal_cache(&mut self, ctx: &egui::Context) {
@@ -325,6 +930,14 @@ impl App {
             .map(|g| g.path.clone())
             .collect();
         self.git_cache.retain(|p| paths.iter().any(|q| q == p));
+
+        let fetch_targets = self
+            .tab_manager
+            .groups
+            .values()
+            .filter(|g| self.enable_git_status && g.auto_fetch)
+            .map(|g| g.path.clone());
+        self.git_fetcher.set_targets(fetch_targets);
     }
 
     fn handle_window_actions(&mut self, actions: WindowActions) {
@@ -342,8 +955,7 @@ impl App {
         }
 
         if let Some(agents) = actions.agents {
-            self.tab_manager
-                .set_agents(agents, self.egui_ctx.clone());
+            self.tab_manager.set_agents(agents);
         }
 
         if let Some(run_as_login_shell) = actions.run_as_login_shell {
@@ -354,27 +966,48 @@ impl App {
             self.enable_git_status = enable_git_status;
         }
 
+        if let Some(secs) = actions.git_fetch_interval_secs {
+            self.git_fetcher.set_interval(Duration::from_secs(secs));
+        }
+
+        if let Some(ignore) = actions.close_confirm_ignore {
+            self.close_confirm_ignore = ignore;
+        }
+
+        if let Some(target) = actions.close_target_confirmed {
+            self.close_target(target);
+        }
+
+        if let Some(tab_alerts) = actions.tab_alerts {
+            self.system_monitor.set_limits(tab_alerts.limits());
+        }
+
+        if let Some(depth) = actions.git_nested_depth {
+            self.git_cache.set_nested_depth(depth);
+        }
+
+        if let Some(path) = actions.refresh_git_path {
+            self.git_cache.refresh(&path);
+        }
+
         if let Some(preload_tabs) = actions.preload_tabs {
-            self.tab_manager
-                .set_preload_enabled(preload_tabs, self.egui_ctx.clone());
+            self.tab_manager.set_preload_enabled(preload_tabs);
+        }
+
+        if let Some(preload_limits) = actions.preload_limits {
+            self.tab_manager.set_preload_limits(preload_limits);
+        }
+
+        if let Some(lazy_tab_spawn) = actions.lazy_tab_spawn {
+            self.tab_manager.set_lazy_spawn(lazy_tab_spawn);
         }
 
         if let Some(theme) = actions.theme {
-            self.theme = theme;
-            self.preview_theme = None;
-            let ctx = self.egui_ctx.clone();
-            self.rebuild_terminal_cache(&ctx);
-            setup_visuals(&self.egui_ctx, &self.theme);
-            self.theme.fonts.apply(&self.egui_ctx);
-            self.egui_ctx.request_repaint();
-            self.window_manager.last_applied_opacity = self.theme.app_bg_opacity;
+            self.apply_theme(theme);
         }
 
         if let Some(fonts) = actions.fonts {
-            self.theme.fonts = fonts;
-            let ctx = self.egui_ctx.clone();
-            self.rebuild_terminal_cache(&ctx);
-            self.theme.fonts.apply(&self.egui_ctx);
+            self.apply_fonts(fonts);
         }
 
         if actions.should_save_settings {
@@ -406,6 +1039,13 @@ impl eframe::App for App {
             if self.exit_confirmed {
                 return;
             }
+            let all_tabs: Vec<u64> = self
+                .tab_manager
+                .groups
+                .values()
+                .flat_map(|g| g.tabs.iter().map(|t| t.id))
+                .collect();
+            self.window_manager.exit_commands = self.busy_commands(&all_tabs);
             self.window_manager.show_close_confirmation = true;
             ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
         }
@@ -432,6 +1072,7 @@ impl eframe::App for App {
         // is cheap after the first call). Both values are copied out so there
         // is no lingering borrow of `system_monitor` inside the UI closures.
         let mem_percent = self.system_monitor.memory().percent;
+        self.tab_manager.maintain_preload_pool(&ctx, mem_percent);
         let total_tabs_kb: u64 = {
             let tm = &self.tab_manager;
             let sm = &mut self.system_monitor;
@@ -443,12 +1084,83 @@ impl eframe::App for App {
                 .sum()
         };
 
+        // Track the tabs' usage and announce tabs that just crossed a limit.
+        let tab_pids: Vec<(u64, u32, Option<UsageLimits>)> = self
+            .tab_manager
+            .groups
+            .values()
+            .flat_map(|g| g.tabs.iter())
+            .filter_map(|t| {
+                let alerts = t
+                    .agent_index
+                    .and_then(|i| self.tab_manager.agents.get(i)?.alerts)
+                    .map(|alerts| alerts.limits());
+                self.tab_manager
+                    .get_tab(t.id)
+                    .map(|tab| (t.id, tab.backend.pty_id(), alerts))
+            })
+            .collect();
+        let limited_tabs: Vec<_> = self
+            .tab_manager
+            .groups
+            .values()
+            .flat_map(|g| g.tabs.iter())
+            .filter_map(|t| {
+                let tab = self.tab_manager.get_tab(t.id)?;
+                (!tab.limits.is_empty())
+                    .then(|| (t.id, tab.backend.pty_id(), tab.limits, tab.memory.clone()))
+            })
+            .collect();
+        self.system_monitor.enforce_limits(&limited_tabs);
+
+        for (tab_id, alert) in self.system_monitor.track_tabs(&tab_pids) {
+            let limits = self.system_monitor.tab_limits(tab_id);
+            let name = self
+                .tab_manager
+                .groups
+                .values()
+                .flat_map(|g| g.tabs.iter())
+                .find(|t| t.id == tab_id)
+                .map(|t| t.display_name.clone())
+                .unwrap_or_default();
+            let what = match alert {
+                UsageAlert::Memory => format!("more than {}", format_kb(limits.memory_kb)),
+                UsageAlert::Cpu => format!("more than {:.0}% CPU", limits.cpu_percent),
+            };
+            self.toasts.push(format!(
+                "⚠ \"{}\" has been using {} for over {}",
+                name,
+                what,
+                format_duration(limits.sustained.as_secs())
+            ));
+        }
+        let total_memory_kb = self.system_monitor.total_memory_kb().max(1);
+        let system_history = self.system_monitor.system_history();
+        let system_memory: Vec<f32> = system_history
+            .samples()
+            .map(|s| s.memory_kb as f32 / total_memory_kb as f32 * 100.0)
+            .collect();
+        let system_cpu: Vec<f32> = system_history.samples().map(|s| s.cpu_percent).collect();
+
         egui::Panel::top("menu_bar")
             .frame(egui::Frame {
                 fill: theme.app_bg_with_opacity(),
                 ..Default::default()
             })
             .show_inside(ui, |ui| {
+                // Accent strip of the active project along the top edge.
+                if let Some(group) = self
+                    .tab_manager
+                    .active_group_id
+                    .and_then(|id| self.tab_manager.groups.get(&id))
+                {
+                    let rect = ui.max_rect();
+                    ui.painter().rect_filled(
+                        egui::Rect::from_min_size(rect.left_top(), egui::vec2(rect.width(), 3.0)),
+                        0.0,
+                        group.accent_color(),
+                    );
+                }
                 ui.add_space(4.0);
                 ui.vertical(|ui| {
                     ui.add_space(2.0);
@@ -477,6 +1189,7 @@ impl eframe::App for App {
                                     ui.close();
                                 }
                             });
+                            self.window_manager.workspace_name_focused = false;
                             ui.menu_button("Projects", |ui| {
                                 apply_menu_style(ui, theme.fonts.ui_font_size);
 
@@ -496,6 +1209,71 @@ impl eframe::App for App {
                                     ui.close();
                                 }
 
+                                if ui.button("🪟 New window").clicked() {
+                                    self.open_window();
+                                    ui.close();
+                                }
+
+                                ui.menu_button(
+                                    format!("🗂 Workspace: {}", self.tab_manager.workspace),
+                                    |ui| {
+                                        apply_menu_style(ui, theme.fonts.ui_font_size);
+
+                                        for name in self.workspaces.names.clone() {
+                                            let current = name == self.tab_manager.workspace;
+                                            if ui.selectable_label(current, &name).clicked() {
+                                                self.switch_workspace(name);
+                                                ui.close();
+                                            }
+                                        }
+
+                                        ui.separator();
+
+                                        let response = ui.add(
+                                            egui::TextEdit::singleline(
+                                                &mut self.workspace_name_input,
+                                            )
+                                            .hint_text("Workspace name")
+                                            .desired_width(160.0),
+                                        );
+                                        self.window_manager.workspace_name_focused =
+                                            response.has_focus();
+                                        ui.horizontal(|ui| {
+                                            if ui.button("➕ New").clicked() {
+                                                self.create_workspace(false);
+                                                ui.close();
+                                            }
+                                            if ui
+                                                .button("⧉ Duplicate")
+                                                .on_hover_text(
+                                                    "New workspace with the current projects",
+                                                )
+                                                .clicked()
+                                            {
+                                                self.create_workspace(true);
+                                                ui.close();
+                                            }
+                                        });
+
+                                        if self.tab_manager.workspace != DEFAULT_WORKSPACE {
+                                            ui.separator();
+                                            if ui
+                                                .button(format!(
+                                                    "🗑 Delete \"{}\"",
+                                                    self.tab_manager.workspace
+                                                ))
+                                                .on_hover_text(
+                                                    "Close its terminals and delete the workspace",
+                                                )
+                                                .clicked()
+                                            {
+                                                self.delete_workspace();
+                                                ui.close();
+                                            }
+                                        }
+                                    },
+                                );
+
                                 ui.separator();
 
                                 let opened_paths: std::collections::HashSet<_> = self
@@ -535,6 +1313,25 @@ impl eframe::App for App {
                                     ui.close();
                                 }
 
+                                ui.menu_button("🌓 Switch theme", |ui| {
+                                    apply_menu_style(ui, theme.fonts.ui_font_size);
+                                    let themes: Vec<_> = self
+                                        .window_manager
+                                        .theme_library
+                                        .themes
+                                        .iter()
+                                        .map(|t| (t.name.clone(), t.theme))
+                                        .collect();
+                                    for (name, library_theme) in themes {
+                                        let active = self.window_manager.saved_theme_name.as_ref()
+                                            == Some(&name);
+                                        if ui.selectable_label(active, &name).clicked() {
+                                            self.switch_theme(name, library_theme);
+                                            ui.close();
+                                        }
+                                    }
+                                });
+
                                 if ui.button("🔤 Fonts").clicked() {
                                     self.window_manager.show_font_settings = true;
                                     ui.close();
@@ -542,6 +1339,30 @@ impl eframe::App for App {
 
                                 ui.separator();
 
+                                let fullscreen = self.main_window.fullscreen;
+                                let fullscreen_label = if fullscreen {
+                                    "⛶ Exit fullscreen"
+                                } else {
+                                    "⛶ Fullscreen"
+                                };
+                                if ui.button(fullscreen_label).clicked() {
+                                    Self::toggle_fullscreen(&ctx);
+                                    ui.close();
+                                }
+
+                                let always_on_top = self.main_window.always_on_top;
+                                let on_top_label = if always_on_top {
+                                    "📌 Stop keeping on top"
+                                } else {
+                                    "📌 Always on top"
+                                };
+                                if ui.button(on_top_label).clicked() {
+                                    self.set_always_on_top(&ctx, !always_on_top);
+                                    ui.close();
+                                }
+
+                                ui.separator();
+
                                 let git_status_label = if self.enable_git_status {
                                     "🔀 Hide git status"
                                 } else {
@@ -565,8 +1386,7 @@ impl eframe::App for App {
                                     let new_state = !self.window_manager.editing_preload_tabs;
                                     self.window_manager.editing_preload_tabs = new_state;
                                     self.window_manager.saved_preload_tabs = new_state;
-                                    self.tab_manager
-                                        .set_preload_enabled(new_state, ctx.clone());
+                                    self.tab_manager.set_preload_enabled(new_state);
                                     self.save_settings();
                                     ui.close();
                                 }
@@ -645,6 +1465,23 @@ impl eframe::App for App {
                                         self.save_settings();
                                     }
 
+                                    if self.show_sidebar {
+                                        let compact_text = if self.compact_sidebar {
+                                            "▶ Expand"
+                                        } else {
+                                            "◀ Compact"
+                                        };
+                                        if ui
+                                            .button(compact_text)
+                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                            .on_hover_text("Show only project icons and status badges")
+                                            .clicked()
+                                        {
+                                            self.compact_sidebar = !self.compact_sidebar;
+                                            self.save_settings();
+                                        }
+                                    }
+
                                     if self.show_system_monitor {
                                         ui.add_space(16.0);
                                         ui.label(format!("Total: {}", format_kb(total_tabs_kb)));
@@ -654,13 +1491,39 @@ impl eframe::App for App {
                                             .button(ram_label)
                                             .on_hover_cursor(egui::CursorIcon::PointingHand)
                                             .on_hover_text(
-                                                "Click to toggle per-tab memory next to tab names",
+                                                "Click to toggle per-tab memory and CPU next to tab names",
                                             )
                                             .clicked()
                                         {
                                             self.show_tab_memory = !self.show_tab_memory;
                                             self.save_settings();
                                         }
+
+                                        // Right-to-left layout: CPU ends up left of RAM.
+                                        let size = egui::vec2(60.0, 16.0);
+                                        sparkline(
+                                            ui,
+                                            &system_memory,
+                                            100.0,
+                                            None,
+                                            egui::Color32::from_rgb(0x42, 0xa5, 0xf5),
+                                            size,
+                                        )
+                                        .on_hover_text("System memory, last 2 minutes");
+                                        ui.add_space(8.0);
+                                        ui.label(format!(
+                                            "CPU: {:.0}%",
+                                            system_cpu.last().copied().unwrap_or(0.0)
+                                        ));
+                                        sparkline(
+                                            ui,
+                                            &system_cpu,
+                                            100.0,
+                                            None,
+                                            egui::Color32::from_rgb(0x4c, 0xaf, 0x50),
+                                            size,
+                                        )
+                                        .on_hover_text("System CPU, last 2 minutes");
                                     }
                                 },
                             );
@@ -671,15 +1534,31 @@ impl eframe::App for App {
             });
 
         let window_actions = self.window_manager.show(&ctx);
+        let dialog_actions = (self.window_manager.dialog_window == MAIN_WINDOW)
+            .then(|| self.show_window_dialogs(&ctx));
+        self.toasts.show(&ctx);
+
+        self.fit_main_window_to_monitor(&ctx);
+        self.track_main_window(&ctx);
 
+        self.tab_manager.focus_window(MAIN_WINDOW);
+        let window_ids: Vec<u64> = std::iter::once(MAIN_WINDOW)
+            .chain(self.windows.iter().map(|w| w.id))
+            .collect();
+        let dialogs_open = self.window_manager.window_dialogs_open();
         let panel_actions = show_left_panel(
             ui,
+            MAIN_WINDOW,
+            &window_ids,
             &self.tab_manager,
             &mut self.window_manager,
             self.show_sidebar,
+            self.compact_sidebar,
+            self.sidebar_width,
             &self.tab_manager.agents,
             &theme,
             &mut self.git_cache,
+            &self.git_fetcher,
             self.enable_git_status,
             self.show_tab_memory,
             &mut self.system_monitor,
@@ -690,6 +1569,7 @@ impl eframe::App for App {
             self.show_fps,
             self.show_terminal_lines,
             &mut self.tab_manager,
+            self.startup_time,
             &theme,
         );
 
@@ -703,38 +1583,42 @@ impl eframe::App for App {
         self.handle_panel_actions(&ctx, panel_actions);
 
         if window_actions.close_confirmed {
+            self.save_settings();
             self.tab_manager.clear();
             self.exit_confirmed = true;
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
         }
 
         self.handle_window_actions(window_actions);
-
-        if let Some(tab_id) = close_tab_id {
-            self.tab_manager.remove(tab_id);
-            self.tab_manager.save_groups();
+        if let Some(actions) = dialog_actions {
+            self.handle_window_actions(actions);
         }
 
-        if let Some(group_id) = add_tab_to_group {
-            self.tab_manager
-                .add_tab_to_group(group_id, ctx.clone(), None);
-            self.tab_manager.save_groups();
-        }
-
-        for (group_id, agent_index) in add_agent_tab_to_group {
-            self.tab_manager
-                .add_tab_to_group(group_id, ctx.clone(), Some(agent_index));
-            self.tab_manager.save_groups();
-        }
+        self.handle_hotkey_tabs(&ctx, close_tab_id, add_tab_to_group, add_agent_tab_to_group);
 
+        self.tab_manager.spawn_active(&ctx);
         show_central_panel(
             ui,
             &mut self.tab_manager,
-            &self.window_manager,
+            &mut self.window_manager,
             &theme,
             &self.cached_terminal_theme,
             &self.cached_terminal_font,
         );
+        if self.window_manager.window_dialogs_open() > dialogs_open {
+            self.window_manager.dialog_window = MAIN_WINDOW;
+        }
+
+        for &window in &window_ids[1..] {
+            self.show_extra_window(&ctx, window, &window_ids, &theme);
+        }
+        if self
+            .windows_save_at
+            .is_some_and(|when| when.elapsed() > Duration::from_millis(600))
+        {
+            self.windows_save_at = None;
+            self.save_settings();
+        }
 
         // Lazily compute real font cell metrics on the first frame (egui fonts
         // are not available during App::new) and seed the terminal hint from
@@ -769,6 +1653,10 @@ impl eframe::App for App {
         // The service compares paths internally and does nothing if nothing changed.
         self.sync_git_paths();
 
+        if self.startup_time.is_none() {
+            self.startup_time = Some(self.started_at.elapsed());
+        }
+
         // The terminal backend updates its state on a background PTY thread, but
         // alacritty_terminal does not emit events for ordinary screen output.
         // Without a pending repaint request egui/eframe on macOS goes to sleep
@@ -788,20 +1676,3 @@ impl eframe::App for App {
         }
     }
 }
-
-impl AppTheme {
-    /// Build egui visuals from this theme. Used during startup and after
-    /// restoring defaults.
-    fn visuals(&self) -> egui::Visuals {
-        let mut visuals = egui::Visuals::dark();
-        let app_bg = self.app_bg_with_opacity();
-        visuals.panel_fill = app_bg;
-        visuals.window_fill = app_bg;
-        visuals.widgets.inactive.bg_fill = app_bg;
-        visuals.widgets.noninteractive.bg_fill = app_bg;
-        visuals.override_text_color = Some(self.panel_text);
-        visuals.selection.bg_fill = self.tab_active_bg;
-        visuals.selection.stroke.color = self.tab_active_bg;
-        visuals
-    }
-}
@@ -2,9 +2,13 @@ use std::path::PathBuf;
 
 pub mod recent_projects;
 pub mod settings;
+pub mod themes;
+pub mod workspaces;
 
 pub use recent_projects::RecentProjects;
 pub use settings::Settings;
+pub use themes::ThemeLibrary;
+pub use workspaces::Workspaces;
 
 pub fn config_dir() -> Option<PathBuf> {
     dirs::config_dir().map(|mut path| {
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
 use crate::constants::*;
-use crate::theme::AppTheme;
+use crate::resource_limits::ResourceLimits;
+use crate::system_monitor::UsageLimits;
+use crate::theme::{AppTheme, FontFaces};
 
 pub const MAX_AGENTS: usize = crate::constants::MAX_AGENTS;
 
@@ -13,6 +15,36 @@ pub struct AgentConfig {
     pub cmd: String,
     #[serde(default = "default_agent_enabled")]
     pub enabled: bool,
+    #[serde(default)]
+    pub limits: AgentLimits,
+    /// Usage alert thresholds of this agent's tabs, instead of the global
+    /// ones when set.
+    #[serde(default)]
+    pub alerts: Option<TabAlertSettings>,
+    /// Keep a warm tab of this agent in the preload pool.
+    #[serde(default = "default_agent_preload")]
+    pub preload: bool,
+}
+
+/// Resource limits for the processes of an agent's tabs. 0 means no limit.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct AgentLimits {
+    #[serde(default)]
+    pub memory_mb: u64,
+    #[serde(default)]
+    pub cpu_time_secs: u64,
+    #[serde(default)]
+    pub open_files: u64,
+}
+
+impl AgentLimits {
+    pub fn limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            memory_kb: self.memory_mb * 1024,
+            cpu_time_secs: self.cpu_time_secs,
+            open_files: self.open_files,
+        }
+    }
 }
 
 fn default_agent_name() -> String {
@@ -27,6 +59,10 @@ fn default_agent_enabled() -> bool {
     false
 }
 
+fn default_agent_preload() -> bool {
+    true
+}
+
 impl AgentConfig {
     pub fn default_for_index(index: usize) -> Self {
         let name = match index {
@@ -46,6 +82,9 @@ impl AgentConfig {
             name,
             cmd,
             enabled: index == 0,
+            limits: AgentLimits::default(),
+            alerts: None,
+            preload: true,
         }
     }
 }
@@ -59,6 +98,113 @@ fn default_agents() -> [AgentConfig; MAX_AGENTS] {
     ]
 }
 
+/// Per-tab resource thresholds, global or of one agent's tabs. A tab whose
+/// process tree stays above a limit for `duration_secs` is highlighted and
+/// raises a notification. A limit of 0 disables that check. The memory and
+/// CPU next to tab names turn yellow above half of their limit and red above
+/// it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TabAlertSettings {
+    #[serde(default = "default_tab_alert_memory_mb")]
+    pub memory_mb: u64,
+    #[serde(default = "default_tab_alert_cpu_percent")]
+    pub cpu_percent: u32,
+    #[serde(default = "default_tab_alert_duration_secs")]
+    pub duration_secs: u64,
+}
+
+impl Default for TabAlertSettings {
+    fn default() -> Self {
+        Self {
+            memory_mb: DEFAULT_TAB_ALERT_MEMORY_MB,
+            cpu_percent: DEFAULT_TAB_ALERT_CPU_PERCENT,
+            duration_secs: DEFAULT_TAB_ALERT_DURATION_SECS,
+        }
+    }
+}
+
+impl TabAlertSettings {
+    pub fn limits(&self) -> UsageLimits {
+        UsageLimits {
+            memory_kb: self.memory_mb * 1024,
+            cpu_percent: self.cpu_percent as f32,
+            sustained: std::time::Duration::from_secs(self.duration_secs),
+        }
+    }
+}
+
+/// Bounds on the preload pool. Only the `recent_groups` most recently used
+/// groups get preloaded tabs, at most `max_tabs` in all, and a group unused
+/// for `idle_mins` loses them. Nothing new is preloaded while system memory
+/// use is above `max_memory_percent`. 0 turns the idle and memory checks off.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PreloadLimits {
+    #[serde(default = "default_preload_max_tabs")]
+    pub max_tabs: usize,
+    #[serde(default = "default_preload_recent_groups")]
+    pub recent_groups: usize,
+    #[serde(default = "default_preload_idle_mins")]
+    pub idle_mins: u64,
+    #[serde(default = "default_preload_max_memory_percent")]
+    pub max_memory_percent: u32,
+}
+
+impl Default for PreloadLimits {
+    fn default() -> Self {
+        Self {
+            max_tabs: DEFAULT_PRELOAD_MAX_TABS,
+            recent_groups: DEFAULT_PRELOAD_RECENT_GROUPS,
+            idle_mins: DEFAULT_PRELOAD_IDLE_MINS,
+            max_memory_percent: DEFAULT_PRELOAD_MAX_MEMORY_PERCENT,
+        }
+    }
+}
+
+impl PreloadLimits {
+    pub fn idle_timeout(&self) -> Option<std::time::Duration> {
+        (self.idle_mins > 0).then(|| std::time::Duration::from_secs(self.idle_mins * 60))
+    }
+
+    pub fn memory_exceeded(&self, memory_percent: f32) -> bool {
+        self.max_memory_percent > 0 && memory_percent > self.max_memory_percent as f32
+    }
+}
+
+fn default_preload_max_tabs() -> usize {
+    DEFAULT_PRELOAD_MAX_TABS
+}
+
+fn default_preload_recent_groups() -> usize {
+    DEFAULT_PRELOAD_RECENT_GROUPS
+}
+
+fn default_preload_idle_mins() -> u64 {
+    DEFAULT_PRELOAD_IDLE_MINS
+}
+
+fn default_preload_max_memory_percent() -> u32 {
+    DEFAULT_PRELOAD_MAX_MEMORY_PERCENT
+}
+
+fn default_close_confirm_ignore() -> Vec<String> {
+    DEFAULT_CLOSE_CONFIRM_IGNORE
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_tab_alert_memory_mb() -> u64 {
+    DEFAULT_TAB_ALERT_MEMORY_MB
+}
+
+fn default_tab_alert_cpu_percent() -> u32 {
+    DEFAULT_TAB_ALERT_CPU_PERCENT
+}
+
+fn default_tab_alert_duration_secs() -> u64 {
+    DEFAULT_TAB_ALERT_DURATION_SECS
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct Settings {
     #[serde(default = "default_show_terminal_lines")]
@@ -67,6 +213,11 @@ pub struct Settings {
     pub show_fps: bool,
     #[serde(default = "default_show_sidebar")]
     pub show_sidebar: bool,
+    /// Sidebar shows only project icons and status badges.
+    #[serde(default)]
+    pub compact_sidebar: bool,
+    #[serde(default = "default_sidebar_width")]
+    pub sidebar_width: f32,
     #[serde(default = "default_show_system_monitor")]
     pub show_system_monitor: bool,
     #[serde(default = "default_show_tab_memory")]
@@ -83,10 +234,40 @@ pub struct Settings {
     pub legacy_default_agent_cmd: Option<String>,
     #[serde(default = "default_theme")]
     pub theme: AppTheme,
+    #[serde(default)]
+    pub font_faces: FontFaces,
+    /// Theme library entry `theme` was last picked from or saved as. `None`
+    /// for settings written before the library existed.
+    #[serde(default)]
+    pub theme_name: Option<String>,
     #[serde(default = "default_enable_git_status")]
     pub enable_git_status: bool,
     #[serde(default = "default_preload_tabs")]
     pub preload_tabs: bool,
+    #[serde(default)]
+    pub preload_limits: PreloadLimits,
+    /// Start restored tabs when they are first opened instead of at launch.
+    #[serde(default = "default_lazy_tab_spawn")]
+    pub lazy_tab_spawn: bool,
+    /// Workspace to reopen on launch; `None` is the default workspace.
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// Keep the terminals of a workspace running after switching away.
+    #[serde(default = "default_keep_workspaces_alive")]
+    pub keep_workspaces_alive: bool,
+    /// Interval between background `git fetch` runs for groups that opted in.
+    #[serde(default = "default_git_fetch_interval_secs")]
+    pub git_fetch_interval_secs: u64,
+    /// How many directory levels below a group path are searched for nested
+    /// repositories and submodules. 0 disables the search.
+    #[serde(default = "default_git_nested_depth")]
+    pub git_nested_depth: usize,
+    #[serde(default)]
+    pub tab_alerts: TabAlertSettings,
+    /// Programs that may be running in a tab without a confirmation before
+    /// the tab is closed. A trailing `*` matches by prefix.
+    #[serde(default = "default_close_confirm_ignore")]
+    pub close_confirm_ignore: Vec<String>,
     /// Last known terminal content size [width, height] in pixels. Used to seed
     /// new terminals at the correct column/row count on startup so the PTY does
     /// not boot at the 80x50 default and resize on the first frame.
@@ -97,6 +278,56 @@ pub struct Settings {
     /// 80x50 default. Recomputed on the first frame and on font changes.
     #[serde(default)]
     pub last_terminal_cell_metrics: Option<[f32; 2]>,
+    #[serde(default)]
+    pub main_window: MainWindowState,
+    /// Additional top-level windows; groups refer to them by id.
+    #[serde(default)]
+    pub windows: Vec<WindowState>,
+}
+
+/// How the main window was left, restored on the next launch. Position and
+/// size are the normal (not maximized or fullscreen) geometry in points.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub struct MainWindowState {
+    #[serde(default)]
+    pub pos: Option<[f32; 2]>,
+    #[serde(default)]
+    pub size: Option<[f32; 2]>,
+    /// Size of the monitor the window was on, to notice when it is gone.
+    #[serde(default)]
+    pub monitor_size: Option<[f32; 2]>,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    #[serde(default)]
+    pub always_on_top: bool,
+}
+
+impl MainWindowState {
+    /// Whether enough of the window's top edge lies on a monitor of size
+    /// `monitor` to grab it. egui does not say where the monitor is, so it
+    /// is taken to start at the desktop origin, like the one monitor left
+    /// after another is unplugged.
+    pub fn on_monitor(&self, monitor: [f32; 2]) -> bool {
+        const GRIP: f32 = 50.0;
+        let Some([x, y]) = self.pos else {
+            return true;
+        };
+        let [width, _] = self.size.unwrap_or(DEFAULT_WINDOW_SIZE);
+        let visible_width = (x + width).min(monitor[0]) - x.max(0.0);
+        visible_width >= GRIP.min(width) && y > -GRIP / 2.0 && y + GRIP <= monitor[1]
+    }
+}
+
+/// An additional window and its last position and inner size in points.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct WindowState {
+    pub id: u64,
+    #[serde(default)]
+    pub pos: Option<[f32; 2]>,
+    #[serde(default)]
+    pub size: Option<[f32; 2]>,
 }
 
 fn default_show_terminal_lines() -> bool {
@@ -111,6 +342,10 @@ fn default_show_sidebar() -> bool {
     DEFAULT_SHOW_SIDEBAR
 }
 
+fn default_sidebar_width() -> f32 {
+    DEFAULT_SIDEBAR_WIDTH
+}
+
 fn default_show_system_monitor() -> bool {
     DEFAULT_SHOW_SYSTEM_MONITOR
 }
@@ -139,6 +374,22 @@ fn default_preload_tabs() -> bool {
     DEFAULT_PRELOAD_TABS
 }
 
+fn default_lazy_tab_spawn() -> bool {
+    DEFAULT_LAZY_TAB_SPAWN
+}
+
+fn default_keep_workspaces_alive() -> bool {
+    DEFAULT_KEEP_WORKSPACES_ALIVE
+}
+
+fn default_git_fetch_interval_secs() -> u64 {
+    DEFAULT_GIT_FETCH_INTERVAL_SECS
+}
+
+fn default_git_nested_depth() -> usize {
+    DEFAULT_GIT_NESTED_DEPTH
+}
+
 impl Settings {
     pub fn load() -> Self {
         let mut settings = if let Some(config_dir) = super::config_dir() {
@@ -179,3 +430,47 @@ impl Settings {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_main_window_on_monitor() {
+        let at = |x: f32, y: f32| MainWindowState {
+            pos: Some([x, y]),
+            size: Some([1000.0, 700.0]),
+            ..Default::default()
+        };
+        let monitor = [1920.0, 1080.0];
+        assert!(MainWindowState::default().on_monitor(monitor));
+        assert!(at(100.0, 100.0).on_monitor(monitor));
+        assert!(at(-500.0, 0.0).on_monitor(monitor));
+        // Left on an unplugged monitor beside or above this one.
+        assert!(!at(2000.0, 100.0).on_monitor(monitor));
+        assert!(!at(-1900.0, 100.0).on_monitor(monitor));
+        assert!(!at(100.0, -800.0).on_monitor(monitor));
+        assert!(!at(100.0, 1060.0).on_monitor(monitor));
+    }
+
+    #[test]
+    fn test_preload_limits() {
+        let limits = PreloadLimits {
+            idle_mins: 30,
+            max_memory_percent: 85,
+            ..Default::default()
+        };
+        assert_eq!(limits.idle_timeout(), Some(Duration::from_secs(30 * 60)));
+        assert!(!limits.memory_exceeded(85.0));
+        assert!(limits.memory_exceeded(85.5));
+
+        let unlimited = PreloadLimits {
+            idle_mins: 0,
+            max_memory_percent: 0,
+            ..Default::default()
+        };
+        assert_eq!(unlimited.idle_timeout(), None);
+        assert!(!unlimited.memory_exceeded(100.0));
+    }
+}
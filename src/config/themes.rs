@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::constants::*;
+use crate::theme::{builtin_themes, AppTheme};
+
+/// A theme file in the library directory. Exported themes use the same
+/// format, so they can be copied into another library.
+#[derive(Serialize, Deserialize)]
+struct ThemeFile {
+    name: String,
+    theme: AppTheme,
+}
+
+#[derive(Clone)]
+pub struct LibraryTheme {
+    pub name: String,
+    pub theme: AppTheme,
+    /// Bundled presets cannot be overwritten, renamed or deleted.
+    pub builtin: bool,
+}
+
+/// Named themes: the bundled presets followed by the user's saved themes,
+/// which live as JSON files under the config dir.
+pub struct ThemeLibrary {
+    pub themes: Vec<LibraryTheme>,
+    dir: Option<PathBuf>,
+}
+
+impl ThemeLibrary {
+    pub fn load() -> Self {
+        Self::load_from(super::config_dir().map(|dir| dir.join(THEMES_DIR)))
+    }
+
+    fn load_from(dir: Option<PathBuf>) -> Self {
+        let mut library = Self {
+            themes: builtin_themes()
+                .into_iter()
+                .map(|(name, theme)| LibraryTheme {
+                    name: name.to_string(),
+                    theme,
+                    builtin: true,
+                })
+                .collect(),
+            dir,
+        };
+
+        if let Some(entries) = library.dir.as_ref().and_then(|d| std::fs::read_dir(d).ok()) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|e| e != "json") {
+                    continue;
+                }
+                let Some(file) = std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<ThemeFile>(&content).ok())
+                else {
+                    continue;
+                };
+                if library.get(&file.name).is_none() {
+                    library.themes.push(LibraryTheme {
+                        name: file.name,
+                        theme: file.theme,
+                        builtin: false,
+                    });
+                }
+            }
+        }
+        library.sort();
+        library
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LibraryTheme> {
+        self.themes.iter().find(|t| t.name == name)
+    }
+
+    /// Name of the first library theme identical to `theme`.
+    pub fn name_of(&self, theme: &AppTheme) -> Option<String> {
+        self.themes
+            .iter()
+            .find(|t| t.theme == *theme)
+            .map(|t| t.name.clone())
+    }
+
+    /// Save `theme` under `name`, replacing a saved theme of the same name.
+    pub fn save_as(&mut self, name: &str, theme: AppTheme) -> Result<(), String> {
+        let name = self.check_name(name, None)?;
+        std::fs::write(self.file_path(&name)?, theme_json(&name, &theme)?)
+            .map_err(|e| format!("Failed to save \"{}\": {}", name, e))?;
+        match self.themes.iter_mut().find(|t| t.name == name) {
+            Some(existing) => existing.theme = theme,
+            None => {
+                self.themes.push(LibraryTheme {
+                    name,
+                    theme,
+                    builtin: false,
+                });
+                self.sort();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let theme = self.saved(name)?.theme;
+        let new_name = self.check_name(new_name, Some(name))?;
+        if new_name != name && self.get(&new_name).is_some() {
+            return Err(format!("A theme named \"{}\" already exists", new_name));
+        }
+        std::fs::write(self.file_path(&new_name)?, theme_json(&new_name, &theme)?)
+            .map_err(|e| format!("Failed to rename \"{}\": {}", name, e))?;
+        let old_path = self.file_path(name)?;
+        if old_path != self.file_path(&new_name)? {
+            let _ = std::fs::remove_file(old_path);
+        }
+        if let Some(entry) = self.themes.iter_mut().find(|t| t.name == name) {
+            entry.name = new_name;
+        }
+        self.sort();
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        self.saved(name)?;
+        std::fs::remove_file(self.file_path(name)?)
+            .map_err(|e| format!("Failed to delete \"{}\": {}", name, e))?;
+        self.themes.retain(|t| t.name != name);
+        Ok(())
+    }
+
+    /// A saved (not built-in) theme.
+    fn saved(&self, name: &str) -> Result<&LibraryTheme, String> {
+        match self.get(name) {
+            Some(t) if t.builtin => Err(format!("\"{}\" is a built-in theme", name)),
+            Some(t) => Ok(t),
+            None => Err(format!("No theme named \"{}\"", name)),
+        }
+    }
+
+    /// Trim `name` and make sure it can be written: not empty, not a preset
+    /// and not sharing a file with another theme than `renaming`.
+    fn check_name(&self, name: &str, renaming: Option<&str>) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Enter a theme name".to_string());
+        }
+        if self.get(name).is_some_and(|t| t.builtin) {
+            return Err(format!("\"{}\" is a built-in theme", name));
+        }
+        let stem = file_stem(name);
+        if let Some(other) = self.themes.iter().find(|t| {
+            !t.builtin
+                && t.name != name
+                && Some(t.name.as_str()) != renaming
+                && file_stem(&t.name) == stem
+        }) {
+            return Err(format!("\"{}\" conflicts with \"{}\"", name, other.name));
+        }
+        Ok(name.to_string())
+    }
+
+    fn file_path(&self, name: &str) -> Result<PathBuf, String> {
+        let dir = self.dir.as_ref().ok_or("No config directory")?;
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(dir.join(format!("{}.json", file_stem(name))))
+    }
+
+    /// Presets first in their bundled order, then saved themes by name.
+    fn sort(&mut self) {
+        self.themes.sort_by_cached_key(|t| {
+            (
+                !t.builtin,
+                if t.builtin {
+                    String::new()
+                } else {
+                    t.name.to_lowercase()
+                },
+            )
+        });
+    }
+}
+
+/// Write `theme` to `path` in the library file format.
+pub fn export(name: &str, theme: &AppTheme, path: &Path) -> Result<(), String> {
+    std::fs::write(path, theme_json(name, theme)?)
+        .map_err(|e| format!("Failed to export to {}: {}", path.display(), e))
+}
+
+fn theme_json(name: &str, theme: &AppTheme) -> Result<String, String> {
+    serde_json::to_string_pretty(&ThemeFile {
+        name: name.to_string(),
+        theme: *theme,
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// File name for a theme: anything but letters, digits, `-` and `_` becomes `_`.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(library: &ThemeLibrary) -> Vec<&str> {
+        library.themes.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_save_rename_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut library = ThemeLibrary::load_from(Some(dir.path().to_path_buf()));
+        let builtin = names(&library).len();
+
+        let theme = AppTheme {
+            terminal_fg: egui::Color32::RED,
+            ..AppTheme::default()
+        };
+        library.save_as("My theme", theme).unwrap();
+        library.save_as("another", AppTheme::default()).unwrap();
+        assert_eq!(names(&library)[builtin..], ["another", "My theme"]);
+
+        let reloaded = ThemeLibrary::load_from(Some(dir.path().to_path_buf()));
+        assert_eq!(reloaded.get("My theme").unwrap().theme, theme);
+        assert_eq!(reloaded.name_of(&theme).as_deref(), Some("My theme"));
+
+        library.rename("My theme", "Zed").unwrap();
+        assert!(library.rename("Zed", "another").is_err());
+        library.delete("another").unwrap();
+        assert_eq!(names(&library)[builtin..], ["Zed"]);
+
+        let reloaded = ThemeLibrary::load_from(Some(dir.path().to_path_buf()));
+        assert_eq!(names(&reloaded)[builtin..], ["Zed"]);
+        assert_eq!(reloaded.get("Zed").unwrap().theme, theme);
+    }
+
+    #[test]
+    fn test_builtin_themes_are_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut library = ThemeLibrary::load_from(Some(dir.path().to_path_buf()));
+        assert!(library.save_as("Dark", AppTheme::default()).is_err());
+        assert!(library.rename("Light", "Mine").is_err());
+        assert!(library.delete("Light").is_err());
+        assert!(library.save_as("  ", AppTheme::default()).is_err());
+
+        library.save_as("a b", AppTheme::default()).unwrap();
+        assert!(library.save_as("a/b", AppTheme::default()).is_err());
+        assert_eq!(
+            library.name_of(&AppTheme::default()).as_deref(),
+            Some("Dark")
+        );
+        assert!(library.get("Light").unwrap().theme.is_light());
+    }
+
+    #[test]
+    fn test_short_palette_keeps_default_colors() {
+        let json = r##"{"normal": ["#ff0000"], "bright": [], "dim": ["nope"],
+            "dim_foreground": "#828482", "cursor": "#ffffff",
+            "cursor_text": "#000000", "selection": "#444444"}"##;
+        let palette: crate::theme::TerminalPalette = serde_json::from_str(json).unwrap();
+        let default = crate::theme::TerminalPalette::default();
+        assert_eq!(palette.normal[0], egui::Color32::RED);
+        assert_eq!(palette.normal[1..], default.normal[1..]);
+        assert_eq!(palette.bright, default.bright);
+        assert_eq!(palette.dim, default.dim);
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+
+use crate::constants::*;
+
+/// Named sets of project groups. The default workspace keeps using
+/// `groups.json`; every other one is a groups file of the same format
+/// under the workspaces dir, named after the workspace.
+pub struct Workspaces {
+    /// Default first, then the other workspaces by name.
+    pub names: Vec<String>,
+    dir: Option<PathBuf>,
+}
+
+impl Workspaces {
+    pub fn load() -> Self {
+        Self::load_from(super::config_dir())
+    }
+
+    fn load_from(config_dir: Option<PathBuf>) -> Self {
+        let mut workspaces = Self {
+            names: vec![DEFAULT_WORKSPACE.to_string()],
+            dir: config_dir,
+        };
+        if let Some(entries) = workspaces
+            .workspaces_dir()
+            .and_then(|d| std::fs::read_dir(d).ok())
+        {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|e| e != "json") {
+                    continue;
+                }
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    if name != DEFAULT_WORKSPACE {
+                        workspaces.names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        workspaces.sort();
+        workspaces
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.iter().any(|n| n == name)
+    }
+
+    /// File holding the groups of workspace `name`.
+    pub fn groups_file(&self, name: &str) -> Option<PathBuf> {
+        if name == DEFAULT_WORKSPACE {
+            self.dir.as_ref().map(|dir| dir.join(GROUPS_FILE))
+        } else {
+            self.workspaces_dir()
+                .map(|dir| dir.join(format!("{}.json", name)))
+        }
+    }
+
+    /// Create an empty workspace and return its (trimmed) name.
+    pub fn create(&mut self, name: &str) -> Result<String, String> {
+        let name = self.check_name(name)?;
+        self.write(&name, "[]")?;
+        Ok(name)
+    }
+
+    /// Copy the groups of `from` into a new workspace. Save `from` first if
+    /// it is the open one.
+    pub fn duplicate(&mut self, from: &str, name: &str) -> Result<String, String> {
+        let name = self.check_name(name)?;
+        let content = self
+            .groups_file(from)
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_else(|| "[]".to_string());
+        self.write(&name, &content)?;
+        Ok(name)
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        if name == DEFAULT_WORKSPACE {
+            return Err("The default workspace cannot be deleted".to_string());
+        }
+        let path = self.groups_file(name).ok_or("No config directory")?;
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to delete \"{}\": {}", name, e))?;
+        self.names.retain(|n| n != name);
+        Ok(())
+    }
+
+    fn write(&mut self, name: &str, content: &str) -> Result<(), String> {
+        let dir = self.workspaces_dir().ok_or("No config directory")?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        std::fs::write(dir.join(format!("{}.json", name)), content)
+            .map_err(|e| format!("Failed to save \"{}\": {}", name, e))?;
+        self.names.push(name.to_string());
+        self.sort();
+        Ok(())
+    }
+
+    /// Workspace names double as file names, so they are limited to
+    /// letters, digits, spaces, `-` and `_`.
+    fn check_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Enter a workspace name".to_string());
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+        {
+            return Err("Use only letters, digits, spaces, - and _".to_string());
+        }
+        if self.names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            return Err(format!("A workspace named \"{}\" already exists", name));
+        }
+        Ok(name.to_string())
+    }
+
+    fn workspaces_dir(&self) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(WORKSPACES_DIR))
+    }
+
+    fn sort(&mut self) {
+        self.names
+            .sort_by_cached_key(|n| (n != DEFAULT_WORKSPACE, n.to_lowercase()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_duplicate_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = Some(dir.path().to_path_buf());
+        std::fs::write(dir.path().join(GROUPS_FILE), "[1]").unwrap();
+
+        let mut workspaces = Workspaces::load_from(config_dir.clone());
+        assert_eq!(workspaces.names, [DEFAULT_WORKSPACE]);
+
+        assert_eq!(workspaces.create(" Client A ").unwrap(), "Client A");
+        assert_eq!(
+            workspaces.duplicate(DEFAULT_WORKSPACE, "internal").unwrap(),
+            "internal"
+        );
+        assert!(workspaces.create("client a").is_err());
+        assert!(workspaces.create("a/b").is_err());
+        assert!(workspaces.create(DEFAULT_WORKSPACE).is_err());
+
+        let reloaded = Workspaces::load_from(config_dir.clone());
+        assert_eq!(reloaded.names, [DEFAULT_WORKSPACE, "Client A", "internal"]);
+        let copied = std::fs::read_to_string(reloaded.groups_file("internal").unwrap());
+        assert_eq!(copied.unwrap(), "[1]");
+
+        assert!(workspaces.delete(DEFAULT_WORKSPACE).is_err());
+        workspaces.delete("Client A").unwrap();
+        let reloaded = Workspaces::load_from(config_dir);
+        assert_eq!(reloaded.names, [DEFAULT_WORKSPACE, "internal"]);
+    }
+}
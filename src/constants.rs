@@ -1,10 +1,27 @@
 pub const GROUPS_FILE: &str = "groups.json";
 pub const SETTINGS_FILE: &str = "settings.json";
 pub const RECENT_PROJECTS_FILE: &str = "recent_projects.json";
+/// Login-shell environment captured by the last run.
+pub const SHELL_ENV_FILE: &str = "shell_env.json";
+/// Directory of the theme library, one JSON file per saved theme.
+pub const THEMES_DIR: &str = "themes";
+/// Directory of the non-default workspaces, one groups file each.
+pub const WORKSPACES_DIR: &str = "workspaces";
+/// Workspace stored in `groups.json`.
+pub const DEFAULT_WORKSPACE: &str = "Default";
+/// egui font family the terminal is drawn with.
+pub const TERMINAL_FONT_FAMILY: &str = "terminal";
 
 pub const DEFAULT_SHOW_TERMINAL_LINES: bool = true;
 pub const DEFAULT_SHOW_FPS: bool = true;
 pub const DEFAULT_SHOW_SIDEBAR: bool = true;
+/// Id of the window eframe opens; additional windows count up from 1.
+pub const MAIN_WINDOW: u64 = 0;
+pub const DEFAULT_WINDOW_SIZE: [f32; 2] = [1000.0, 700.0];
+pub const DEFAULT_SIDEBAR_WIDTH: f32 = 220.0;
+pub const MIN_SIDEBAR_WIDTH: f32 = 140.0;
+pub const MAX_SIDEBAR_WIDTH: f32 = 600.0;
+pub const COMPACT_SIDEBAR_WIDTH: f32 = 52.0;
 pub const DEFAULT_SHOW_SYSTEM_MONITOR: bool = true;
 pub const DEFAULT_SHOW_TAB_MEMORY: bool = true;
 pub const DEFAULT_RUN_AS_LOGIN_SHELL: bool = false;
@@ -12,3 +29,17 @@ pub const DEFAULT_SHELL_CMD: &str = "";
 pub const DEFAULT_AGENT_CMD: &str = "opencode";
 pub const MAX_AGENTS: usize = 4;
 pub const DEFAULT_PRELOAD_TABS: bool = true;
+pub const DEFAULT_LAZY_TAB_SPAWN: bool = false;
+pub const DEFAULT_PRELOAD_MAX_TABS: usize = 6;
+pub const DEFAULT_PRELOAD_RECENT_GROUPS: usize = 3;
+pub const DEFAULT_PRELOAD_IDLE_MINS: u64 = 30;
+pub const DEFAULT_PRELOAD_MAX_MEMORY_PERCENT: u32 = 85;
+pub const DEFAULT_KEEP_WORKSPACES_ALIVE: bool = true;
+pub const DEFAULT_GIT_FETCH_INTERVAL_SECS: u64 = 300;
+pub const DEFAULT_GIT_NESTED_DEPTH: usize = 2;
+pub const DEFAULT_TAB_ALERT_MEMORY_MB: u64 = 2048;
+pub const DEFAULT_TAB_ALERT_CPU_PERCENT: u32 = 200;
+pub const DEFAULT_TAB_ALERT_DURATION_SECS: u64 = 60;
+/// Programs that never trigger the "close running tab" confirmation: helpers
+/// that shells and prompts keep running in the background.
+pub const DEFAULT_CLOSE_CONFIRM_IGNORE: &[&str] = &["gitstatusd*", "ssh-agent", "gpg-agent"];
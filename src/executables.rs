@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Lookups by program name, valid for the `PATH` they were made with.
+type Lookups = (OsString, HashMap<String, Option<PathBuf>>);
+
+static CACHE: Mutex<Option<Lookups>> = Mutex::new(None);
+
+/// Where the program of `cmd` (its first word) would be started from, like
+/// `which`, but without spawning a process. Results are cached until
+/// `PATH` changes or [`clear_cache`] is called.
+pub fn resolve(cmd: &str) -> Option<PathBuf> {
+    let program = cmd.split_whitespace().next()?;
+    let path = search_path();
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.as_ref().is_none_or(|(cached, _)| *cached != path) {
+        *cache = Some((path.clone(), HashMap::new()));
+    }
+    let (_, found) = cache.as_mut()?;
+    found
+        .entry(program.to_string())
+        .or_insert_with(|| find_in_path(program, &path))
+        .clone()
+}
+
+/// Forget cached lookups, e.g. after installing a program.
+pub fn clear_cache() {
+    *CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// `PATH` that started programs search: the login shell's once captured.
+fn search_path() -> OsString {
+    crate::shell_env::var("PATH")
+        .map(OsString::from)
+        .unwrap_or_default()
+}
+
+/// Find `program` in the directories of `path`. A program given with a
+/// directory is only checked to be executable.
+pub fn find_in_path(program: &str, path: &OsStr) -> Option<PathBuf> {
+    let program_path = Path::new(program);
+    if program_path.components().count() > 1 {
+        return executable(program_path.to_path_buf());
+    }
+    std::env::split_paths(path)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .find_map(|dir| executable(dir.join(program)))
+}
+
+#[cfg(unix)]
+fn executable(path: PathBuf) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+    let metadata = std::fs::metadata(&path).ok()?;
+    (metadata.is_file() && metadata.permissions().mode() & 0o111 != 0).then_some(path)
+}
+
+#[cfg(windows)]
+fn executable(path: PathBuf) -> Option<PathBuf> {
+    if path.extension().is_some() && path.is_file() {
+        return Some(path);
+    }
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+    extensions
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| {
+            let mut with_ext = path.clone().into_os_string();
+            with_ext.push(ext);
+            PathBuf::from(with_ext)
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn write_file(path: &Path, mode: u32) {
+        std::fs::write(path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn test_find_in_path() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        write_file(&first.path().join("agent"), 0o644);
+        write_file(&second.path().join("agent"), 0o755);
+        write_file(&first.path().join("shell"), 0o755);
+        write_file(&second.path().join("shell"), 0o755);
+        let path = std::env::join_paths([first.path(), second.path()]).unwrap();
+
+        // Not executable in the first directory, so the second one wins.
+        assert_eq!(
+            find_in_path("agent", &path),
+            Some(second.path().join("agent"))
+        );
+        // Earlier directories take precedence.
+        assert_eq!(
+            find_in_path("shell", &path),
+            Some(first.path().join("shell"))
+        );
+        assert_eq!(find_in_path("missing", &path), None);
+        assert_eq!(find_in_path("agent", OsStr::new("")), None);
+
+        let absolute = second.path().join("agent");
+        assert_eq!(
+            find_in_path(absolute.to_str().unwrap(), OsStr::new("")),
+            Some(absolute)
+        );
+        let not_executable = first.path().join("agent");
+        assert_eq!(find_in_path(not_executable.to_str().unwrap(), &path), None);
+        assert_eq!(find_in_path(first.path().to_str().unwrap(), &path), None);
+    }
+}
@@ -5,41 +5,142 @@
 //! cache by scanning system fonts and takes ~3 seconds on macOS while finding
 //! no useful fonts (fontconfig is not the native macOS font stack). egui's
 //! built-in default fonts cover the UI perfectly well on their own.
+//!
+//! The terminal is drawn with its own font family, `TERMINAL_FONT_FAMILY`: the
+//! chosen terminal font (or egui's default monospace font) followed by the
+//! monospace fallbacks. egui_term sizes its cells from the font alone, so line
+//! height and letter spacing are baked into a copy of the font's metrics.
 
-use egui::{FontData, FontDefinitions, FontFamily};
+use crate::constants::TERMINAL_FONT_FAMILY;
+use crate::theme::{AppFonts, FontFaces, FontSource};
+use egui::{FontData, FontDefinitions, FontFamily, FontTweak};
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-/// Initialize fonts with optional system fallback.
-pub fn setup_fonts_with_fallback(ctx: &egui::Context) {
-    #[cfg(target_os = "macos")]
-    {
-        log::info!("Using egui default fonts on macOS (fontconfig fallback skipped)");
-        ctx.set_fonts(FontDefinitions::default());
-        return;
+/// Font data keys of the chosen fonts.
+const UI_FONT: &str = "ui-font";
+const TERMINAL_FONT: &str = "terminal-font";
+
+/// A font installed on the system, as listed by fontconfig.
+#[derive(Debug, Clone)]
+pub struct SystemFont {
+    pub name: String,
+    pub monospace: bool,
+}
+
+/// Install egui's default fonts, the system fallbacks, the chosen UI and
+/// terminal fonts and the terminal font family. egui switches to them on the
+/// next frame. A font that fails to load is left out and reported.
+pub fn setup_fonts(ctx: &egui::Context, faces: &FontFaces, sizes: &AppFonts) -> Result<(), String> {
+    let mut fonts = base_fonts().clone();
+    let mut errors = Vec::new();
+
+    if let Some(source) = &faces.ui {
+        match load_font(source) {
+            Ok(bytes) => {
+                fonts
+                    .font_data
+                    .insert(UI_FONT.to_owned(), Arc::new(FontData::from_owned(bytes)));
+                fonts
+                    .families
+                    .entry(FontFamily::Proportional)
+                    .or_default()
+                    .insert(0, UI_FONT.to_owned());
+            }
+            Err(error) => errors.push(error),
+        }
     }
 
-    #[cfg(not(target_os = "macos"))]
-    setup_fonts_with_fontconfig(ctx);
+    let monospace = fonts
+        .families
+        .get(&FontFamily::Monospace)
+        .cloned()
+        .unwrap_or_default();
+    let chosen = faces
+        .terminal
+        .as_ref()
+        .and_then(|source| load_font(source).map_err(|e| errors.push(e)).ok());
+    let primary = chosen.or_else(|| {
+        monospace
+            .first()
+            .and_then(|name| fonts.font_data.get(name))
+            .map(|data| data.font.to_vec())
+    });
+
+    let mut family = Vec::new();
+    if let Some(mut bytes) = primary {
+        let letter_spacing = sizes.letter_spacing / sizes.terminal_font_size;
+        let data = match adjust_metrics(&mut bytes, sizes.line_height, letter_spacing) {
+            Ok(y_offset_factor) => {
+                let mut data = FontData::from_owned(bytes);
+                data.tweak = FontTweak {
+                    y_offset_factor,
+                    ..Default::default()
+                };
+                data
+            }
+            Err(error) => {
+                errors.push(error);
+                FontData::from_owned(bytes)
+            }
+        };
+        fonts
+            .font_data
+            .insert(TERMINAL_FONT.to_owned(), Arc::new(data));
+        family.push(TERMINAL_FONT.to_owned());
+    }
+    family.extend(monospace);
+    fonts
+        .families
+        .insert(FontFamily::Name(TERMINAL_FONT_FAMILY.into()), family);
+
+    ctx.set_fonts(fonts);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// egui's default fonts plus the system fallbacks, looked up once.
+fn base_fonts() -> &'static FontDefinitions {
+    static BASE_FONTS: OnceLock<FontDefinitions> = OnceLock::new();
+    BASE_FONTS.get_or_init(|| {
+        #[cfg(target_os = "macos")]
+        {
+            log::info!("Using egui default fonts on macOS (fontconfig fallback skipped)");
+            FontDefinitions::default()
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            fonts_with_fontconfig_fallback()
+        }
+    })
 }
 
 #[cfg(not(target_os = "macos"))]
-fn setup_fonts_with_fontconfig(ctx: &egui::Context) {
-    use rust_fontconfig::FcFontCache;
+fn font_cache() -> &'static rust_fontconfig::FcFontCache {
+    static FONT_CACHE: OnceLock<rust_fontconfig::FcFontCache> = OnceLock::new();
+    FONT_CACHE.get_or_init(rust_fontconfig::FcFontCache::build)
+}
 
+#[cfg(not(target_os = "macos"))]
+fn fonts_with_fontconfig_fallback() -> FontDefinitions {
     let mut fonts = FontDefinitions::default();
 
-    // Build the cache once and reuse it for both listing and loading.
-    let cache = FcFontCache::build();
+    // Built once and reused for listing and loading, here and in the font
+    // settings.
+    let cache = font_cache();
 
     // Get system font fallback chain from fontconfig
-    let fallback_fonts = get_fallback_fonts(&cache);
+    let fallback_fonts = get_fallback_fonts(cache);
 
     log::info!("Loading fallback fonts: {:?}", fallback_fonts);
 
     // Load fallback fonts from system
     for font_name in &fallback_fonts {
-        if let Some(font_data) = load_system_font(&cache, font_name) {
+        if let Some(font_data) = load_system_font(cache, font_name) {
             log::info!("Loaded fallback font: {}", font_name);
             fonts
                 .font_data
@@ -61,7 +162,172 @@ fn setup_fonts_with_fontconfig(ctx: &egui::Context) {
         }
     }
 
-    ctx.set_fonts(fonts);
+    fonts
+}
+
+/// Installed fonts by name, for the font pickers. Empty on macOS, where
+/// fontconfig is not used.
+pub fn system_fonts() -> Vec<SystemFont> {
+    #[cfg(target_os = "macos")]
+    {
+        Vec::new()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let mut seen = HashSet::new();
+        let mut fonts: Vec<SystemFont> = font_cache()
+            .list()
+            .iter()
+            .filter_map(|(metadata, _font_id)| {
+                let name = metadata.name.clone()?;
+                seen.insert(name.clone()).then(|| SystemFont {
+                    name,
+                    monospace: metadata.monospace == rust_fontconfig::PatternMatch::True,
+                })
+            })
+            .collect();
+        fonts.sort_by_cached_key(|font| font.name.to_lowercase());
+        fonts
+    }
+}
+
+fn load_font(source: &FontSource) -> Result<Vec<u8>, String> {
+    let bytes = match source {
+        FontSource::File(path) => {
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        }
+        #[cfg(not(target_os = "macos"))]
+        FontSource::System(name) => load_system_font(font_cache(), name)
+            .map(|data| data.font.into_owned())
+            .ok_or_else(|| format!("Font \"{}\" not found", name))?,
+        #[cfg(target_os = "macos")]
+        FontSource::System(name) => {
+            return Err(format!(
+                "Font \"{}\" not found: system fonts are not listed on macOS",
+                name
+            ))
+        }
+    };
+    // egui panics on fonts it cannot parse, so reject anything that is not
+    // even shaped like one.
+    if !is_font_data(&bytes) {
+        return Err(format!(
+            "{} is not a TrueType or OpenType font",
+            source.label()
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Whether `bytes` start with a TrueType, OpenType or font collection tag.
+fn is_font_data(bytes: &[u8]) -> bool {
+    matches!(
+        bytes.get(..4),
+        Some([0, 1, 0, 0] | b"true" | b"OTTO" | b"ttcf")
+    )
+}
+
+/// Offsets of the tables of the first font in `bytes`, by tag.
+fn table_offsets(bytes: &[u8]) -> Option<Vec<([u8; 4], usize, usize)>> {
+    let font = if bytes.get(..4)? == b"ttcf" {
+        read_u32(bytes, 12)? as usize
+    } else {
+        0
+    };
+    let num_tables = read_u16(bytes, font + 4)? as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = font + 12 + i * 16;
+            let tag = bytes.get(record..record + 4)?.try_into().ok()?;
+            let offset = read_u32(bytes, record + 8)? as usize;
+            let length = read_u32(bytes, record + 12)? as usize;
+            (offset + length <= bytes.len()).then_some((tag, offset, length))
+        })
+        .collect()
+}
+
+/// Grow the line gap of the font in `bytes` so rows are `line_height` times
+/// the natural height, and widen every glyph advance by `letter_spacing`, a
+/// fraction of the font size.
+/// Returns the `FontTweak::y_offset_factor` that centers the glyphs in the
+/// taller rows. `bytes` is left unchanged on error.
+fn adjust_metrics(bytes: &mut [u8], line_height: f32, letter_spacing: f32) -> Result<f32, String> {
+    let malformed = || "Unsupported font: missing or truncated metrics tables".to_string();
+    let tables = table_offsets(bytes).ok_or_else(malformed)?;
+    let table = |tag: &[u8; 4], min_len: usize| {
+        tables
+            .iter()
+            .find(|(t, _, len)| t == tag && *len >= min_len)
+            .map(|(_, offset, _)| *offset)
+    };
+    let hhea = table(b"hhea", 36).ok_or_else(malformed)?;
+    let hmtx = table(b"hmtx", 0).ok_or_else(malformed)?;
+    let os2 = table(b"OS/2", 78);
+
+    let num_h_metrics = read_u16(bytes, hhea + 34).ok_or_else(malformed)? as usize;
+    if bytes.len() < hmtx + num_h_metrics * 4 {
+        return Err(malformed());
+    }
+
+    // ttf-parser reads the OS/2 typo metrics instead of hhea when the font
+    // sets USE_TYPO_METRICS, so size the gap from whichever is in use.
+    let vertical = |at: usize| -> Option<[i32; 3]> {
+        Some([
+            read_i16(bytes, at)? as i32,
+            read_i16(bytes, at + 2)? as i32,
+            read_i16(bytes, at + 4)? as i32,
+        ])
+    };
+    let hhea_metrics = vertical(hhea + 4).ok_or_else(malformed)?;
+    let typo_metrics = os2.and_then(|os2| {
+        let use_typo = read_u16(bytes, os2 + 62)? & 0x80 != 0;
+        Some((os2 + 68, vertical(os2 + 68)?)).filter(|_| use_typo)
+    });
+    let [ascent, descent, line_gap] = typo_metrics.map_or(hhea_metrics, |(_, m)| m);
+    let height = (ascent - descent).max(1);
+    let extra_gap = ((height + line_gap) as f32 * (line_height - 1.0)).round() as i32;
+    // egui scales glyphs by font size / (ascent - descent), not unitsPerEm.
+    let extra_advance = (letter_spacing * height as f32).round() as i32;
+
+    let gap_at = |at: usize, gap: i32| {
+        (
+            at + 4,
+            (gap + extra_gap).clamp(i16::MIN as i32, i16::MAX as i32),
+        )
+    };
+    let mut gaps = vec![gap_at(hhea + 4, hhea_metrics[2])];
+    if let Some((at, metrics)) = typo_metrics {
+        gaps.push(gap_at(at, metrics[2]));
+    }
+    for (at, gap) in gaps {
+        bytes[at..at + 2].copy_from_slice(&(gap as i16).to_be_bytes());
+    }
+    if extra_advance != 0 {
+        for i in 0..num_h_metrics {
+            let at = hmtx + i * 4;
+            let advance = u16::from_be_bytes([bytes[at], bytes[at + 1]]) as i32;
+            // Zero-width glyphs (combining marks) stay zero-width.
+            if advance > 0 {
+                let advance = (advance + extra_advance).clamp(1, u16::MAX as i32) as u16;
+                bytes[at..at + 2].copy_from_slice(&advance.to_be_bytes());
+            }
+        }
+    }
+
+    Ok(extra_gap as f32 / 2.0 / height as f32)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_i16(bytes: &[u8], at: usize) -> Option<i16> {
+    read_u16(bytes, at).map(|v| v as i16)
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
 }
 
 /// Get fallback fonts from fontconfig (only for missing glyphs)
@@ -134,3 +400,92 @@ fn load_system_font(cache: &rust_fontconfig::FcFontCache, name: &str) -> Option<
         rust_fontconfig::FontSource::Memory(font) => Some(FontData::from_owned(font.bytes.clone())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A font with the tables `adjust_metrics` reads: hhea ascent 800 /
+    /// descent -200 / gap 0, typo ascent 700 / descent -500 / gap 100, two
+    /// advances, 600 and 0, and an unrelated unitsPerEm of 2048.
+    fn test_font(use_typo_metrics: bool) -> Vec<u8> {
+        let tables: [(&[u8; 4], usize); 4] =
+            [(b"head", 54), (b"hhea", 36), (b"hmtx", 8), (b"OS/2", 78)];
+        let mut bytes = vec![0, 1, 0, 0];
+        bytes.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+        bytes.resize(12, 0);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, len) in tables {
+            bytes.extend_from_slice(tag);
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(&(offset as u32).to_be_bytes());
+            bytes.extend_from_slice(&(len as u32).to_be_bytes());
+            offset += len;
+        }
+        let put = |bytes: &mut Vec<u8>, at: usize, value: i16| {
+            bytes[at..at + 2].copy_from_slice(&value.to_be_bytes());
+        };
+        bytes.resize(offset, 0);
+        let head = 12 + 4 * 16;
+        let (hhea, hmtx, os2) = (head + 54, head + 90, head + 98);
+        put(&mut bytes, head + 18, 2048);
+        put(&mut bytes, hhea + 4, 800);
+        put(&mut bytes, hhea + 6, -200);
+        put(&mut bytes, hhea + 34, 2);
+        put(&mut bytes, hmtx, 600);
+        put(
+            &mut bytes,
+            os2 + 62,
+            if use_typo_metrics { 0x80 } else { 0 },
+        );
+        put(&mut bytes, os2 + 68, 700);
+        put(&mut bytes, os2 + 70, -500);
+        put(&mut bytes, os2 + 72, 100);
+        bytes
+    }
+
+    fn at(bytes: &[u8], at: usize) -> i16 {
+        read_i16(bytes, at).unwrap()
+    }
+
+    #[test]
+    fn test_adjust_metrics() {
+        let head = 12 + 4 * 16;
+        let (hhea, hmtx, os2) = (head + 54, head + 90, head + 98);
+
+        let mut bytes = test_font(false);
+        let y_offset = adjust_metrics(&mut bytes, 1.5, 0.1).unwrap();
+        assert_eq!(at(&bytes, hhea + 8), 500);
+        assert_eq!(y_offset, 0.25);
+        assert_eq!(at(&bytes, hmtx), 700);
+        assert_eq!(at(&bytes, hmtx + 4), 0);
+        // Typo metrics are not in use, so they are left alone.
+        assert_eq!(at(&bytes, os2 + 72), 100);
+
+        // Spacing and gap follow the typo height (1200) when it is in use.
+        let mut bytes = test_font(true);
+        adjust_metrics(&mut bytes, 2.0, 0.1).unwrap();
+        assert_eq!(at(&bytes, os2 + 72), 1400);
+        assert_eq!(at(&bytes, hhea + 8), 1300);
+        assert_eq!(at(&bytes, hmtx), 720);
+
+        let original = test_font(false);
+        let mut bytes = original.clone();
+        assert_eq!(adjust_metrics(&mut bytes, 1.0, 0.0), Ok(0.0));
+        assert_eq!(bytes, original);
+    }
+
+    #[test]
+    fn test_rejects_non_fonts() {
+        assert!(is_font_data(&test_font(false)));
+        assert!(is_font_data(b"OTTO...."));
+        assert!(!is_font_data(b"wOFF...."));
+        assert!(!is_font_data(b""));
+
+        let mut truncated = test_font(false);
+        truncated.truncate(100);
+        let copy = truncated.clone();
+        assert!(adjust_metrics(&mut truncated, 1.5, 0.0).is_err());
+        assert_eq!(truncated, copy);
+    }
+}
@@ -0,0 +1,301 @@
+use git2::build::CheckoutBuilder;
+use git2::{Branch, BranchType, Repository};
+use std::path::Path;
+
+/// A local or remote-tracking branch of a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchInfo {
+    /// Short name, e.g. `main` or `origin/main`.
+    pub name: String,
+    /// `true` for remote-tracking branches (`refs/remotes/...`).
+    pub is_remote: bool,
+    /// `true` for the branch HEAD currently points at.
+    pub is_head: bool,
+}
+
+impl BranchInfo {
+    /// Name of the local branch a checkout of this branch ends up on. For a
+    /// remote branch such as `origin/feature/x` this is `feature/x`.
+    pub fn local_name(&self) -> &str {
+        if self.is_remote {
+            self.name
+                .split_once('/')
+                .map(|(_, rest)| rest)
+                .unwrap_or(&self.name)
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// List local branches followed by remote-tracking branches, each sorted by
+/// name. Symbolic remote refs such as `origin/HEAD` are skipped.
+pub fn list_branches(path: &Path) -> Result<Vec<BranchInfo>, git2::Error> {
+    let repo = Repository::open(path)?;
+    let mut local = Vec::new();
+    let mut remote = Vec::new();
+
+    for entry in repo.branches(None)? {
+        let (branch, kind) = entry?;
+        if branch.get().symbolic_target().is_some() {
+            continue;
+        }
+        let Some(name) = branch.name()? else {
+            continue;
+        };
+        let info = BranchInfo {
+            name: name.to_string(),
+            is_remote: kind == BranchType::Remote,
+            is_head: branch.is_head(),
+        };
+        if info.is_remote {
+            remote.push(info);
+        } else {
+            local.push(info);
+        }
+    }
+
+    local.sort_by(|a, b| a.name.cmp(&b.name));
+    remote.sort_by(|a, b| a.name.cmp(&b.name));
+    local.extend(remote);
+    Ok(local)
+}
+
+/// Check out `branch`. A remote branch is checked out through a local branch
+/// of the same name, which is created and set to track it if it does not exist
+/// yet.
+///
+/// Uses a safe checkout: local modifications are carried over when they do not
+/// conflict with the target, otherwise libgit2 refuses and nothing changes.
+pub fn checkout_branch(path: &Path, branch: &BranchInfo) -> Result<(), git2::Error> {
+    let repo = Repository::open(path)?;
+
+    let local = if branch.is_remote {
+        match repo.find_branch(branch.local_name(), BranchType::Local) {
+            Ok(local) => local,
+            Err(_) => {
+                let remote = repo.find_branch(&branch.name, BranchType::Remote)?;
+                let commit = remote.get().peel_to_commit()?;
+                let mut local = repo.branch(branch.local_name(), &commit, false)?;
+                local.set_upstream(Some(&branch.name))?;
+                local
+            }
+        }
+    } else {
+        repo.find_branch(&branch.name, BranchType::Local)?
+    };
+
+    switch_to(&repo, &local)
+}
+
+/// Create a local branch called `name` from `base` (any revision, e.g. a
+/// branch name or commit id), or from HEAD when `base` is `None`, and
+/// optionally check it out.
+pub fn create_branch(
+    path: &Path,
+    name: &str,
+    base: Option<&str>,
+    checkout: bool,
+) -> Result<(), git2::Error> {
+    let name = name.trim();
+    if name.is_empty() || !Branch::name_is_valid(name)? {
+        return Err(git2::Error::from_str(&format!(
+            "'{name}' is not a valid branch name"
+        )));
+    }
+
+    let repo = Repository::open(path)?;
+    let commit = match base {
+        Some(base) => repo.revparse_single(base)?.peel_to_commit()?,
+        None => repo.head()?.peel_to_commit()?,
+    };
+    let branch = repo.branch(name, &commit, false)?;
+
+    if checkout {
+        switch_to(&repo, &branch)?;
+    }
+    Ok(())
+}
+
+fn switch_to(repo: &Repository, branch: &Branch<'_>) -> Result<(), git2::Error> {
+    let refname = branch
+        .get()
+        .name()
+        .ok_or_else(|| git2::Error::from_str("branch name is not valid UTF-8"))?
+        .to_string();
+    let tree = branch.get().peel_to_tree()?;
+
+    let mut options = CheckoutBuilder::new();
+    options.safe();
+    repo.checkout_tree(tree.as_object(), Some(&mut options))?;
+    repo.set_head(&refname)
+}
+
+/// Case-insensitive subsequence match of `query` against `candidate`.
+///
+/// Returns `None` when the query characters do not all appear in order,
+/// otherwise a score where higher is better: consecutive runs and matches at
+/// the start of a path segment (after `/`, `-`, `_`) are rewarded, gaps are
+/// penalized. An empty query matches everything with score 0.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0i64;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+    let mut prev_char: Option<char> = None;
+
+    for (ci, c) in candidate.to_lowercase().chars().enumerate() {
+        if qi < query.len() && c == query[qi] {
+            score += 1;
+            match prev_match {
+                Some(p) if p + 1 == ci => score += 5,
+                Some(p) => score -= (ci - p - 1).min(5) as i64,
+                None => score -= ci.min(5) as i64,
+            }
+            if prev_char.is_none_or(|p| matches!(p, '/' | '-' | '_' | '.')) {
+                score += 3;
+            }
+            prev_match = Some(ci);
+            qi += 1;
+        }
+        prev_char = Some(c);
+    }
+
+    (qi == query.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn git_available() -> bool {
+        std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("git should start");
+        assert!(status.success(), "git {:?} failed in {:?}", args, dir);
+    }
+
+    fn init_repo(dir: &Path) {
+        run_git(dir, &["init", "--quiet"]);
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("file.txt"), "base").unwrap();
+        run_git(dir, &["add", "file.txt"]);
+        run_git(dir, &["commit", "--quiet", "-m", "base"]);
+    }
+
+    fn head_branch(dir: &Path) -> Option<String> {
+        crate::git_status::compute_git_status(dir).and_then(|s| s.branch)
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "main"), Some(0));
+        assert!(fuzzy_score("ftx", "feature/x").is_some());
+        assert!(fuzzy_score("xf", "feature/x").is_none());
+
+        // Contiguous and segment-start matches rank above scattered ones.
+        let exact = fuzzy_score("fix", "fix/login").unwrap();
+        let scattered = fuzzy_score("fix", "feature/index").unwrap();
+        assert!(exact > scattered);
+        assert!(fuzzy_score("LOG", "fix/login").is_some());
+    }
+
+    #[test]
+    fn test_create_and_checkout_local_branch() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+        let initial = head_branch(dir).expect("initial branch");
+
+        create_branch(dir, "topic", None, true).expect("create branch");
+        assert_eq!(head_branch(dir).as_deref(), Some("topic"));
+
+        let branches = list_branches(dir).expect("list branches");
+        let names: Vec<&str> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert!(names.contains(&"topic"));
+        assert!(branches.iter().any(|b| b.name == "topic" && b.is_head));
+
+        let back = branches
+            .iter()
+            .find(|b| b.name == initial)
+            .expect("initial branch listed");
+        checkout_branch(dir, back).expect("checkout");
+        assert_eq!(head_branch(dir), Some(initial));
+
+        assert!(create_branch(dir, "bad name", None, false).is_err());
+    }
+
+    #[test]
+    fn test_create_branch_from_base() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+        let initial = head_branch(dir).expect("initial branch");
+
+        std::fs::write(dir.join("file.txt"), "second").unwrap();
+        run_git(dir, &["commit", "--quiet", "-am", "second"]);
+
+        create_branch(dir, "from-first", Some(&format!("{initial}~1")), true)
+            .expect("create branch from base");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("file.txt")).unwrap(),
+            "base"
+        );
+    }
+
+    #[test]
+    fn test_checkout_remote_branch_creates_tracking_branch() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let origin: PathBuf = tmp.path().join("origin");
+        std::fs::create_dir(&origin).unwrap();
+        init_repo(&origin);
+        run_git(&origin, &["branch", "feature/x"]);
+
+        run_git(
+            tmp.path(),
+            &["clone", "--quiet", origin.to_str().unwrap(), "clone"],
+        );
+        let clone = tmp.path().join("clone");
+
+        let branches = list_branches(&clone).expect("list branches");
+        assert!(!branches.iter().any(|b| b.name.ends_with("/HEAD")));
+        let remote = branches
+            .iter()
+            .find(|b| b.is_remote && b.name == "origin/feature/x")
+            .expect("remote branch listed");
+        assert_eq!(remote.local_name(), "feature/x");
+
+        checkout_branch(&clone, remote).expect("checkout remote branch");
+        let status = crate::git_status::compute_git_status(&clone).expect("status");
+        assert_eq!(status.branch.as_deref(), Some("feature/x"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/feature/x"));
+    }
+}
@@ -0,0 +1,650 @@
+use git2::{Index, Oid, Repository, RepositoryState, Status, StatusOptions};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Kind of change of a file listed in the commit dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    TypeChange,
+    Untracked,
+    Conflicted,
+}
+
+impl ChangeKind {
+    /// One-letter marker in the style of `git status --short`.
+    pub fn letter(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "A",
+            ChangeKind::Modified => "M",
+            ChangeKind::Deleted => "D",
+            ChangeKind::TypeChange => "T",
+            ChangeKind::Untracked => "?",
+            ChangeKind::Conflicted => "U",
+        }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        match self {
+            ChangeKind::Added | ChangeKind::Untracked => egui::Color32::from_rgb(0x4c, 0xaf, 0x50),
+            ChangeKind::Modified | ChangeKind::TypeChange => {
+                egui::Color32::from_rgb(0xff, 0x98, 0x00)
+            }
+            ChangeKind::Deleted | ChangeKind::Conflicted => {
+                egui::Color32::from_rgb(0xf4, 0x43, 0x36)
+            }
+        }
+    }
+}
+
+/// A file with uncommitted changes in the index or working tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedFile {
+    /// Path relative to the repository root, with `/` separators.
+    pub path: String,
+    pub kind: ChangeKind,
+    /// `true` if the file already has changes staged in the index.
+    pub staged: bool,
+}
+
+/// What to commit besides the selected files.
+#[derive(Debug, Clone, Default)]
+pub struct CommitOptions {
+    pub message: String,
+    /// Replace the HEAD commit instead of creating a new one.
+    pub amend: bool,
+    /// Append a `Signed-off-by` trailer for the configured identity.
+    pub sign_off: bool,
+}
+
+#[derive(Debug)]
+pub enum CommitError {
+    Git(git2::Error),
+    /// A hook exited with a non-zero status; `output` is its stdout and stderr.
+    Hook {
+        name: String,
+        output: String,
+    },
+    EmptyMessage,
+    NothingToCommit,
+    /// A merge is in progress and not every tracked change is selected.
+    PartialMerge,
+}
+
+impl fmt::Display for CommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitError::Git(e) => write!(f, "{}", e.message()),
+            CommitError::Hook { name, output } if output.trim().is_empty() => {
+                write!(f, "{name} hook failed")
+            }
+            CommitError::Hook { name, output } => {
+                write!(f, "{name} hook failed:\n{}", output.trim_end())
+            }
+            CommitError::EmptyMessage => write!(f, "Commit message is empty"),
+            CommitError::NothingToCommit => write!(f, "No changes selected to commit"),
+            CommitError::PartialMerge => write!(
+                f,
+                "Cannot commit only some files during a merge; select all changed files"
+            ),
+        }
+    }
+}
+
+impl From<git2::Error> for CommitError {
+    fn from(e: git2::Error) -> Self {
+        CommitError::Git(e)
+    }
+}
+
+/// List files with staged or unstaged changes, sorted by path. Untracked
+/// directories are expanded to individual files.
+pub fn list_changes(path: &Path) -> Result<Vec<ChangedFile>, git2::Error> {
+    let repo = Repository::open(path)?;
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .exclude_submodules(true);
+    let statuses = repo.statuses(Some(&mut opts))?;
+
+    let staged_flags = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE;
+
+    let mut files: Vec<ChangedFile> = statuses
+        .iter()
+        .filter_map(|entry| {
+            let s = entry.status();
+            let kind = if s.contains(Status::CONFLICTED) {
+                ChangeKind::Conflicted
+            } else if s.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+                ChangeKind::Deleted
+            } else if s.contains(Status::INDEX_NEW) {
+                ChangeKind::Added
+            } else if s.contains(Status::WT_NEW) {
+                ChangeKind::Untracked
+            } else if s.intersects(Status::INDEX_TYPECHANGE | Status::WT_TYPECHANGE) {
+                ChangeKind::TypeChange
+            } else if s.intersects(
+                Status::INDEX_MODIFIED
+                    | Status::WT_MODIFIED
+                    | Status::INDEX_RENAMED
+                    | Status::WT_RENAMED,
+            ) {
+                ChangeKind::Modified
+            } else {
+                return None;
+            };
+            Some(ChangedFile {
+                path: entry.path()?.to_string(),
+                kind,
+                staged: s.intersects(staged_flags),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Message of the HEAD commit, used to prefill the editor when amending.
+pub fn head_message(path: &Path) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    commit.message().map(|m| m.trim_end().to_string())
+}
+
+/// `Name <email>` of the identity commits are made with, or `None` if
+/// `user.name`/`user.email` are not configured.
+pub fn identity(path: &Path) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let sig = repo.signature().ok()?;
+    Some(format!("{} <{}>", sig.name()?, sig.email()?))
+}
+
+/// Commit exactly the `selected` paths (as returned by [`list_changes`]).
+///
+/// Like `git commit --only`, the commit is built in a temporary index: HEAD
+/// plus the selected files from the working tree. The `pre-commit` and
+/// `commit-msg` hooks run against it like they would for `git commit`, and a
+/// failing hook aborts the commit with its output. The real index is only
+/// touched once the commit is made, and then only for the selected paths, so
+/// anything else staged stays staged. The commit uses the repository's
+/// configured identity; `post-commit` runs afterwards and its result is
+/// ignored.
+///
+/// While a merge is in progress every tracked change must be selected, the
+/// commit gets `MERGE_HEAD` as additional parents and the merge state is
+/// cleaned up afterwards, like `git commit -a` concluding the merge.
+pub fn commit(
+    path: &Path,
+    changes: &[ChangedFile],
+    selected: &[String],
+    options: &CommitOptions,
+) -> Result<Oid, CommitError> {
+    if options.message.trim().is_empty() {
+        return Err(CommitError::EmptyMessage);
+    }
+
+    let repo = Repository::open(path)?;
+    let mut real_index = repo.index()?;
+    let index_path = repo
+        .path()
+        .join(format!("next-index-{}", std::process::id()));
+    let result = commit_in_index(&repo, &index_path, changes, selected, options);
+    // The repository keeps using the temporary index until told otherwise.
+    repo.set_index(&mut real_index)?;
+    let _ = std::fs::remove_file(&index_path);
+    let (oid, index) = result?;
+
+    real_index.read(true)?;
+    for path in selected {
+        match index.get_path(Path::new(path), 0) {
+            Some(entry) => real_index.add(&entry)?,
+            None => real_index.remove_path(Path::new(path))?,
+        }
+    }
+    real_index.write()?;
+    if repo.state() == RepositoryState::Merge {
+        repo.cleanup_state()?;
+    }
+
+    let workdir = repo.workdir().unwrap_or(repo.path());
+    let real_index_path = real_index
+        .path()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| repo.path().join("index"));
+    if let Err(e) = run_hook(&repo, workdir, "post-commit", &[], &real_index_path) {
+        log::warn!("{e}");
+    }
+    Ok(oid)
+}
+
+/// Make the commit of [`commit`] from a new index at `index_path`. Returns
+/// the index the commit was made from.
+fn commit_in_index(
+    repo: &Repository,
+    index_path: &Path,
+    changes: &[ChangedFile],
+    selected: &[String],
+    options: &CommitOptions,
+) -> Result<(Oid, Index), CommitError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("cannot commit in a bare repository"))?
+        .to_path_buf();
+    let signature = repo.signature()?;
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+
+    let mut merge_heads = Vec::new();
+    if repo.state() == RepositoryState::Merge {
+        if options.amend {
+            return Err(git2::Error::from_str("cannot amend during a merge").into());
+        }
+        let partial = changes
+            .iter()
+            .any(|c| c.kind != ChangeKind::Untracked && !selected.contains(&c.path));
+        if partial {
+            return Err(CommitError::PartialMerge);
+        }
+        let heads = std::fs::read_to_string(repo.path().join("MERGE_HEAD")).unwrap_or_default();
+        for line in heads.lines().filter(|l| !l.trim().is_empty()) {
+            merge_heads.push(repo.find_commit(Oid::from_str(line.trim())?)?);
+        }
+    }
+
+    let mut index = Index::open(index_path)?;
+    if let Some(head) = &head {
+        index.read_tree(&head.tree()?)?;
+    }
+    // Lets `add_path` read files from the working tree.
+    repo.set_index(&mut index)?;
+    for path in selected {
+        if !changes.iter().any(|c| &c.path == path) {
+            continue;
+        }
+        if workdir.join(path).symlink_metadata().is_ok() {
+            index.add_path(Path::new(path))?;
+        } else {
+            index.remove_path(Path::new(path))?;
+        }
+    }
+    index.write()?;
+
+    let tree_id = index.write_tree()?;
+    let unchanged = head.as_ref().is_some_and(|c| c.tree_id() == tree_id);
+    if unchanged && !options.amend && merge_heads.is_empty() {
+        return Err(CommitError::NothingToCommit);
+    }
+
+    run_hook(repo, &workdir, "pre-commit", &[], index_path)?;
+
+    let mut message = options.message.trim_end().to_string();
+    if options.sign_off {
+        let trailer = format!(
+            "Signed-off-by: {} <{}>",
+            signature.name().unwrap_or_default(),
+            signature.email().unwrap_or_default()
+        );
+        if !message.lines().any(|line| line == trailer) {
+            message.push_str("\n\n");
+            message.push_str(&trailer);
+        }
+    }
+    message.push('\n');
+
+    // commit-msg receives the message in a file and may rewrite it.
+    let msg_file = repo.path().join("COMMIT_EDITMSG");
+    std::fs::write(&msg_file, &message)
+        .map_err(|e| git2::Error::from_str(&format!("failed to write commit message: {e}")))?;
+    run_hook(
+        repo,
+        &workdir,
+        "commit-msg",
+        &[msg_file.as_os_str()],
+        index_path,
+    )?;
+    let message = std::fs::read_to_string(&msg_file).unwrap_or(message);
+
+    // pre-commit may have restaged files, so the tree is written again.
+    index.read(true)?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let oid = match (&head, options.amend) {
+        (Some(head), true) => head.amend(
+            Some("HEAD"),
+            None,
+            Some(&signature),
+            None,
+            Some(&message),
+            Some(&tree),
+        )?,
+        (None, true) => return Err(git2::Error::from_str("there is no commit to amend").into()),
+        (head, false) => {
+            let parents: Vec<&git2::Commit> = head.iter().chain(&merge_heads).collect();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parents,
+            )?
+        }
+    };
+    Ok((oid, index))
+}
+
+/// Directory hooks are looked up in, honoring `core.hooksPath`.
+fn hooks_dir(repo: &Repository, workdir: &Path) -> PathBuf {
+    repo.config()
+        .ok()
+        .and_then(|config| config.get_path("core.hooksPath").ok())
+        .map(|dir| {
+            if dir.is_relative() {
+                workdir.join(dir)
+            } else {
+                dir
+            }
+        })
+        .unwrap_or_else(|| repo.path().join("hooks"))
+}
+
+/// Run hook `name` from the working tree root if it exists and is executable.
+/// `index` is the index file the hook sees as staged.
+fn run_hook(
+    repo: &Repository,
+    workdir: &Path,
+    name: &str,
+    args: &[&std::ffi::OsStr],
+    index: &Path,
+) -> Result<(), CommitError> {
+    let hook = hooks_dir(repo, workdir).join(name);
+    if !is_executable(&hook) {
+        return Ok(());
+    }
+
+    // Hooks are usually shell scripts; on Windows they have to go through the
+    // `sh` that ships with Git.
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("sh");
+        command.arg(&hook);
+        command
+    } else {
+        Command::new(&hook)
+    };
+    let output = command
+        .args(args)
+        .env("GIT_INDEX_FILE", index)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| CommitError::Hook {
+            name: name.to_string(),
+            output: e.to_string(),
+        })?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Err(CommitError::Hook {
+        name: name.to_string(),
+        output: text,
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git_available() -> bool {
+        std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("git should start");
+        assert!(status.success(), "git {:?} failed in {:?}", args, dir);
+    }
+
+    fn init_repo(dir: &Path) {
+        run_git(dir, &["init", "--quiet"]);
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join("b.txt"), "b").unwrap();
+        run_git(dir, &["add", "."]);
+        run_git(dir, &["commit", "--quiet", "-m", "base"]);
+    }
+
+    fn head_commit(dir: &Path) -> (String, Vec<String>) {
+        let repo = Repository::open(dir).unwrap();
+        let commit = repo.head().unwrap().peel_to_commit().unwrap();
+        let tree = commit.tree().unwrap();
+        let files = tree
+            .iter()
+            .filter_map(|e| e.name().map(|n| n.to_string()))
+            .collect();
+        (commit.message().unwrap().to_string(), files)
+    }
+
+    fn options(message: &str) -> CommitOptions {
+        CommitOptions {
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_commit_selected_files_only() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+
+        std::fs::write(dir.join("a.txt"), "a changed").unwrap();
+        std::fs::remove_file(dir.join("b.txt")).unwrap();
+        std::fs::create_dir(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/new.rs"), "new").unwrap();
+        // Staged but not selected: must be left out of the commit.
+        std::fs::write(dir.join("c.txt"), "c").unwrap();
+        run_git(dir, &["add", "c.txt"]);
+
+        let changes = list_changes(dir).expect("list changes");
+        let kinds: Vec<(&str, ChangeKind, bool)> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind, c.staged))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("a.txt", ChangeKind::Modified, false),
+                ("b.txt", ChangeKind::Deleted, false),
+                ("c.txt", ChangeKind::Added, true),
+                ("src/new.rs", ChangeKind::Untracked, false),
+            ]
+        );
+
+        let selected = vec![
+            "a.txt".to_string(),
+            "b.txt".to_string(),
+            "src/new.rs".to_string(),
+        ];
+        commit(dir, &changes, &selected, &options("Update files")).expect("commit");
+
+        let (message, files) = head_commit(dir);
+        assert_eq!(message, "Update files\n");
+        assert_eq!(files, vec!["a.txt".to_string(), "src".to_string()]);
+
+        // What else was staged stays staged.
+        let remaining = list_changes(dir).expect("list changes");
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].path, "c.txt");
+        assert_eq!(remaining[0].kind, ChangeKind::Added);
+        assert!(remaining[0].staged);
+    }
+
+    #[test]
+    fn test_amend_and_sign_off() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+
+        assert!(matches!(
+            commit(dir, &[], &[], &options("nothing")),
+            Err(CommitError::NothingToCommit)
+        ));
+        assert!(matches!(
+            commit(dir, &[], &[], &options("  ")),
+            Err(CommitError::EmptyMessage)
+        ));
+
+        let amend = CommitOptions {
+            message: "Reworded".to_string(),
+            amend: true,
+            sign_off: true,
+        };
+        commit(dir, &[], &[], &amend).expect("amend");
+
+        let (message, _) = head_commit(dir);
+        assert_eq!(
+            message,
+            "Reworded\n\nSigned-off-by: Test <test@example.com>\n"
+        );
+        let repo = Repository::open(dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 0);
+        assert_eq!(head_message(dir).as_deref(), Some(message.trim_end()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_hook_aborts_commit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+
+        let hook = dir.join(".git/hooks/pre-commit");
+        std::fs::write(&hook, "#!/bin/sh\necho 'lint failed: a.txt' >&2\nexit 1\n").unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        std::fs::write(dir.join("a.txt"), "a changed").unwrap();
+        std::fs::write(dir.join("c.txt"), "c").unwrap();
+        run_git(dir, &["add", "c.txt"]);
+        let changes = list_changes(dir).expect("list changes");
+        let selected = vec!["a.txt".to_string()];
+        let err = commit(dir, &changes, &selected, &options("Change a")).unwrap_err();
+        match &err {
+            CommitError::Hook { name, output } => {
+                assert_eq!(name, "pre-commit");
+                assert!(output.contains("lint failed: a.txt"));
+            }
+            other => panic!("expected hook error, got {other:?}"),
+        }
+        assert!(err.to_string().contains("lint failed"));
+        assert_eq!(head_commit(dir).0, "base\n");
+        // The index is left as it was.
+        let staged: Vec<(String, bool)> = list_changes(dir)
+            .expect("list changes")
+            .into_iter()
+            .map(|c| (c.path, c.staged))
+            .collect();
+        assert_eq!(
+            staged,
+            vec![("a.txt".to_string(), false), ("c.txt".to_string(), true)]
+        );
+
+        // commit-msg can rewrite the message.
+        std::fs::remove_file(&hook).unwrap();
+        let msg_hook = dir.join(".git/hooks/commit-msg");
+        std::fs::write(&msg_hook, "#!/bin/sh\necho 'Rewritten' > \"$1\"\n").unwrap();
+        std::fs::set_permissions(&msg_hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        commit(dir, &changes, &selected, &options("Change a")).expect("commit");
+        assert_eq!(head_commit(dir).0, "Rewritten\n");
+    }
+
+    #[test]
+    fn test_commit_concludes_merge() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+        run_git(dir, &["checkout", "--quiet", "-b", "other"]);
+        std::fs::write(dir.join("b.txt"), "b changed").unwrap();
+        run_git(dir, &["commit", "--quiet", "-am", "Change b"]);
+        run_git(dir, &["checkout", "--quiet", "-"]);
+        std::fs::write(dir.join("a.txt"), "a changed").unwrap();
+        run_git(dir, &["commit", "--quiet", "-am", "Change a"]);
+        run_git(
+            dir,
+            &["merge", "--quiet", "--no-commit", "--no-ff", "other"],
+        );
+        std::fs::write(dir.join("c.txt"), "c").unwrap();
+
+        let changes = list_changes(dir).expect("list changes");
+        let err = commit(dir, &changes, &[], &options("Merge")).unwrap_err();
+        assert!(matches!(err, CommitError::PartialMerge));
+        let amend = CommitOptions {
+            amend: true,
+            ..options("Merge")
+        };
+        let selected = vec!["b.txt".to_string()];
+        assert!(commit(dir, &changes, &selected, &amend).is_err());
+
+        // Untracked files may stay out of the merge commit.
+        commit(dir, &changes, &selected, &options("Merge")).expect("commit");
+        let repo = Repository::open(dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert_eq!(head.parent(1).unwrap().message(), Some("Change b\n"));
+        assert_eq!(repo.state(), RepositoryState::Clean);
+        assert!(!dir.join(".git/MERGE_HEAD").exists());
+        let remaining: Vec<String> = list_changes(dir)
+            .expect("list changes")
+            .into_iter()
+            .map(|c| c.path)
+            .collect();
+        assert_eq!(remaining, vec!["c.txt".to_string()]);
+    }
+}
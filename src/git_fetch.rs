@@ -0,0 +1,357 @@
+use git2::{
+    CertificateCheckStatus, Cred, CredentialType, FetchOptions, RemoteCallbacks, Repository,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Longest delay between attempts for a repository whose fetches keep failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Outcome of the background fetches for one repository, shown in the group
+/// tooltip.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchState {
+    /// Wall-clock time of the last successful fetch.
+    pub last_fetch: Option<SystemTime>,
+    /// Error message of the last attempt, cleared on success.
+    pub last_error: Option<String>,
+    /// Number of failed attempts since the last success.
+    pub failures: u32,
+}
+
+/// Delay before the next fetch given the configured interval and the number of
+/// consecutive failures. Doubles with every failure, capped at one hour (or the
+/// interval itself if that is longer).
+pub fn backoff_delay(interval: Duration, failures: u32) -> Duration {
+    if failures == 0 {
+        return interval;
+    }
+    let factor = 1u32 << failures.min(6);
+    (interval * factor).min(MAX_BACKOFF.max(interval))
+}
+
+/// Fetch every remote of the repository at `path` using libgit2.
+///
+/// Credentials are taken from the SSH agent for SSH remotes and from the
+/// configured Git credential helpers for HTTPS remotes. `cancel` is polled from
+/// the certificate check, sideband and transfer progress callbacks so a long
+/// fetch can be aborted on shutdown.
+pub fn fetch_remotes(path: &Path, cancel: &AtomicBool) -> Result<(), git2::Error> {
+    let repo = Repository::open(path)?;
+    let config = repo.config()?;
+    let remotes = repo.remotes()?;
+
+    for name in remotes.iter().flatten() {
+        let mut remote = repo.find_remote(name)?;
+
+        // libgit2 calls the credentials callback again after every rejected
+        // attempt, so each method is only tried once to avoid looping forever.
+        let mut tried_agent = false;
+        let mut tried_helper = false;
+        let mut tried_default = false;
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(|url, username_from_url, allowed| {
+            if allowed.contains(CredentialType::USERNAME) {
+                return Cred::username(username_from_url.unwrap_or("git"));
+            }
+            if allowed.contains(CredentialType::SSH_KEY) && !tried_agent {
+                tried_agent = true;
+                return Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+            }
+            if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && !tried_helper {
+                tried_helper = true;
+                return Cred::credential_helper(&config, url, username_from_url);
+            }
+            if allowed.contains(CredentialType::DEFAULT) && !tried_default {
+                tried_default = true;
+                return Cred::default();
+            }
+            Err(git2::Error::from_str(&format!(
+                "no usable credentials for {url}"
+            )))
+        });
+        callbacks.certificate_check(|_, _| {
+            if cancel.load(Ordering::Relaxed) {
+                Err(git2::Error::from_str("fetch cancelled"))
+            } else {
+                Ok(CertificateCheckStatus::CertificatePassthrough)
+            }
+        });
+        callbacks.sideband_progress(|_| !cancel.load(Ordering::Relaxed));
+        callbacks.transfer_progress(|_| !cancel.load(Ordering::Relaxed));
+
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(callbacks);
+        // An empty refspec list fetches the remote's configured refspecs and
+        // updates the remote-tracking branches, like `git fetch <remote>`.
+        remote.fetch(&[] as &[&str], Some(&mut options), None)?;
+    }
+
+    Ok(())
+}
+
+/// Periodic background `git fetch` for opted-in project groups.
+///
+/// Mirrors [`GitStatusCache`](crate::git_status::GitStatusCache): the UI thread
+/// only updates the target set and reads cached [`FetchState`]s, while a single
+/// background thread performs the network operations. Failed fetches are
+/// retried with exponential backoff.
+pub struct GitFetcher {
+    states: Arc<Mutex<HashMap<PathBuf, FetchState>>>,
+    targets: Arc<Mutex<HashSet<PathBuf>>>,
+    interval_secs: Arc<AtomicU64>,
+    shutdown: Arc<AtomicBool>,
+    _thread: Option<thread::JoinHandle<()>>,
+}
+
+impl GitFetcher {
+    /// Create a fetcher that fetches each target every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        let states: Arc<Mutex<HashMap<PathBuf, FetchState>>> = Arc::new(Mutex::new(HashMap::new()));
+        let targets: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+        let interval_secs = Arc::new(AtomicU64::new(interval.as_secs().max(1)));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let states_clone = Arc::clone(&states);
+        let targets_clone = Arc::clone(&targets);
+        let interval_clone = Arc::clone(&interval_secs);
+        let shutdown_clone = Arc::clone(&shutdown);
+
+        let handle = thread::Builder::new()
+            .name("git-fetch".into())
+            .spawn(move || {
+                let mut next_due: HashMap<PathBuf, Instant> = HashMap::new();
+                while !shutdown_clone.load(Ordering::Relaxed) {
+                    let targets = targets_clone.lock().unwrap().clone();
+                    next_due.retain(|path, _| targets.contains(path));
+                    states_clone
+                        .lock()
+                        .unwrap()
+                        .retain(|path, _| targets.contains(path));
+
+                    let now = Instant::now();
+                    let due: Vec<PathBuf> = targets
+                        .iter()
+                        .filter(|path| next_due.get(*path).is_none_or(|when| *when <= now))
+                        .cloned()
+                        .collect();
+
+                    for path in due {
+                        if shutdown_clone.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let result = fetch_remotes(&path, &shutdown_clone);
+                        let interval = Duration::from_secs(interval_clone.load(Ordering::Relaxed));
+
+                        let mut states = states_clone.lock().unwrap();
+                        let state = states.entry(path.clone()).or_default();
+                        match result {
+                            Ok(()) => {
+                                state.last_fetch = Some(SystemTime::now());
+                                state.last_error = None;
+                                state.failures = 0;
+                            }
+                            Err(e) => {
+                                log::warn!("Background fetch failed for {}: {e}", path.display());
+                                state.last_error = Some(e.message().to_string());
+                                state.failures += 1;
+                            }
+                        }
+                        let delay = backoff_delay(interval, state.failures);
+                        next_due.insert(path, Instant::now() + delay);
+                    }
+
+                    thread::sleep(Duration::from_millis(500));
+                }
+            })
+            .ok();
+
+        Self {
+            states,
+            targets,
+            interval_secs,
+            shutdown,
+            _thread: handle,
+        }
+    }
+
+    /// Change the fetch interval. Takes effect after each target's next fetch.
+    pub fn set_interval(&self, interval: Duration) {
+        self.interval_secs
+            .store(interval.as_secs().max(1), Ordering::Relaxed);
+    }
+
+    /// Replace the set of repositories that should be fetched in the
+    /// background. Newly added paths are fetched right away.
+    pub fn set_targets<I>(&self, paths: I)
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        let paths: HashSet<PathBuf> = paths.into_iter().collect();
+        let mut targets = self.targets.lock().unwrap();
+        if *targets != paths {
+            *targets = paths;
+        }
+    }
+
+    /// Latest fetch outcome for `path`, if it is a target and has been
+    /// attempted at least once.
+    pub fn state(&self, path: &Path) -> Option<FetchState> {
+        self.states.lock().unwrap().get(path).cloned()
+    }
+}
+
+impl Drop for GitFetcher {
+    /// Asks the thread to stop without waiting for it: a fetch still
+    /// connecting to an unreachable remote only notices at the next callback,
+    /// which may be a network timeout away.
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        self._thread.take();
+    }
+}
+
+/// Format a past wall-clock time relative to now, e.g. "3 min ago".
+pub fn format_ago(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 60 * 60 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{} h ago", secs / (60 * 60))
+    } else {
+        format!("{} d ago", secs / (24 * 60 * 60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_status::compute_git_status;
+
+    fn git_available() -> bool {
+        std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("git should start");
+        assert!(status.success(), "git {:?} failed in {:?}", args, dir);
+    }
+
+    fn configure_identity(dir: &Path) {
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "Test"]);
+    }
+
+    /// Create a bare `origin` repository plus two clones: `work`, which is the
+    /// repository under test, and `other`, used to push new commits.
+    fn setup_remote(root: &Path) -> (PathBuf, PathBuf) {
+        let origin = root.join("origin.git");
+        run_git(root, &["init", "--quiet", "--bare", "origin.git"]);
+
+        let seed = root.join("seed");
+        run_git(
+            root,
+            &["clone", "--quiet", origin.to_str().unwrap(), "seed"],
+        );
+        configure_identity(&seed);
+        std::fs::write(seed.join("file.txt"), "base").unwrap();
+        run_git(&seed, &["add", "file.txt"]);
+        run_git(&seed, &["commit", "--quiet", "-m", "base"]);
+        run_git(&seed, &["push", "--quiet", "origin", "HEAD"]);
+
+        run_git(
+            root,
+            &["clone", "--quiet", origin.to_str().unwrap(), "work"],
+        );
+        (root.join("work"), seed)
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let interval = Duration::from_secs(60);
+        assert_eq!(backoff_delay(interval, 0), interval);
+        assert_eq!(backoff_delay(interval, 1), Duration::from_secs(120));
+        assert_eq!(backoff_delay(interval, 3), Duration::from_secs(480));
+        assert_eq!(backoff_delay(interval, 30), MAX_BACKOFF);
+
+        // Intervals longer than the cap are never shortened.
+        let long = Duration::from_secs(2 * 60 * 60);
+        assert_eq!(backoff_delay(long, 5), long);
+    }
+
+    #[test]
+    fn test_fetch_remotes_updates_tracking_branch() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let (work, seed) = setup_remote(tmp.path());
+
+        std::fs::write(seed.join("file.txt"), "remote change").unwrap();
+        run_git(&seed, &["commit", "--quiet", "-am", "remote change"]);
+        run_git(&seed, &["push", "--quiet", "origin", "HEAD"]);
+
+        let before = compute_git_status(&work).expect("should detect git repo");
+        assert_eq!(before.behind, 0);
+
+        fetch_remotes(&work, &AtomicBool::new(false)).expect("fetch should succeed");
+
+        let after = compute_git_status(&work).expect("should detect git repo");
+        assert_eq!(after.behind, 1);
+    }
+
+    #[test]
+    fn test_fetch_remotes_reports_missing_remote() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let (work, _seed) = setup_remote(tmp.path());
+        std::fs::remove_dir_all(tmp.path().join("origin.git")).unwrap();
+
+        assert!(fetch_remotes(&work, &AtomicBool::new(false)).is_err());
+    }
+
+    #[test]
+    fn test_fetcher_records_state() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let (work, _seed) = setup_remote(tmp.path());
+
+        let fetcher = GitFetcher::new(Duration::from_secs(3600));
+        fetcher.set_targets([work.clone()]);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut state = None;
+        while Instant::now() < deadline && state.is_none() {
+            thread::sleep(Duration::from_millis(100));
+            state = fetcher.state(&work);
+        }
+        let state = state.expect("fetch should have run");
+        assert!(state.last_fetch.is_some());
+        assert_eq!(state.last_error, None);
+        assert_eq!(state.failures, 0);
+    }
+}
@@ -1,10 +1,12 @@
-use git2::{BranchType, Repository, StatusOptions};
+use crate::constants::DEFAULT_GIT_NESTED_DEPTH;
+use git2::{BranchType, Repository, RepositoryState, Status, StatusOptions};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Synchronization state of a Git branch relative to its upstream.
 /// Conflicts take precedence over divergence/pull/push because they must be
@@ -51,8 +53,28 @@ impl GitSyncStatus {
         }
     }
 
+    /// Rank used to pick the most urgent state across several repositories.
+    /// Follows the precedence [`compute_git_status`] applies within one.
+    pub fn severity(&self) -> u8 {
+        match self {
+            GitSyncStatus::Clean => 0,
+            GitSyncStatus::Dirty => 1,
+            GitSyncStatus::NeedsPull => 2,
+            GitSyncStatus::NeedsPush => 3,
+            GitSyncStatus::Diverged => 4,
+            GitSyncStatus::Conflicts => 5,
+        }
+    }
+
+    /// The most urgent of `statuses`, or `None` if there are none.
+    pub fn worst<I>(statuses: I) -> Option<GitSyncStatus>
+    where
+        I: IntoIterator<Item = GitSyncStatus>,
+    {
+        statuses.into_iter().max_by_key(|s| s.severity())
+    }
+
     /// Short human-readable description, useful for tooltips.
-    #[allow(dead_code)]
     pub fn label(&self) -> &'static str {
         match self {
             GitSyncStatus::Clean => "Up to date",
@@ -65,21 +87,124 @@ impl GitSyncStatus {
     }
 }
 
+/// Multi-step Git operation that is currently in progress in a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Bisect,
+    ApplyMailbox,
+}
+
+impl GitOperation {
+    fn from_state(state: RepositoryState) -> Option<Self> {
+        match state {
+            RepositoryState::Clean => None,
+            RepositoryState::Merge => Some(GitOperation::Merge),
+            RepositoryState::Revert | RepositoryState::RevertSequence => Some(GitOperation::Revert),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(GitOperation::CherryPick)
+            }
+            RepositoryState::Bisect => Some(GitOperation::Bisect),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(GitOperation::Rebase),
+            RepositoryState::ApplyMailbox => Some(GitOperation::ApplyMailbox),
+        }
+    }
+
+    /// Short human-readable description, e.g. "Rebase in progress".
+    pub fn label(&self) -> &'static str {
+        match self {
+            GitOperation::Merge => "Merge in progress",
+            GitOperation::Rebase => "Rebase in progress",
+            GitOperation::CherryPick => "Cherry-pick in progress",
+            GitOperation::Revert => "Revert in progress",
+            GitOperation::Bisect => "Bisect in progress",
+            GitOperation::ApplyMailbox => "git am in progress",
+        }
+    }
+}
+
 /// Complete Git status for a repository path.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GitStatus {
     /// Current branch name, if any. `None` for detached HEAD.
     pub branch: Option<String>,
+    /// Abbreviated commit id of HEAD when it is detached.
+    pub detached_commit: Option<String>,
+    /// Upstream branch name (e.g. `origin/main`), if one is configured.
+    pub upstream: Option<String>,
+    /// Commits on the local branch that the upstream does not have.
+    pub ahead: usize,
+    /// Commits on the upstream that the local branch does not have.
+    pub behind: usize,
+    /// Files with changes staged in the index.
+    pub staged: usize,
+    /// Tracked files with unstaged changes in the working tree.
+    pub modified: usize,
+    /// Untracked files (untracked directories count once).
+    pub untracked: usize,
+    /// Files with unresolved merge conflicts.
+    pub conflicted: usize,
+    /// Number of entries in the stash.
+    pub stash_count: usize,
+    /// Merge/rebase/cherry-pick/bisect currently in progress, if any.
+    pub operation: Option<GitOperation>,
     /// How the local branch compares to its upstream.
     pub sync_status: GitSyncStatus,
 }
 
+impl GitStatus {
+    /// One-line summary such as "3 ahead, 1 behind, 5 modified". Falls back to
+    /// the sync status label when there is nothing to count.
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            (self.ahead, "ahead"),
+            (self.behind, "behind"),
+            (self.staged, "staged"),
+            (self.modified, "modified"),
+            (self.untracked, "untracked"),
+            (self.conflicted, "conflicted"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{} {}", count, what))
+        .collect();
+
+        if parts.is_empty() {
+            self.sync_status.label().to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
 /// Detect Git status for the directory at `path` using libgit2.
 /// Returns `None` if `path` is not inside a Git repository.
 pub fn compute_git_status(path: &Path) -> Option<GitStatus> {
-    let repo = Repository::open(path).ok()?;
+    let mut repo = Repository::open(path).ok()?;
+    let operation = GitOperation::from_state(repo.state());
+    let stash_count = count_stashes(&mut repo);
+
     let head = repo.head().ok()?;
-    let branch = head.shorthand().map(|s| s.to_string());
+    let detached = repo.head_detached().unwrap_or(false);
+    let branch = if detached {
+        None
+    } else {
+        head.shorthand().map(|s| s.to_string())
+    };
+    let detached_commit = if detached {
+        head.peel_to_commit()
+            .ok()
+            .and_then(|commit| commit.as_object().short_id().ok())
+            .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+    } else {
+        None
+    };
 
     // Working tree / index status.
     let mut opts = StatusOptions::new();
@@ -88,30 +213,52 @@ pub fn compute_git_status(path: &Path) -> Option<GitStatus> {
         .exclude_submodules(true);
     let statuses = repo.statuses(Some(&mut opts)).ok()?;
 
-    let mut dirty = false;
-    let mut conflicts = false;
+    let staged_flags = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE;
+    let modified_flags =
+        Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE;
+
+    let (mut staged, mut modified, mut untracked, mut conflicted) = (0, 0, 0, 0);
     for entry in statuses.iter() {
         let s = entry.status();
         if s.is_conflicted() {
-            conflicts = true;
-        } else {
-            dirty = true;
+            conflicted += 1;
+            continue;
+        }
+        if s.intersects(staged_flags) {
+            staged += 1;
+        }
+        if s.intersects(modified_flags) {
+            modified += 1;
+        }
+        if s.contains(Status::WT_NEW) {
+            untracked += 1;
         }
     }
 
     // Ahead/behind upstream.
-    let (ahead, behind) = head
-        .shorthand()
-        .and_then(|name| {
-            let local = repo.find_branch(name, BranchType::Local).ok()?;
-            let upstream = local.upstream().ok()?;
+    let upstream_branch = branch
+        .as_deref()
+        .and_then(|name| repo.find_branch(name, BranchType::Local).ok())
+        .and_then(|local| local.upstream().ok());
+    let upstream = upstream_branch
+        .as_ref()
+        .and_then(|b| b.name().ok().flatten())
+        .map(|s| s.to_string());
+    let (ahead, behind) = upstream_branch
+        .as_ref()
+        .and_then(|upstream| {
             let local_oid = head.target()?;
             let upstream_oid = upstream.get().target()?;
             repo.graph_ahead_behind(local_oid, upstream_oid).ok()
         })
         .unwrap_or((0, 0));
 
-    let sync_status = if conflicts {
+    let dirty = staged + modified + untracked > 0;
+    let sync_status = if conflicted > 0 {
         GitSyncStatus::Conflicts
     } else if ahead > 0 && behind > 0 {
         GitSyncStatus::Diverged
@@ -127,63 +274,592 @@ pub fn compute_git_status(path: &Path) -> Option<GitStatus> {
 
     Some(GitStatus {
         branch,
+        detached_commit,
+        upstream,
+        ahead,
+        behind,
+        staged,
+        modified,
+        untracked,
+        conflicted,
+        stash_count,
+        operation,
         sync_status,
     })
 }
 
+fn count_stashes(repo: &mut Repository) -> usize {
+    let mut count = 0;
+    let _ = repo.stash_foreach(|_, _, _| {
+        count += 1;
+        true
+    });
+    count
+}
+
+/// Directories never searched for nested repositories: dependency and build
+/// trees are large and do not contain projects of their own.
+const DISCOVERY_SKIP_DIRS: &[&str] = &["node_modules", "target"];
+
+/// How often group paths are searched again for nested repositories, so repos
+/// cloned or initialized later show up without a restart.
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Find Git repositories nested under `root` (not `root` itself), looking at
+/// most `max_depth` directory levels down, plus the checked-out submodules of
+/// `root` and of every repository found, at any depth. Hidden directories and
+/// [`DISCOVERY_SKIP_DIRS`] are not searched. A depth of 0 disables discovery.
+pub fn discover_repositories(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    if max_depth == 0 {
+        return Vec::new();
+    }
+
+    let mut found = Vec::new();
+    let mut stack = vec![(root.to_path_buf(), 0usize)];
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // `file_type` does not follow symlinks, so linked directories are
+            // skipped and cannot cause cycles.
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || DISCOVERY_SKIP_DIRS.contains(&name.as_ref()) {
+                continue;
+            }
+            let path = entry.path();
+            // A `.git` file instead of a directory marks submodules and linked
+            // worktrees.
+            if path.join(".git").exists() {
+                found.push(path.clone());
+            }
+            if depth + 1 < max_depth {
+                stack.push((path, depth + 1));
+            }
+        }
+    }
+
+    let mut i = 0;
+    let mut with_root: Vec<PathBuf> = std::iter::once(root.to_path_buf())
+        .chain(found.iter().cloned())
+        .collect();
+    while i < with_root.len() {
+        let submodules = Repository::open(&with_root[i])
+            .and_then(|repo| {
+                let workdir = repo.workdir().map(Path::to_path_buf);
+                Ok(repo
+                    .submodules()?
+                    .iter()
+                    .filter(|sm| sm.open().is_ok())
+                    .filter_map(|sm| workdir.as_ref().map(|w| w.join(sm.path())))
+                    .collect::<Vec<_>>())
+            })
+            .unwrap_or_default();
+        for path in submodules {
+            if !with_root.contains(&path) {
+                with_root.push(path);
+            }
+        }
+        i += 1;
+    }
+
+    let mut repos: Vec<PathBuf> = with_root.into_iter().skip(1).collect();
+    repos.sort();
+    repos.dedup();
+    repos
+}
+
+/// How long to wait after the last filesystem event for a repository before
+/// recomputing its status. Editors and `git` itself touch many files in a row
+/// (e.g. `index.lock` -> `index`), so recomputing on every event is wasteful.
+const EVENT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Upper bound on how long a continuous burst of events (a running build, a
+/// large checkout) can postpone a recompute.
+const EVENT_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Safety-net rescan interval for repositories that do have a working watcher,
+/// in case the platform drops events (e.g. inotify queue overflow).
+const WATCHED_RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Returns `true` if a change to `path` can affect the status of the repository
+/// rooted at `root`. Object database and reflog writes never change the status
+/// on their own, and ignored files in the working tree are skipped as well so
+/// build output does not trigger recomputes.
+fn is_relevant_change(repo: &Repository, root: &Path, path: &Path) -> bool {
+    let git_dir = repo.path();
+    if let Ok(rel) = path.strip_prefix(git_dir) {
+        return !(rel.starts_with("objects") || rel.starts_with("logs"));
+    }
+    let workdir = repo.workdir().unwrap_or(root);
+    match path.strip_prefix(workdir) {
+        Ok(rel) if rel.as_os_str().is_empty() => true,
+        Ok(rel) => !repo.is_path_ignored(rel).unwrap_or(false),
+        Err(_) => true,
+    }
+}
+
+/// What to watch for `repo`: its Git directory (HEAD, the index, packed
+/// refs), `refs` recursively, and each directory of the working tree that is
+/// not ignored on its own. Ignored folders such as `target/` or
+/// `node_modules/` can hold more directories than inotify allows watches.
+fn watch_targets(repo: &Repository) -> Vec<(PathBuf, RecursiveMode)> {
+    let git_dir = repo.path().to_path_buf();
+    // Linked worktrees keep refs in the main repository's Git directory.
+    let common_dir = std::fs::read_to_string(git_dir.join("commondir"))
+        .map(|dir| git_dir.join(dir.trim()))
+        .unwrap_or_else(|_| git_dir.clone());
+
+    let mut targets = vec![(git_dir.clone(), RecursiveMode::NonRecursive)];
+    if common_dir != git_dir {
+        targets.push((common_dir.clone(), RecursiveMode::NonRecursive));
+    }
+    targets.push((common_dir.join("refs"), RecursiveMode::Recursive));
+    if let Some(workdir) = repo.workdir() {
+        targets.extend(
+            unignored_dirs(repo, workdir)
+                .into_iter()
+                .map(|dir| (dir, RecursiveMode::NonRecursive)),
+        );
+    }
+    targets
+}
+
+/// `workdir` and the directories below it that are not ignored, skipping
+/// `.git` directories.
+fn unignored_dirs(repo: &Repository, workdir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![workdir.to_path_buf()];
+    let mut next = 0;
+    while next < dirs.len() {
+        let Ok(entries) = std::fs::read_dir(&dirs[next]) else {
+            next += 1;
+            continue;
+        };
+        next += 1;
+        for entry in entries.flatten() {
+            // `file_type` does not follow symlinks, so linked directories are
+            // not walked twice.
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) || entry.file_name() == ".git" {
+                continue;
+            }
+            let path = entry.path();
+            let rel = path.strip_prefix(workdir).unwrap_or(&path);
+            if !repo.is_path_ignored(rel).unwrap_or(false) {
+                dirs.push(path);
+            }
+        }
+    }
+    dirs
+}
+
+/// Per-repository bookkeeping owned by the background thread.
+struct WatchedRepo {
+    /// Opened lazily and reused to filter out ignored paths.
+    repo: Option<Repository>,
+    /// Directories this repository registered with the filesystem watcher
+    /// (see [`watch_targets`]). Empty when the repository is polled.
+    watched_dirs: HashSet<PathBuf>,
+    /// Directories were added, removed or renamed, or ignore rules changed,
+    /// so `watched_dirs` needs to be brought up to date.
+    rewatch: bool,
+    /// Time of the first and the most recent unprocessed event.
+    pending: Option<(Instant, Instant)>,
+    last_computed: Instant,
+}
+
+impl WatchedRepo {
+    fn has_watch(&self) -> bool {
+        !self.watched_dirs.is_empty()
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        match self.pending {
+            Some((first, last)) => {
+                now.duration_since(last) >= EVENT_DEBOUNCE
+                    || now.duration_since(first) >= EVENT_MAX_DELAY
+            }
+            None => false,
+        }
+    }
+
+    /// Whether `path` is in or directly below a directory this repository
+    /// watches.
+    fn watches(&self, path: &Path) -> bool {
+        path.ancestors().any(|dir| self.watched_dirs.contains(dir))
+    }
+}
+
+/// The filesystem watcher, with the number of repositories using each
+/// watched directory: nested repositories and submodules share directories
+/// with the repository around them.
+struct WatchSet {
+    watcher: Option<RecommendedWatcher>,
+    counts: HashMap<PathBuf, usize>,
+}
+
+impl WatchSet {
+    fn add(&mut self, dir: &Path, mode: RecursiveMode) -> notify::Result<()> {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return Err(notify::Error::generic("no file watcher"));
+        };
+        match self.counts.get_mut(dir) {
+            Some(count) => *count += 1,
+            None => {
+                watcher.watch(dir, mode)?;
+                self.counts.insert(dir.to_path_buf(), 1);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, dir: &Path) {
+        let Some(count) = self.counts.get_mut(dir) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(dir);
+            if let Some(watcher) = self.watcher.as_mut() {
+                let _ = watcher.unwatch(dir);
+            }
+        }
+    }
+
+    /// Watch what `entry` needs now and release what it no longer does.
+    /// Falls back to polling the repository when a watch cannot be added,
+    /// e.g. once `fs.inotify.max_user_watches` is used up.
+    fn update(&mut self, entry: &mut WatchedRepo) {
+        entry.rewatch = false;
+        let Some(repo) = entry.repo.as_ref() else {
+            return;
+        };
+        if self.watcher.is_none() {
+            return;
+        }
+        let targets = watch_targets(repo);
+
+        let wanted: HashSet<&PathBuf> = targets.iter().map(|(dir, _)| dir).collect();
+        let unwanted: Vec<PathBuf> = entry
+            .watched_dirs
+            .iter()
+            .filter(|dir| !wanted.contains(dir))
+            .cloned()
+            .collect();
+        for dir in unwanted {
+            self.remove(&dir);
+            entry.watched_dirs.remove(&dir);
+        }
+
+        for (dir, mode) in targets {
+            // Directories may disappear between the walk and here.
+            if entry.watched_dirs.contains(&dir) || !dir.is_dir() {
+                continue;
+            }
+            if let Err(e) = self.add(&dir, mode) {
+                log::warn!(
+                    "Git status: cannot watch {}, falling back to polling: {e}",
+                    dir.display()
+                );
+                for dir in entry.watched_dirs.drain() {
+                    self.remove(&dir);
+                }
+                return;
+            }
+            entry.watched_dirs.insert(dir);
+        }
+    }
+}
+
+/// State of the background thread: the filesystem watcher plus the set of
+/// repositories it is responsible for.
+struct RepoWatcher {
+    watches: WatchSet,
+    repos: HashMap<PathBuf, WatchedRepo>,
+}
+
+impl RepoWatcher {
+    fn new(events: mpsc::Sender<notify::Result<notify::Event>>) -> Self {
+        let watcher = notify::recommended_watcher(move |res| {
+            let _ = events.send(res);
+        })
+        .map_err(|e| log::warn!("Git status: file watcher unavailable, polling only: {e}"))
+        .ok();
+        Self {
+            watches: WatchSet {
+                watcher,
+                counts: HashMap::new(),
+            },
+            repos: HashMap::new(),
+        }
+    }
+
+    /// Start watching newly registered paths and stop watching removed ones.
+    /// Returns the paths that were just added and need an initial compute.
+    fn sync(&mut self, known: &HashSet<PathBuf>) -> Vec<PathBuf> {
+        let removed: Vec<PathBuf> = self
+            .repos
+            .keys()
+            .filter(|p| !known.contains(*p))
+            .cloned()
+            .collect();
+        for path in removed {
+            if let Some(entry) = self.repos.remove(&path) {
+                for dir in &entry.watched_dirs {
+                    self.watches.remove(dir);
+                }
+            }
+        }
+
+        let added: Vec<PathBuf> = known
+            .iter()
+            .filter(|p| !self.repos.contains_key(*p))
+            .cloned()
+            .collect();
+        for path in &added {
+            let entry = self.watch(path);
+            self.repos.insert(path.clone(), entry);
+        }
+        added
+    }
+
+    fn watch(&mut self, path: &Path) -> WatchedRepo {
+        let mut entry = WatchedRepo {
+            repo: Repository::open(path).ok(),
+            watched_dirs: HashSet::new(),
+            rewatch: false,
+            pending: None,
+            last_computed: Instant::now(),
+        };
+        self.watches.update(&mut entry);
+        entry
+    }
+
+    /// Bring the watches of repositories whose directories changed up to
+    /// date. Called once per batch of events rather than per event.
+    fn update_watches(&mut self) {
+        for entry in self.repos.values_mut().filter(|entry| entry.rewatch) {
+            self.watches.update(entry);
+        }
+    }
+
+    /// Record a filesystem event against the repositories it touches.
+    fn handle_event(&mut self, event: notify::Event, now: Instant) {
+        if event.need_rescan() {
+            self.mark_all(now);
+            return;
+        }
+        // Reads (including our own libgit2 status scans) never change state.
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for (root, entry) in self.repos.iter_mut() {
+            let relevant: Vec<&PathBuf> = event
+                .paths
+                .iter()
+                .filter(|p| {
+                    entry.watches(p)
+                        && entry
+                            .repo
+                            .as_ref()
+                            .is_none_or(|repo| is_relevant_change(repo, root, p))
+                })
+                .collect();
+            if relevant.is_empty() {
+                continue;
+            }
+            let first = entry.pending.map_or(now, |(first, _)| first);
+            entry.pending = Some((first, now));
+            entry.rewatch |= relevant.iter().any(|p| {
+                p.is_dir() != entry.watched_dirs.contains(*p)
+                    || p.file_name().is_some_and(|name| name == ".gitignore")
+            });
+        }
+    }
+
+    fn mark_all(&mut self, now: Instant) {
+        for entry in self.repos.values_mut() {
+            let first = entry.pending.map_or(now, |(first, _)| first);
+            entry.pending = Some((first, now));
+            entry.rewatch = entry.has_watch();
+        }
+    }
+
+    /// Paths whose status should be recomputed now: debounced event targets,
+    /// plus polling for repositories without a watcher.
+    fn due_paths(&self, now: Instant, poll_interval: Duration) -> Vec<PathBuf> {
+        self.repos
+            .iter()
+            .filter(|(_, entry)| {
+                let interval = if entry.has_watch() {
+                    WATCHED_RESCAN_INTERVAL
+                } else {
+                    poll_interval
+                };
+                entry.is_due(now) || now.duration_since(entry.last_computed) >= interval
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn mark_computed(&mut self, path: &Path, now: Instant) {
+        let Some(entry) = self.repos.get_mut(path) else {
+            return;
+        };
+        entry.pending = None;
+        entry.last_computed = now;
+
+        // A path that was not a repository when registered may have become
+        // one (`git init`); retry opening and watching it.
+        if entry.repo.is_none() {
+            let fresh = self.watch(path);
+            if fresh.repo.is_some() {
+                self.repos.insert(path.to_path_buf(), fresh);
+            }
+        }
+    }
+}
+
 /// Background-thread Git status cache.
 ///
 /// The UI thread never blocks: [`get_or_refresh`](GitStatusCache::get_or_refresh)
 /// reads from a shared cache and registers unknown paths for the background
-/// thread to compute. The thread polls all known paths every `refresh_interval`
-/// using libgit2 (no external `git` process), so there are no `conhost.exe`
-/// window flashes on Windows.
+/// thread to compute. The thread watches each repository's working tree and Git
+/// directory for filesystem events and recomputes only the repositories that
+/// changed, debouncing bursts of events. Repositories that cannot be watched
+/// are polled every `poll_interval` instead. Each registered path is also
+/// searched for nested repositories and submodules (see
+/// [`discover_repositories`]), which are tracked the same way. Statuses are computed with libgit2
+/// (no external `git` process), so there are no `conhost.exe` window flashes on
+/// Windows.
 pub struct GitStatusCache {
     cache: Arc<Mutex<HashMap<PathBuf, Option<GitStatus>>>>,
     known_paths: Arc<Mutex<HashSet<PathBuf>>>,
+    refresh_requests: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Nested repositories found under each registered path.
+    nested: Arc<Mutex<HashMap<PathBuf, Vec<PathBuf>>>>,
+    nested_depth: Arc<AtomicUsize>,
     shutdown: Arc<AtomicBool>,
     _thread: Option<thread::JoinHandle<()>>,
 }
 
 impl GitStatusCache {
-    /// Create a new cache and spawn the background refresh thread.
-    /// `refresh_interval` controls how often the thread recomputes statuses.
-    pub fn new(refresh_interval: Duration) -> Self {
+    /// Create a new cache and spawn the background watcher thread.
+    /// `poll_interval` controls how often repositories without a working
+    /// filesystem watcher are recomputed.
+    pub fn new(poll_interval: Duration) -> Self {
         let cache: Arc<Mutex<HashMap<PathBuf, Option<GitStatus>>>> =
             Arc::new(Mutex::new(HashMap::new()));
         let known_paths: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+        let refresh_requests: Arc<Mutex<HashSet<PathBuf>>> =
+            Arc::new(Mutex::new(HashSet::new()));
+        let nested: Arc<Mutex<HashMap<PathBuf, Vec<PathBuf>>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        let nested_depth = Arc::new(AtomicUsize::new(DEFAULT_GIT_NESTED_DEPTH));
         let shutdown = Arc::new(AtomicBool::new(false));
 
         let cache_clone = Arc::clone(&cache);
         let paths_clone = Arc::clone(&known_paths);
+        let requests_clone = Arc::clone(&refresh_requests);
+        let nested_clone = Arc::clone(&nested);
+        let depth_clone = Arc::clone(&nested_depth);
         let shutdown_clone = Arc::clone(&shutdown);
 
         let handle = thread::Builder::new()
             .name("git-status-watcher".into())
             .spawn(move || {
+                let (event_tx, event_rx) = mpsc::channel();
+                let mut watcher = RepoWatcher::new(event_tx);
+                // Wake up at least this often so shutdown and newly registered
+                // paths are handled promptly.
+                let step = Duration::from_millis(200);
+                let mut discovered: HashMap<PathBuf, (Vec<PathBuf>, Instant)> = HashMap::new();
+                let mut discovery_depth = depth_clone.load(Ordering::Relaxed);
+
                 while !shutdown_clone.load(Ordering::Relaxed) {
-                    let paths: Vec<PathBuf> =
-                        paths_clone.lock().unwrap().iter().cloned().collect();
-                    for path in &paths {
-                        if shutdown_clone.load(Ordering::Relaxed) {
-                            break;
+                    let roots = paths_clone.lock().unwrap().clone();
+
+                    let depth = depth_clone.load(Ordering::Relaxed);
+                    if depth != discovery_depth {
+                        discovery_depth = depth;
+                        discovered.clear();
+                    }
+                    let mut nested_changed = false;
+                    discovered.retain(|root, _| {
+                        let keep = roots.contains(root);
+                        nested_changed |= !keep;
+                        keep
+                    });
+                    for root in &roots {
+                        let stale = discovered
+                            .get(root)
+                            .is_none_or(|(_, at)| at.elapsed() >= DISCOVERY_INTERVAL);
+                        if stale {
+                            let found = discover_repositories(root, discovery_depth);
+                            nested_changed |=
+                                discovered.get(root).is_none_or(|(list, _)| *list != found);
+                            discovered.insert(root.clone(), (found, Instant::now()));
                         }
-                        let status = compute_git_status(path);
+                    }
+
+                    let mut known = roots;
+                    for (list, _) in discovered.values() {
+                        known.extend(list.iter().cloned());
+                    }
+                    if nested_changed {
+                        *nested_clone.lock().unwrap() = discovered
+                            .iter()
+                            .map(|(root, (list, _))| (root.clone(), list.clone()))
+                            .collect();
                         cache_clone
                             .lock()
                             .unwrap()
-                            .insert(path.clone(), status);
+                            .retain(|path, _| known.contains(path));
+                    }
+                    let added = watcher.sync(&known);
+
+                    let now = Instant::now();
+                    let mut due = added;
+                    for path in requests_clone.lock().unwrap().drain() {
+                        if known.contains(&path) && !due.contains(&path) {
+                            due.push(path);
+                        }
+                    }
+                    for path in watcher.due_paths(now, poll_interval) {
+                        if !due.contains(&path) {
+                            due.push(path);
+                        }
                     }
-                    // Sleep in small increments so shutdown is responsive.
-                    let mut elapsed = Duration::ZERO;
-                    let step = Duration::from_millis(200);
-                    while elapsed < refresh_interval {
+
+                    for path in &due {
                         if shutdown_clone.load(Ordering::Relaxed) {
                             return;
                         }
-                        thread::sleep(step);
-                        elapsed += step;
+                        let status = compute_git_status(path);
+                        cache_clone.lock().unwrap().insert(path.clone(), status);
+                        watcher.mark_computed(path, Instant::now());
+                    }
+
+                    match event_rx.recv_timeout(step) {
+                        Ok(first) => {
+                            let now = Instant::now();
+                            for res in std::iter::once(first).chain(event_rx.try_iter()) {
+                                match res {
+                                    Ok(event) => watcher.handle_event(event, now),
+                                    Err(e) => {
+                                        log::warn!("Git status: watcher error: {e}");
+                                        watcher.mark_all(now);
+                                    }
+                                }
+                            }
+                        }
+                        Err(mpsc::RecvTimeoutError::Timeout) => {}
+                        Err(mpsc::RecvTimeoutError::Disconnected) => thread::sleep(step),
                     }
+                    watcher.update_watches();
                 }
             })
             .ok();
@@ -191,11 +867,23 @@ impl GitStatusCache {
         Self {
             cache,
             known_paths,
+            refresh_requests,
+            nested,
+            nested_depth,
             shutdown,
             _thread: handle,
         }
     }
 
+    /// Ask the background thread to recompute the status of `path` right away,
+    /// e.g. after yaaa itself changed the repository (checkout, commit).
+    pub fn refresh(&self, path: &Path) {
+        self.refresh_requests
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf());
+    }
+
     /// Return a cached status for `path`, registering it for background
     /// refresh if not already known. Returns `None` if the path has not been
     /// checked yet or is not a Git repository.
@@ -208,12 +896,44 @@ impl GitStatusCache {
             .and_then(|opt| opt.clone())
     }
 
-    /// Remove entries for paths that no longer satisfy `predicate`.
+    /// Nested repositories and submodules found under `root`, sorted by path,
+    /// each with its cached status (`None` until computed).
+    pub fn nested(&self, root: &Path) -> Vec<(PathBuf, Option<GitStatus>)> {
+        let paths = self
+            .nested
+            .lock()
+            .unwrap()
+            .get(root)
+            .cloned()
+            .unwrap_or_default();
+        let cache = self.cache.lock().unwrap();
+        paths
+            .into_iter()
+            .map(|path| {
+                let status = cache.get(&path).and_then(|opt| opt.clone());
+                (path, status)
+            })
+            .collect()
+    }
+
+    /// Change how many directory levels below each registered path are
+    /// searched for nested repositories. 0 disables the search.
+    pub fn set_nested_depth(&self, depth: usize) {
+        self.nested_depth.store(depth, Ordering::Relaxed);
+    }
+
+    /// Remove entries for paths that no longer satisfy `predicate`, along with
+    /// the nested repositories found under them.
     pub fn retain<F>(&mut self, mut predicate: F)
     where
         F: FnMut(&Path) -> bool,
     {
-        self.cache.lock().unwrap().retain(|path, _| predicate(path));
+        let mut nested = self.nested.lock().unwrap();
+        nested.retain(|root, _| predicate(root));
+        self.cache
+            .lock()
+            .unwrap()
+            .retain(|path, _| predicate(path) || nested.values().any(|list| list.contains(path)));
         self.known_paths
             .lock()
             .unwrap()
@@ -361,6 +1081,86 @@ mod tests {
         assert_eq!(git_status.sync_status, GitSyncStatus::Conflicts);
     }
 
+    #[test]
+    fn test_compute_git_status_counts() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+
+        run_git(dir, &["init", "--quiet"]);
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "Test"]);
+
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join("b.txt"), "b").unwrap();
+        run_git(dir, &["add", "a.txt", "b.txt"]);
+        run_git(dir, &["commit", "--quiet", "-m", "initial"]);
+
+        // One staged change, one unstaged change, one untracked file and one
+        // stash entry.
+        std::fs::write(dir.join("a.txt"), "stashed").unwrap();
+        run_git(dir, &["stash", "--quiet"]);
+        std::fs::write(dir.join("a.txt"), "a2").unwrap();
+        run_git(dir, &["add", "a.txt"]);
+        std::fs::write(dir.join("b.txt"), "b2").unwrap();
+        std::fs::write(dir.join("c.txt"), "c").unwrap();
+
+        let status = compute_git_status(dir).expect("should detect git repo");
+        assert_eq!(status.staged, 1);
+        assert_eq!(status.modified, 1);
+        assert_eq!(status.untracked, 1);
+        assert_eq!(status.conflicted, 0);
+        assert_eq!(status.stash_count, 1);
+        assert_eq!(status.operation, None);
+        assert_eq!(status.summary(), "1 staged, 1 modified, 1 untracked");
+    }
+
+    #[test]
+    fn test_compute_git_status_ahead_behind_and_detached() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let origin = tmp.path().join("origin");
+        let clone = tmp.path().join("clone");
+        std::fs::create_dir(&origin).unwrap();
+
+        run_git(&origin, &["init", "--quiet"]);
+        run_git(&origin, &["config", "user.email", "test@example.com"]);
+        run_git(&origin, &["config", "user.name", "Test"]);
+        std::fs::write(origin.join("file.txt"), "base").unwrap();
+        run_git(&origin, &["add", "file.txt"]);
+        run_git(&origin, &["commit", "--quiet", "-m", "base"]);
+
+        run_git(
+            tmp.path(),
+            &["clone", "--quiet", origin.to_str().unwrap(), "clone"],
+        );
+        run_git(&clone, &["config", "user.email", "test@example.com"]);
+        run_git(&clone, &["config", "user.name", "Test"]);
+        std::fs::write(clone.join("file.txt"), "local").unwrap();
+        run_git(&clone, &["commit", "--quiet", "-am", "local"]);
+
+        let status = compute_git_status(&clone).expect("should detect git repo");
+        assert_eq!((status.ahead, status.behind), (1, 0));
+        assert_eq!(status.sync_status, GitSyncStatus::NeedsPush);
+        let upstream = status.upstream.expect("clone should track origin");
+        assert!(
+            upstream.starts_with("origin/"),
+            "unexpected upstream: {upstream}"
+        );
+
+        run_git(&clone, &["checkout", "--quiet", "--detach", "HEAD~1"]);
+        let status = compute_git_status(&clone).expect("should detect git repo");
+        assert_eq!(status.branch, None);
+        assert!(status.detached_commit.is_some());
+        assert_eq!(status.upstream, None);
+    }
+
     #[test]
     fn test_compute_git_status_not_a_repo() {
         let tmp = tempfile::tempdir().expect("tempdir");
@@ -395,4 +1195,221 @@ mod tests {
         let status = cache.get_or_refresh(dir).expect("should be cached");
         assert_eq!(status.sync_status, GitSyncStatus::Clean);
     }
+
+    #[test]
+    fn test_cache_refreshes_on_file_change() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+
+        run_git(dir, &["init", "--quiet"]);
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("file.txt"), "hello").unwrap();
+        run_git(dir, &["add", "file.txt"]);
+        run_git(dir, &["commit", "--quiet", "-m", "initial"]);
+
+        // Polling is effectively disabled, so only a filesystem event can
+        // trigger the second recompute.
+        let cache = GitStatusCache::new(Duration::from_secs(3600));
+        assert!(cache.get_or_refresh(dir).is_none());
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(
+            cache
+                .get_or_refresh(dir)
+                .expect("should be cached")
+                .sync_status,
+            GitSyncStatus::Clean
+        );
+
+        std::fs::write(dir.join("file.txt"), "modified").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut status = None;
+        while Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+            status = cache.get_or_refresh(dir).map(|s| s.sync_status);
+            if status == Some(GitSyncStatus::Dirty) {
+                break;
+            }
+        }
+        assert_eq!(status, Some(GitSyncStatus::Dirty));
+    }
+
+    #[test]
+    fn test_cache_watches_new_directories() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        init_repo(dir);
+
+        let cache = GitStatusCache::new(Duration::from_secs(3600));
+        cache.get_or_refresh(dir);
+        thread::sleep(Duration::from_millis(400));
+
+        // An empty directory does not change the status; the file created in
+        // it afterwards is only seen once the directory is watched.
+        std::fs::create_dir(dir.join("new")).unwrap();
+        thread::sleep(Duration::from_millis(600));
+        assert_eq!(
+            cache.get_or_refresh(dir).map(|s| s.sync_status),
+            Some(GitSyncStatus::Clean)
+        );
+        std::fs::write(dir.join("new/file.txt"), "new").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut status = None;
+        while Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+            status = cache.get_or_refresh(dir).map(|s| s.sync_status);
+            if status == Some(GitSyncStatus::Dirty) {
+                break;
+            }
+        }
+        assert_eq!(status, Some(GitSyncStatus::Dirty));
+    }
+
+    fn init_repo(dir: &Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        run_git(dir, &["init", "--quiet"]);
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("file.txt"), "hello").unwrap();
+        run_git(dir, &["add", "file.txt"]);
+        run_git(dir, &["commit", "--quiet", "-m", "initial"]);
+    }
+
+    #[test]
+    fn test_worst_status() {
+        assert_eq!(GitSyncStatus::worst([]), None);
+        assert_eq!(
+            GitSyncStatus::worst([GitSyncStatus::Clean, GitSyncStatus::Dirty]),
+            Some(GitSyncStatus::Dirty)
+        );
+        assert_eq!(
+            GitSyncStatus::worst([
+                GitSyncStatus::NeedsPull,
+                GitSyncStatus::Conflicts,
+                GitSyncStatus::Diverged,
+            ]),
+            Some(GitSyncStatus::Conflicts)
+        );
+    }
+
+    #[test]
+    fn test_discover_repositories() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let root = tmp.path().join("root");
+        init_repo(&root);
+        init_repo(&root.join("libs/a"));
+        init_repo(&root.join("deep/x/y/repo"));
+        init_repo(&root.join(".hidden/repo"));
+        init_repo(&root.join("node_modules/pkg"));
+
+        // A submodule deeper than the search depth is still found through
+        // the parent's submodule list.
+        let upstream = tmp.path().join("upstream");
+        init_repo(&upstream);
+        std::fs::create_dir_all(root.join("third_party/deps")).unwrap();
+        run_git(
+            &root,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "--quiet",
+                upstream.to_str().unwrap(),
+                "third_party/deps/sub",
+            ],
+        );
+
+        assert_eq!(
+            discover_repositories(&root, 2),
+            vec![root.join("libs/a"), root.join("third_party/deps/sub")]
+        );
+        assert!(discover_repositories(&root, 4).contains(&root.join("deep/x/y/repo")));
+        assert!(discover_repositories(&root, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cache_tracks_nested_repository() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let root = tmp.path();
+        init_repo(root);
+        let nested = root.join("services/api");
+        init_repo(&nested);
+        std::fs::write(root.join(".gitignore"), "services/\n").unwrap();
+        run_git(root, &["add", ".gitignore"]);
+        run_git(root, &["commit", "--quiet", "-m", "ignore services"]);
+
+        let cache = GitStatusCache::new(Duration::from_secs(3600));
+        cache.get_or_refresh(root);
+
+        let wait_for = |expected: GitSyncStatus| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let found = cache.nested(root);
+                let status = found
+                    .first()
+                    .and_then(|(_, status)| status.as_ref().map(|s| s.sync_status));
+                if status == Some(expected) || Instant::now() >= deadline {
+                    assert_eq!(found.len(), 1);
+                    assert_eq!(found[0].0, nested);
+                    return status;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        };
+        assert_eq!(wait_for(GitSyncStatus::Clean), Some(GitSyncStatus::Clean));
+
+        // The root ignores `services/`, so this change is only picked up
+        // through the nested repository's own watches.
+        std::fs::write(nested.join("file.txt"), "modified").unwrap();
+        assert_eq!(wait_for(GitSyncStatus::Dirty), Some(GitSyncStatus::Dirty));
+        assert_eq!(
+            cache.get_or_refresh(root).map(|s| s.sync_status),
+            Some(GitSyncStatus::Clean)
+        );
+    }
+
+    #[test]
+    fn test_watch_targets_skip_ignored_dirs() {
+        if !git_available() {
+            return;
+        }
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let root = tmp.path();
+        init_repo(root);
+        for dir in ["src/ui", "target/debug/deps", "node_modules/pkg/lib"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "target/\nnode_modules\n").unwrap();
+
+        let repo = Repository::open(root).unwrap();
+        let targets = watch_targets(&repo);
+        let dirs: HashSet<&Path> = targets.iter().map(|(dir, _)| dir.as_path()).collect();
+        for dir in ["", "src", "src/ui", ".git", ".git/refs"] {
+            assert!(dirs.contains(root.join(dir).as_path()), "{dir} not watched");
+        }
+        for skipped in ["target", "node_modules", ".git/objects"] {
+            let skipped = root.join(skipped);
+            assert!(!dirs.iter().any(|dir| dir.starts_with(&skipped)));
+        }
+    }
 }
@@ -13,6 +13,7 @@ pub fn get_hotkeys() -> BTreeMap<&'static str, &'static str> {
     hotkeys.insert("Ctrl + Shift + Home", "Scroll terminal to top");
     hotkeys.insert("Ctrl + Shift + End", "Scroll terminal to bottom");
     hotkeys.insert("Ctrl + F", "Toggle search in terminal");
+    hotkeys.insert("F11", "Toggle fullscreen");
     hotkeys
 }
 
@@ -27,6 +28,7 @@ pub struct KeyboardEvents {
     pub scroll_page_up: bool,
     pub scroll_page_down: bool,
     pub toggle_search: bool,
+    pub toggle_fullscreen: bool,
 }
 
 pub fn handle_keyboard_events(ctx: &Context, active_group_exists: bool) -> KeyboardEvents {
@@ -43,6 +45,7 @@ pub fn handle_keyboard_events(ctx: &Context, active_group_exists: bool) -> Keybo
         scroll_page_up: false,
         scroll_page_down: false,
         toggle_search: false,
+        toggle_fullscreen: false,
     };
 
     if input.key_pressed(egui::Key::Tab) && input.modifiers.ctrl {
@@ -118,5 +121,10 @@ pub fn handle_keyboard_events(ctx: &Context, active_group_exists: bool) -> Keybo
         events.toggle_search = true;
     }
 
+    if input.key_pressed(egui::Key::F11) {
+        ctx.input_mut(|i| i.consume_key(i.modifiers, egui::Key::F11));
+        events.toggle_fullscreen = true;
+    }
+
     events
 }
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// A TCP port some process of a tab is listening on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningPort {
+    pub port: u16,
+    pub address: IpAddr,
+    pub pid: u32,
+}
+
+impl ListeningPort {
+    /// The address the socket is bound to, e.g. "127.0.0.1:3000",
+    /// "0.0.0.0:8080" or "[::]:5432".
+    pub fn label(&self) -> String {
+        SocketAddr::new(self.address, self.port).to_string()
+    }
+
+    /// URL to open the port in a browser. Sockets bound to all interfaces or
+    /// to loopback are opened on `localhost`, others on their own address.
+    pub fn url(&self) -> String {
+        if self.address.is_unspecified() || self.address.is_loopback() {
+            format!("http://localhost:{}", self.port)
+        } else {
+            format!("http://{}", self.label())
+        }
+    }
+}
+
+/// Listening TCP sockets of the system, keyed by socket inode.
+///
+/// Only Linux is supported: sockets are read from `/proc/net/tcp{,6}` and
+/// matched to processes through the `socket:[inode]` links in
+/// `/proc/<pid>/fd`. Elsewhere the table is always empty.
+#[derive(Debug, Default)]
+pub struct SocketTable {
+    listening: HashMap<u64, (IpAddr, u16)>,
+}
+
+impl SocketTable {
+    pub fn read() -> Self {
+        let mut table = Self::default();
+        #[cfg(target_os = "linux")]
+        for file in ["/proc/net/tcp", "/proc/net/tcp6"] {
+            if let Ok(contents) = std::fs::read_to_string(file) {
+                table.listening.extend(parse_proc_net_tcp(&contents));
+            }
+        }
+        table
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listening.is_empty()
+    }
+
+    /// Ports that any of `pids` listens on, sorted by port. A port bound on
+    /// both IPv4 and IPv6 by the same process is reported once, with the
+    /// address found first.
+    pub fn ports_for(&self, pids: impl IntoIterator<Item = u32>) -> Vec<ListeningPort> {
+        let mut ports: Vec<ListeningPort> = Vec::new();
+        if self.listening.is_empty() {
+            return ports;
+        }
+        for pid in pids {
+            for inode in socket_inodes(pid) {
+                let Some(&(address, port)) = self.listening.get(&inode) else {
+                    continue;
+                };
+                if !ports.iter().any(|p| p.port == port && p.pid == pid) {
+                    ports.push(ListeningPort { port, address, pid });
+                }
+            }
+        }
+        ports.sort_by_key(|p| (p.port, p.pid));
+        ports
+    }
+}
+
+/// Socket inodes of the open file descriptors of `pid`. Unreadable (other
+/// users' processes, or a process that just exited) yields nothing.
+fn socket_inodes(pid: u32) -> Vec<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+            .filter_map(|target| parse_socket_link(&target.to_string_lossy()))
+            .collect()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Vec::new()
+    }
+}
+
+/// Inode of an fd link target like "socket:[123456]".
+fn parse_socket_link(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// TCP state code of LISTEN in `/proc/net/tcp`.
+const TCP_LISTEN: &str = "0A";
+
+/// (inode, (address, port)) of every listening socket in the contents of
+/// `/proc/net/tcp` or `/proc/net/tcp6`.
+fn parse_proc_net_tcp(contents: &str) -> Vec<(u64, (IpAddr, u16))> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                return None;
+            }
+            let (address, port) = fields[1].split_once(':')?;
+            let address = parse_hex_address(address)?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields[9].parse().ok()?;
+            Some((inode, (address, port)))
+        })
+        .collect()
+}
+
+/// The kernel prints addresses as 32-bit words in host byte order, so each
+/// word's bytes are reversed on little-endian machines.
+fn parse_hex_address(hex: &str) -> Option<IpAddr> {
+    let mut bytes = Vec::with_capacity(16);
+    for chunk in hex.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    match bytes.len() {
+        4 => Some(IpAddr::V4(Ipv4Addr::new(
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ))),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn test_parse_proc_net_tcp() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0BB8 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 41299 1 0000000000000000 20 4 30 10 -1
+   2: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41300 1 0000000000000000 100 0 0 10 0
+";
+        let parsed = parse_proc_net_tcp(tcp);
+        assert_eq!(
+            parsed,
+            vec![
+                (41234, ("127.0.0.1".parse().unwrap(), 3000)),
+                (41300, ("0.0.0.0".parse().unwrap(), 8080)),
+            ]
+        );
+
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:1538 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 52001 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(
+            parse_proc_net_tcp(tcp6),
+            vec![(52001, ("::1".parse().unwrap(), 5432))]
+        );
+    }
+
+    #[test]
+    fn test_label_and_url() {
+        let port = |address: &str, port| ListeningPort {
+            port,
+            address: address.parse().unwrap(),
+            pid: 1,
+        };
+        assert_eq!(port("127.0.0.1", 3000).label(), "127.0.0.1:3000");
+        assert_eq!(port("127.0.0.1", 3000).url(), "http://localhost:3000");
+        assert_eq!(port("0.0.0.0", 8080).label(), "0.0.0.0:8080");
+        assert_eq!(port("0.0.0.0", 8080).url(), "http://localhost:8080");
+        assert_eq!(port("::", 5432).label(), "[::]:5432");
+        assert_eq!(port("192.168.1.5", 80).url(), "http://192.168.1.5:80");
+        assert_eq!(port("fe80::1", 80).url(), "http://[fe80::1]:80");
+    }
+
+    #[test]
+    fn test_parse_socket_link() {
+        assert_eq!(parse_socket_link("socket:[123456]"), Some(123456));
+        assert_eq!(parse_socket_link("pipe:[123456]"), None);
+        assert_eq!(parse_socket_link("/dev/pts/3"), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_finds_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let ports = SocketTable::read().ports_for([std::process::id()]);
+        assert!(
+            ports.iter().any(|p| p.port == port),
+            "{} not in {:?}",
+            port,
+            ports
+        );
+    }
+}
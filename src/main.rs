@@ -6,19 +6,29 @@ use egui::IconData;
 mod app;
 mod config;
 mod constants;
+mod executables;
 mod font_setup;
+mod git_branches;
+mod git_commit;
+mod git_fetch;
 mod git_status;
 mod hotkeys;
+mod listening_ports;
 mod menu;
+mod resource_limits;
+mod shell_env;
 mod system_monitor;
 mod terminal;
 mod theme;
+mod theme_import;
 mod ui;
 
 const APP_ICON: &[u8] = include_bytes!("icons/app_icon.png");
 
 fn main() -> eframe::Result {
     env_logger::init();
+    // While this is the only thread, as it sets PATH.
+    shell_env::init();
 
     log_startup_env();
     let icon = load_icon();
@@ -118,14 +128,25 @@ fn log_startup_env() {
 }
 
 fn try_run(renderer: eframe::Renderer, icon: IconData) -> eframe::Result {
-    let viewport = egui::ViewportBuilder::default()
-        .with_inner_size([400.0, 300.0])
+    // Reopen the main window the way it was left. A position on a monitor
+    // that is gone is corrected by the app on the first frame.
+    let window = config::Settings::load().main_window;
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(window.size.unwrap_or(constants::DEFAULT_WINDOW_SIZE))
         .with_min_inner_size([300.0, 220.0])
+        .with_maximized(window.maximized)
+        .with_fullscreen(window.fullscreen)
         .with_title("YAAA byOrlov")
         .with_app_id("yaaa")
         .with_icon(icon)
         .with_transparent(true)
         .with_has_shadow(false);
+    if let Some(pos) = window.pos {
+        viewport = viewport.with_position(pos);
+    }
+    if window.always_on_top {
+        viewport = viewport.with_always_on_top();
+    }
 
     let native_options = eframe::NativeOptions {
         viewport,
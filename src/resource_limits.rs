@@ -0,0 +1,394 @@
+#[cfg(target_os = "linux")]
+use std::process::{Command, Stdio};
+
+/// Limits on the processes of an agent tab. A limit of 0 is no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub memory_kb: u64,
+    /// CPU time of each process, enforced by `SystemMonitor` with
+    /// `RLIMIT_CPU` as a backstop.
+    pub cpu_time_secs: u64,
+    /// Open file descriptors per process, enforced with `RLIMIT_NOFILE`.
+    pub open_files: u64,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.memory_kb == 0 && self.cpu_time_secs == 0 && self.open_files == 0
+    }
+}
+
+/// Usage of a limited tab at the last refresh, measured the way each limit is
+/// enforced: memory for the whole process tree, CPU time and open files for
+/// the busiest single process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LimitUsage {
+    pub memory_kb: u64,
+    pub cpu_time_secs: u64,
+    pub open_files: u64,
+}
+
+/// The limit a tab's process was killed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKill {
+    Memory,
+    CpuTime,
+}
+
+impl LimitKill {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LimitKill::Memory => "memory limit",
+            LimitKill::CpuTime => "CPU time limit",
+        }
+    }
+}
+
+/// Margin of `RLIMIT_CPU` over the CPU time limit, so the watchdog in
+/// `SystemMonitor` gets to kill first.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const CPU_TIME_BACKSTOP_SECS: u64 = 10;
+
+/// How the memory limit of a tab is enforced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryEnforcement {
+    /// No memory limit.
+    None,
+    /// The tab was started in its own transient systemd scope (the unit name)
+    /// with `MemoryMax`; the kernel's OOM killer enforces it and systemd
+    /// stops the whole scope when it fires.
+    Scope(String),
+    /// No systemd user manager: `SystemMonitor` kills the tree when a sample
+    /// exceeds the limit.
+    Watchdog,
+}
+
+/// The command that starts `program` with `args` for tab `tab_id` under
+/// `limits`, and how its memory limit is enforced. On Linux a memory limit
+/// starts the program through `systemd-run --user --scope`, so the whole
+/// tree lives in a cgroup with `MemoryMax` from its first instruction.
+pub fn limited_command(
+    tab_id: u64,
+    limits: &ResourceLimits,
+    program: String,
+    args: Vec<String>,
+) -> (String, Vec<String>, MemoryEnforcement) {
+    if limits.memory_kb == 0 {
+        return (program, args, MemoryEnforcement::None);
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(systemd_run) = scope::systemd_run() {
+        let unit = scope::unit_name(tab_id);
+        let args = scope::args(&unit, limits.memory_kb, program, args);
+        return (systemd_run, args, MemoryEnforcement::Scope(unit));
+    }
+    let _ = tab_id;
+    (program, args, MemoryEnforcement::Watchdog)
+}
+
+/// Set the rlimits of `limits` on process `pid`. Children inherit them, so
+/// this only needs to run once per process. Does nothing outside Linux.
+pub fn apply(pid: u32, limits: &ResourceLimits) {
+    #[cfg(target_os = "linux")]
+    {
+        if limits.cpu_time_secs > 0 {
+            // `SystemMonitor` kills at the limit; this only catches processes
+            // it misses. SIGXCPU at the soft limit, SIGKILL a few seconds
+            // later.
+            let soft = limits.cpu_time_secs + CPU_TIME_BACKSTOP_SECS;
+            let result = rlimit::set(pid, rlimit::Resource::CpuTime, soft, soft + 5);
+            if let Err(e) = result {
+                log::warn!("Failed to limit CPU time of {}: {}", pid, e);
+            }
+        }
+        if limits.open_files > 0 {
+            let result = rlimit::set(
+                pid,
+                rlimit::Resource::OpenFiles,
+                limits.open_files,
+                limits.open_files,
+            );
+            if let Err(e) = result {
+                log::warn!("Failed to limit open files of {}: {}", pid, e);
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (pid, limits);
+}
+
+/// Whether the tab's scope was stopped because the OOM killer fired in it.
+/// Call once its processes are gone.
+pub fn oom_killed(memory: &MemoryEnforcement) -> bool {
+    #[cfg(target_os = "linux")]
+    if let MemoryEnforcement::Scope(unit) = memory {
+        return scope::result(unit).as_deref() == Some("oom-kill");
+    }
+    let _ = memory;
+    false
+}
+
+/// Forget the tab's scope. A scope that failed, e.g. after an OOM kill, stays
+/// loaded until reset; one that ended normally is already gone.
+pub fn release(memory: &MemoryEnforcement) {
+    #[cfg(target_os = "linux")]
+    if let MemoryEnforcement::Scope(unit) = memory {
+        scope::reset_failed(unit);
+    }
+    let _ = memory;
+}
+
+/// CPU time (user + system) process `pid` has used so far, in seconds.
+pub fn cpu_time_secs(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let ticks = parse_stat_cpu_ticks(&stat)?;
+        // SAFETY: sysconf has no preconditions.
+        let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        (ticks_per_sec > 0).then(|| ticks / ticks_per_sec as u64)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Number of open file descriptors of process `pid`.
+pub fn open_files(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_dir(format!("/proc/{}/fd", pid))
+            .ok()
+            .map(|entries| entries.count() as u64)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// utime + stime from the contents of `/proc/<pid>/stat`. The command name in
+/// field 2 may contain spaces and parentheses, so fields are counted from the
+/// last ')'.
+fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // `fields[0]` is field 3 (state); utime and stime are fields 14 and 15.
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+#[cfg(target_os = "linux")]
+mod rlimit {
+    use std::io;
+
+    pub enum Resource {
+        CpuTime,
+        OpenFiles,
+    }
+
+    /// Set the limits of `resource` for `pid`. The hard limit is capped at the
+    /// current one: raising it needs privileges.
+    pub fn set(pid: u32, resource: Resource, soft: u64, hard: u64) -> io::Result<()> {
+        let pid = pid as libc::pid_t;
+        let mut old = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: a null new limit only reads the current one into `old`.
+        check(unsafe { prlimit(pid, &resource, std::ptr::null(), &mut old) })?;
+        let hard = (hard as libc::rlim_t).min(old.rlim_max);
+        let new = libc::rlimit {
+            rlim_cur: (soft as libc::rlim_t).min(hard),
+            rlim_max: hard,
+        };
+        // SAFETY: `new` is a valid rlimit and the old limit is not requested.
+        check(unsafe { prlimit(pid, &resource, &new, std::ptr::null_mut()) })
+    }
+
+    /// The resource constants have different types on glibc and musl, so they
+    /// are only named at the call.
+    unsafe fn prlimit(
+        pid: libc::pid_t,
+        resource: &Resource,
+        new: *const libc::rlimit,
+        old: *mut libc::rlimit,
+    ) -> libc::c_int {
+        match resource {
+            Resource::CpuTime => libc::prlimit(pid, libc::RLIMIT_CPU, new, old),
+            Resource::OpenFiles => libc::prlimit(pid, libc::RLIMIT_NOFILE, new, old),
+        }
+    }
+
+    fn check(ret: libc::c_int) -> io::Result<()> {
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+/// Transient systemd scopes for tabs with a memory limit. Each tab gets
+/// its own unit under the user manager, so the user's session scope is left
+/// alone.
+#[cfg(target_os = "linux")]
+mod scope {
+    use super::{Command, Stdio};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::OnceLock;
+
+    /// Path of `systemd-run` if it can start scopes with a memory limit, i.e.
+    /// a user manager runs and has the memory controller. Checked once.
+    pub fn systemd_run() -> Option<String> {
+        static SYSTEMD_RUN: OnceLock<Option<String>> = OnceLock::new();
+        SYSTEMD_RUN
+            .get_or_init(|| {
+                let path = crate::executables::resolve("systemd-run")?;
+                let path = path.to_string_lossy().into_owned();
+                let mut check = args(&unit_name(0), 1024 * 1024, "true".into(), Vec::new());
+                check.insert(0, "--collect".into());
+                let ok = Command::new(&path)
+                    .args(&check)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .is_ok_and(|status| status.success());
+                if !ok {
+                    log::info!("systemd user scopes unavailable; memory limits use the watchdog");
+                }
+                ok.then_some(path)
+            })
+            .clone()
+    }
+
+    /// A unit name no other tab, instance or earlier start of the same tab
+    /// uses.
+    pub fn unit_name(tab_id: u64) -> String {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        format!(
+            "yaaa-tab-{}-{}-{}",
+            std::process::id(),
+            tab_id,
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// `systemd-run` arguments that run `program` in scope `unit` with at
+    /// most `memory_kb` of memory and no swap.
+    pub fn args(unit: &str, memory_kb: u64, program: String, args: Vec<String>) -> Vec<String> {
+        let mut all: Vec<String> = [
+            "--user",
+            "--scope",
+            "--quiet",
+            "--unit",
+            unit,
+            "--property",
+            &format!("MemoryMax={}", memory_kb * 1024),
+            // Without this the limit only pushes the tree into swap.
+            "--property",
+            "MemorySwapMax=0",
+            "--",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        all.push(program);
+        all.extend(args);
+        all
+    }
+
+    /// `Result` of scope `unit`, e.g. "success" or "oom-kill".
+    pub fn result(unit: &str) -> Option<String> {
+        let output = Command::new("systemctl")
+            .args(["--user", "show", "--property=Result", "--value"])
+            .arg(format!("{}.scope", unit))
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Unload scope `unit` if it failed, without waiting for systemctl.
+    pub fn reset_failed(unit: &str) {
+        let unit = format!("{}.scope", unit);
+        std::thread::spawn(move || {
+            let _ = Command::new("systemctl")
+                .args(["--user", "reset-failed", "--quiet"])
+                .arg(&unit)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat_cpu_ticks() {
+        let stat = "4242 (node (worker) 1) S 4200 4242 4200 34816 4242 4194304 \
+                    5012 0 3 0 1234 567 0 0 20 0 11 0 99817 1215311872 26431";
+        assert_eq!(parse_stat_cpu_ticks(stat), Some(1234 + 567));
+        assert_eq!(parse_stat_cpu_ticks("4242 (node) S 1"), None);
+    }
+
+    #[test]
+    fn test_limited_command() {
+        let limits = ResourceLimits {
+            open_files: 64,
+            ..Default::default()
+        };
+        let (program, args, memory) =
+            limited_command(1, &limits, "claude".into(), vec!["--verbose".into()]);
+        assert_eq!(
+            (program.as_str(), memory),
+            ("claude", MemoryEnforcement::None)
+        );
+        assert_eq!(args, ["--verbose"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_scope_args() {
+        let args = scope::args("yaaa-tab-1", 1024, "claude".into(), vec!["-c".into()]);
+        assert_eq!(
+            args,
+            [
+                "--user",
+                "--scope",
+                "--quiet",
+                "--unit",
+                "yaaa-tab-1",
+                "--property",
+                "MemoryMax=1048576",
+                "--property",
+                "MemorySwapMax=0",
+                "--",
+                "claude",
+                "-c"
+            ]
+        );
+        assert_ne!(scope::unit_name(1), scope::unit_name(1));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_own_usage_is_readable() {
+        let pid = std::process::id();
+        assert!(cpu_time_secs(pid).is_some());
+        assert!(open_files(pid).unwrap() >= 3);
+    }
+}
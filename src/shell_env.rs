@@ -0,0 +1,345 @@
+use crate::constants::SHELL_ENV_FILE;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Printed around the environment dump so output of the user's rc files
+/// can be told apart from it.
+const MARKER: &str = "__YAAA_SHELL_ENV__";
+
+/// How long the login shell may take before it is given up on.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Variables that describe the capturing shell or its (missing) terminal
+/// rather than the user's setup; never passed on to tabs.
+const SKIPPED_VARS: &[&str] = &[
+    "_",
+    "PWD",
+    "OLDPWD",
+    "SHLVL",
+    "TERM",
+    "COLORTERM",
+    "TERM_PROGRAM",
+    "TERM_PROGRAM_VERSION",
+    "COLUMNS",
+    "LINES",
+];
+
+/// Variables of the session the app runs in. The login shell may set them
+/// differently or not at all, and overriding them breaks ssh-agent, GUI and
+/// D-Bus use in tabs.
+const SESSION_VARS: &[&str] = &[
+    "SSH_AUTH_SOCK",
+    "SSH_AGENT_PID",
+    "SSH_CONNECTION",
+    "SSH_CLIENT",
+    "SSH_TTY",
+    "GPG_TTY",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "XDG_RUNTIME_DIR",
+];
+const SESSION_VAR_PREFIXES: &[&str] = &["XDG_SESSION_"];
+
+/// The only variables written to the cache: what command lookup needs. The
+/// rest of the environment may hold credentials.
+const CACHED_VARS: &[&str] = &["PATH"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    /// Not supported on this platform.
+    Unavailable,
+    Loading,
+    /// Variables captured from `shell`.
+    Ready {
+        shell: String,
+        count: usize,
+    },
+    Failed(String),
+}
+
+struct State {
+    /// Environment of the last capture in this run.
+    env: Option<HashMap<String, String>>,
+    /// [`CACHED_VARS`] from the previous run, for command lookup until the
+    /// capture is done.
+    cached: Option<HashMap<String, String>>,
+    status: Status,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    env: None,
+    cached: None,
+    status: Status::Unavailable,
+});
+
+fn state() -> std::sync::MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Apps started from a desktop menu do not get the `PATH` set up by the
+/// user's login shell (`~/.local/bin`, nvm, ...). Put the `PATH` cached by
+/// the last run on this process, for terminals to inherit. Must be called
+/// before any other thread exists: changing the environment races with
+/// threads reading it, including C libraries calling `getenv`.
+pub fn init() {
+    if !cfg!(unix) {
+        return;
+    }
+    let Some(cached) = load_cache() else {
+        return;
+    };
+    if let Some(path) = cached.get("PATH") {
+        std::env::set_var("PATH", path);
+    }
+    state().cached = Some(cached);
+}
+
+/// Capture the login-shell environment in the background. Commands are
+/// looked up with it as soon as it is there; its `PATH` is cached for
+/// [`init`], so terminals only inherit it from the next launch on.
+pub fn refresh(ctx: egui::Context) {
+    if !cfg!(unix) {
+        return;
+    }
+    {
+        let mut state = state();
+        if state.status == Status::Loading {
+            return;
+        }
+        state.status = Status::Loading;
+    }
+    std::thread::spawn(move || {
+        let shell = login_shell();
+        let result = capture(&shell);
+        let mut state = state();
+        match result {
+            Ok(env) => {
+                save_cache(&env);
+                state.status = Status::Ready {
+                    shell,
+                    count: env.len(),
+                };
+                state.env = Some(env);
+            }
+            Err(e) => {
+                log::warn!("Could not capture the environment of {}: {}", shell, e);
+                state.status = Status::Failed(e);
+            }
+        }
+        drop(state);
+        ctx.request_repaint();
+    });
+}
+
+pub fn status() -> Status {
+    state().status.clone()
+}
+
+/// Value of `name` in the captured environment, falling back to the cache
+/// and then to this process' own.
+pub fn var(name: &str) -> Option<String> {
+    let state = state();
+    state
+        .env
+        .as_ref()
+        .and_then(|env| env.get(name))
+        .or_else(|| state.cached.as_ref()?.get(name))
+        .cloned()
+        .or_else(|| std::env::var(name).ok())
+}
+
+fn login_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string())
+}
+
+fn is_passed_on(name: &str) -> bool {
+    !name.is_empty()
+        && !SKIPPED_VARS.contains(&name)
+        && !SESSION_VARS.contains(&name)
+        && !SESSION_VAR_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// Run `shell` as an interactive login shell and read its environment.
+#[cfg(unix)]
+fn capture(shell: &str) -> Result<HashMap<String, String>, String> {
+    use std::io::Read;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::time::Instant;
+
+    let script = format!("printf '{0}'; env -0; printf '{0}'", MARKER);
+    let mut child = Command::new(shell)
+        .args(["-l", "-i", "-c", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        // So whatever the profile starts can be killed along with it.
+        .process_group(0)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Daemons started by the profile (gpg-agent, ssh-agent, ...) may inherit
+    // stdout and keep it open after the shell exited, so read until the dump
+    // is complete rather than until the end of the output.
+    let mut stdout = child.stdout.take().ok_or("No output")?;
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buffer = [0; 8192];
+        while let Ok(read @ 1..) = stdout.read(&mut buffer) {
+            if sender.send(buffer[..read].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + CAPTURE_TIMEOUT;
+    let mut output = Vec::new();
+    let env = loop {
+        if let Some(env) = parse_env(&output) {
+            break Some(env);
+        }
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(chunk) => output.extend(chunk),
+            Err(RecvTimeoutError::Disconnected) => break None,
+            Err(RecvTimeoutError::Timeout) => {
+                kill_group(&mut child);
+                return Err(format!("timed out after {} s", CAPTURE_TIMEOUT.as_secs()));
+            }
+        }
+    };
+
+    // Give the shell until the deadline to finish its logout scripts.
+    while child.try_wait().map_err(|e| e.to_string())?.is_none() {
+        if Instant::now() >= deadline {
+            kill_group(&mut child);
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    env.ok_or_else(|| "no environment in the shell output".to_string())
+}
+
+#[cfg(not(unix))]
+fn capture(_shell: &str) -> Result<HashMap<String, String>, String> {
+    Err("not supported on this platform".to_string())
+}
+
+/// Kill `child` and everything else in its process group.
+#[cfg(unix)]
+fn kill_group(child: &mut std::process::Child) {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Variables between the markers of a `env -0` dump that are passed on to
+/// tabs.
+fn parse_env(output: &[u8]) -> Option<HashMap<String, String>> {
+    let text = String::from_utf8_lossy(output);
+    let start = text.find(MARKER)? + MARKER.len();
+    let end = start + text[start..].find(MARKER)?;
+    let env: HashMap<String, String> = text[start..end]
+        .split('\0')
+        .filter_map(|entry| entry.split_once('='))
+        .filter(|(name, _)| is_passed_on(name))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    (!env.is_empty()).then_some(env)
+}
+
+fn load_cache() -> Option<HashMap<String, String>> {
+    let path = crate::config::config_dir()?.join(SHELL_ENV_FILE);
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut cached: HashMap<String, String> = serde_json::from_str(&content).ok()?;
+    cached.retain(|name, _| CACHED_VARS.contains(&name.as_str()));
+    Some(cached)
+}
+
+fn save_cache(env: &HashMap<String, String>) {
+    let Some(dir) = crate::config::config_dir() else {
+        return;
+    };
+    let cached: HashMap<&String, &String> = env
+        .iter()
+        .filter(|(name, _)| CACHED_VARS.contains(&name.as_str()))
+        .collect();
+    if let Ok(content) = serde_json::to_string_pretty(&cached) {
+        if let Err(e) = write_private(&dir.join(SHELL_ENV_FILE), &content) {
+            log::warn!("Could not save {}: {}", SHELL_ENV_FILE, e);
+        }
+    }
+}
+
+/// Write `content` to `path`, readable by the user only.
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // `mode` only applies to new files; older versions used the umask.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let output = format!(
+            "motd from .zshrc\n{0}PATH=/home/u/.local/bin:/usr/bin\0EDITOR=vim\0\
+             SHLVL=2\0MULTI=a\nb=c\0SSH_AUTH_SOCK=/tmp/old\0XDG_SESSION_ID=3\0{0}",
+            MARKER
+        );
+        let env = parse_env(output.as_bytes()).unwrap();
+        assert_eq!(env.len(), 3);
+        assert_eq!(env["PATH"], "/home/u/.local/bin:/usr/bin");
+        assert_eq!(env["EDITOR"], "vim");
+        assert_eq!(env["MULTI"], "a\nb=c");
+
+        assert_eq!(parse_env(b"no markers here"), None);
+        assert_eq!(parse_env(format!("{0}{0}", MARKER).as_bytes()), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_capture_does_not_wait_for_daemons() {
+        // A "shell" that leaves a process behind holding its stdout.
+        let dir = tempfile::tempdir().unwrap();
+        let shell = dir.path().join("shell");
+        std::fs::write(
+            &shell,
+            format!(
+                "#!/bin/sh\nsleep 5 &\nprintf '{0}EDITOR=vim\\0TERM=dumb\\0{0}'\n",
+                MARKER
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&shell, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let env = capture(shell.to_str().unwrap()).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert_eq!(env.len(), 1);
+        assert_eq!(env["EDITOR"], "vim");
+    }
+}
@@ -1,13 +1,284 @@
-use std::collections::{HashMap, HashSet};
+use crate::listening_ports::{ListeningPort, SocketTable};
+use crate::resource_limits::{self, LimitKill, LimitUsage, MemoryEnforcement, ResourceLimits};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 use sysinfo::{
-    MemoryRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System,
+    CpuRefreshKind, MemoryRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind,
+    Signal, System, UpdateKind,
 };
 
+/// How often process data is refreshed, on the UI thread and on the sampler
+/// thread. Usage histories get one sample per refresh of the sampler.
+const PROCESS_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Samples kept per usage history: two minutes at one sample per refresh.
+pub const HISTORY_LEN: usize = 60;
+
 pub struct MemoryInfo {
     pub percent: f32,
 }
 
+/// Memory and CPU of a process tree (or the whole system) at one refresh.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageSample {
+    pub memory_kb: u64,
+    pub cpu_percent: f32,
+}
+
+/// Ring buffer of the last [`HISTORY_LEN`] usage samples, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct UsageHistory {
+    samples: VecDeque<UsageSample>,
+}
+
+impl UsageHistory {
+    pub fn push(&mut self, sample: UsageSample) {
+        if self.samples.len() == HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &UsageSample> {
+        self.samples.iter()
+    }
+
+    /// The limit that every sample of the last `limits.sustained` exceeded,
+    /// if any. Memory is checked before CPU.
+    pub fn sustained_breach(&self, limits: &UsageLimits) -> Option<UsageAlert> {
+        let needed = (limits.sustained.as_secs_f32() / PROCESS_REFRESH_INTERVAL.as_secs_f32())
+            .ceil()
+            .clamp(1.0, HISTORY_LEN as f32) as usize;
+        if self.samples.len() < needed {
+            return None;
+        }
+        let recent = || self.samples.iter().rev().take(needed);
+        if limits.memory_kb > 0 && recent().all(|s| s.memory_kb > limits.memory_kb) {
+            Some(UsageAlert::Memory)
+        } else if limits.cpu_percent > 0.0 && recent().all(|s| s.cpu_percent > limits.cpu_percent) {
+            Some(UsageAlert::Cpu)
+        } else {
+            None
+        }
+    }
+}
+
+/// Per-tab thresholds for usage alerts. A limit of 0 disables that check.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct UsageLimits {
+    pub memory_kb: u64,
+    pub cpu_percent: f32,
+    /// How long a limit must be exceeded before the alert is raised.
+    pub sustained: Duration,
+}
+
+/// Which limit a tab's process tree has exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageAlert {
+    Memory,
+    Cpu,
+}
+
+/// Usage data shared between [`SystemMonitor`] and its sampler thread.
+#[derive(Default)]
+struct UsageState {
+    /// (tab id, root pid, alert thresholds) of every tab, set by the UI.
+    tabs: Vec<(u64, u32, UsageLimits)>,
+    system_history: UsageHistory,
+    tab_histories: HashMap<u64, UsageHistory>,
+    tab_alerts: HashMap<u64, UsageAlert>,
+    /// Listening TCP ports per tab, rescanned with every sample.
+    tab_ports: HashMap<u64, Vec<ListeningPort>>,
+    /// Alerts raised since the UI last took them.
+    raised: Vec<(u64, UsageAlert)>,
+}
+
+impl UsageState {
+    /// Append one sample per tab, drop histories of closed tabs and
+    /// re-evaluate the alerts.
+    fn record(
+        &mut self,
+        system: UsageSample,
+        samples: &[(u64, UsageSample)],
+        ports: HashMap<u64, Vec<ListeningPort>>,
+    ) {
+        self.system_history.push(system);
+        let tabs = &self.tabs;
+        self.tab_histories
+            .retain(|id, _| tabs.iter().any(|(tab_id, _, _)| tab_id == id));
+        self.tab_alerts
+            .retain(|id, _| tabs.iter().any(|(tab_id, _, _)| tab_id == id));
+        self.tab_ports = ports;
+        self.tab_ports
+            .retain(|id, _| tabs.iter().any(|(tab_id, _, _)| tab_id == id));
+
+        for &(tab_id, sample) in samples {
+            let Some(&(_, _, limits)) = tabs.iter().find(|(id, _, _)| *id == tab_id) else {
+                continue;
+            };
+            let history = self.tab_histories.entry(tab_id).or_default();
+            history.push(sample);
+            match history.sustained_breach(&limits) {
+                Some(alert) => {
+                    if self.tab_alerts.insert(tab_id, alert) != Some(alert) {
+                        self.raised.push((tab_id, alert));
+                    }
+                }
+                None => {
+                    self.tab_alerts.remove(&tab_id);
+                }
+            }
+        }
+    }
+}
+
+/// Process snapshot of the sampler thread. It is separate from the UI's so
+/// samples keep their pace while no frames are drawn, e.g. when the window
+/// is hidden.
+struct UsageSampler {
+    system: System,
+}
+
+impl UsageSampler {
+    fn new() -> Self {
+        Self {
+            system: System::new_with_specifics(
+                RefreshKind::nothing()
+                    .with_memory(MemoryRefreshKind::everything())
+                    .with_cpu(CpuRefreshKind::nothing().with_cpu_usage())
+                    .with_processes(Self::refresh_kind()),
+            ),
+        }
+    }
+
+    fn refresh_kind() -> ProcessRefreshKind {
+        ProcessRefreshKind::nothing().with_memory().with_cpu()
+    }
+
+    /// Refresh the snapshot and record one sample for the system and for
+    /// every tab in `state`, along with the tab's listening ports.
+    fn sample(&mut self, state: &Mutex<UsageState>) {
+        self.system
+            .refresh_processes_specifics(ProcessesToUpdate::All, true, Self::refresh_kind());
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+
+        let tabs = state.lock().unwrap().tabs.clone();
+        let children = children_map(&self.system);
+        let sockets = SocketTable::read();
+        let mut samples = Vec::new();
+        let mut ports = HashMap::new();
+        for &(tab_id, pid, _) in &tabs {
+            let tree = walk_tree(&self.system, &children, Pid::from_u32(pid));
+            let mut sample = UsageSample::default();
+            for (pid, _) in &tree {
+                if let Some(p) = self.system.process(*pid) {
+                    sample.memory_kb += p.memory() / 1024;
+                    sample.cpu_percent += p.cpu_usage();
+                }
+            }
+            samples.push((tab_id, sample));
+            let tab_ports = sockets.ports_for(tree.into_iter().map(|(p, _)| p.as_u32()));
+            if !tab_ports.is_empty() {
+                ports.insert(tab_id, tab_ports);
+            }
+        }
+        let total_kb = self.system.total_memory() / 1024;
+        let available_kb = self.system.available_memory() / 1024;
+        let system = UsageSample {
+            memory_kb: total_kb.saturating_sub(available_kb),
+            cpu_percent: self.system.global_cpu_usage(),
+        };
+        state.lock().unwrap().record(system, &samples, ports);
+    }
+}
+
+/// Processes of a limited tab that were killed for exceeding a limit.
+#[derive(Debug, Default)]
+struct KillRecord {
+    /// The latest kill, shown in the sidebar.
+    last: Option<LimitKill>,
+    /// A kill that took the tab's root process and that its exit has not
+    /// reported yet.
+    root: Option<LimitKill>,
+}
+
+impl KillRecord {
+    fn record(&mut self, kill: LimitKill, took_root: bool) {
+        self.last = Some(kill);
+        if took_root {
+            self.root = Some(kill);
+        }
+    }
+
+    /// Whether the root process that just exited was killed for a limit.
+    /// Consumes the record, so a later exit is not mistaken for a kill.
+    fn take_exit(&mut self) -> Option<LimitKill> {
+        self.root.take()
+    }
+}
+
+/// Enforcement state of an agent tab with resource limits.
+struct LimitedTab {
+    root_pid: u32,
+    limits: ResourceLimits,
+    memory: MemoryEnforcement,
+    /// Processes the limits were already applied to.
+    applied: HashSet<Pid>,
+    usage: LimitUsage,
+    kills: KillRecord,
+}
+
+/// One process of a tab's process tree, as listed in the Processes window.
+#[derive(Debug, Clone)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    /// Nesting level below the tab's root process (0 for the root itself).
+    pub depth: usize,
+    pub name: String,
+    /// Full command line, or the name if it is not available.
+    pub cmd: String,
+    /// CPU usage since the previous refresh; 100% is one fully busy core.
+    pub cpu_percent: f32,
+    pub memory_kb: u64,
+    pub run_time_secs: u64,
+}
+
+/// Signals that can be sent to a process from the Processes window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSignal {
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+impl ProcessSignal {
+    pub const ALL: [ProcessSignal; 3] = [
+        ProcessSignal::Interrupt,
+        ProcessSignal::Terminate,
+        ProcessSignal::Kill,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProcessSignal::Interrupt => "SIGINT",
+            ProcessSignal::Terminate => "SIGTERM",
+            ProcessSignal::Kill => "SIGKILL",
+        }
+    }
+
+    fn to_sysinfo(self) -> Signal {
+        match self {
+            ProcessSignal::Interrupt => Signal::Interrupt,
+            ProcessSignal::Terminate => Signal::Term,
+            ProcessSignal::Kill => Signal::Kill,
+        }
+    }
+}
+
 pub struct SystemMonitor {
     system: System,
     last_memory_refresh: Instant,
@@ -15,6 +286,17 @@ pub struct SystemMonitor {
     current: MemoryInfo,
     /// Parent -> children map, rebuilt lazily after each process refresh.
     children_cache: Option<HashMap<Pid, Vec<Pid>>>,
+    /// Incremented on every process refresh, so per-refresh work runs once
+    /// per refresh rather than once per frame.
+    refresh_generation: u64,
+    /// Histories, alerts and ports, recorded by the sampler thread.
+    usage: Arc<Mutex<UsageState>>,
+    shutdown: Arc<AtomicBool>,
+    _sampler: Option<thread::JoinHandle<()>>,
+    /// Alert thresholds of tabs without their own.
+    limits: UsageLimits,
+    limited_tabs: HashMap<u64, LimitedTab>,
+    enforced_generation: u64,
 }
 
 impl Default for SystemMonitor {
@@ -28,18 +310,240 @@ impl SystemMonitor {
         let system = System::new_with_specifics(
             RefreshKind::nothing()
                 .with_memory(MemoryRefreshKind::everything())
-                .with_processes(ProcessRefreshKind::everything()),
+                .with_cpu(CpuRefreshKind::nothing().with_cpu_usage())
+                .with_processes(Self::process_refresh_kind()),
         );
         let current = Self::read_memory(&system);
+
+        let usage = Arc::new(Mutex::new(UsageState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let usage_clone = Arc::clone(&usage);
+        let shutdown_clone = Arc::clone(&shutdown);
+        let handle = thread::Builder::new()
+            .name("usage-sampler".into())
+            .spawn(move || {
+                let mut sampler = UsageSampler::new();
+                // Wake up at least this often so shutdown is handled promptly.
+                let step = Duration::from_millis(200);
+                let mut next = Instant::now() + PROCESS_REFRESH_INTERVAL;
+                while !shutdown_clone.load(Ordering::Relaxed) {
+                    let now = Instant::now();
+                    if now >= next {
+                        sampler.sample(&usage_clone);
+                        next = (next + PROCESS_REFRESH_INTERVAL).max(now);
+                    }
+                    thread::sleep(step.min(next.saturating_duration_since(now)));
+                }
+            })
+            .ok();
+
         Self {
             system,
             last_memory_refresh: Instant::now(),
             last_process_refresh: Instant::now(),
             current,
             children_cache: None,
+            refresh_generation: 1,
+            usage,
+            shutdown,
+            _sampler: handle,
+            limits: UsageLimits::default(),
+            limited_tabs: HashMap::new(),
+            enforced_generation: 0,
+        }
+    }
+
+    /// Change the alert thresholds of tabs without their own. Alerts are
+    /// re-evaluated on the next sample.
+    pub fn set_limits(&mut self, limits: UsageLimits) {
+        self.limits = limits;
+    }
+
+    /// Alert thresholds tab `tab_id` is checked against.
+    pub fn tab_limits(&self, tab_id: u64) -> UsageLimits {
+        self.usage
+            .lock()
+            .unwrap()
+            .tabs
+            .iter()
+            .find(|(id, _, _)| *id == tab_id)
+            .map_or(self.limits, |&(_, _, limits)| limits)
+    }
+
+    /// Total physical memory in KB, the scale of the system memory history.
+    pub fn total_memory_kb(&self) -> u64 {
+        self.system.total_memory() / 1024
+    }
+
+    /// Used memory and global CPU usage over the last couple of minutes.
+    pub fn system_history(&self) -> UsageHistory {
+        self.usage.lock().unwrap().system_history.clone()
+    }
+
+    pub fn tab_history(&self, tab_id: u64) -> Option<UsageHistory> {
+        self.usage
+            .lock()
+            .unwrap()
+            .tab_histories
+            .get(&tab_id)
+            .cloned()
+    }
+
+    /// TCP ports that processes of tab `tab_id` listen on, sorted by port.
+    pub fn tab_ports(&self, tab_id: u64) -> Vec<ListeningPort> {
+        self.usage
+            .lock()
+            .unwrap()
+            .tab_ports
+            .get(&tab_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Name of process `pid`, if it still exists.
+    pub fn process_name(&self, pid: u32) -> Option<String> {
+        self.system
+            .process(Pid::from_u32(pid))
+            .map(|p| p.name().to_string_lossy().into_owned())
+    }
+
+    /// The limit tab `tab_id` is currently in sustained breach of, if any.
+    pub fn tab_alert(&self, tab_id: u64) -> Option<UsageAlert> {
+        self.usage.lock().unwrap().tab_alerts.get(&tab_id).copied()
+    }
+
+    /// Call once per frame with the (tab id, root pid, own alert thresholds)
+    /// of every tab; tabs without thresholds use [`Self::set_limits`]. The
+    /// sampler thread records their usage and listening ports, also while no
+    /// frames are drawn. Returns the alerts raised since the previous call.
+    pub fn track_tabs(
+        &mut self,
+        tabs: &[(u64, u32, Option<UsageLimits>)],
+    ) -> Vec<(u64, UsageAlert)> {
+        let mut usage = self.usage.lock().unwrap();
+        usage.tabs = tabs
+            .iter()
+            .map(|&(tab_id, pid, limits)| (tab_id, pid, limits.unwrap_or(self.limits)))
+            .collect();
+        std::mem::take(&mut usage.raised)
+    }
+
+    /// Call once per frame with the (tab id, root pid, limits, memory
+    /// enforcement) of every tab with resource limits, as the tab was started
+    /// with. After each process refresh this applies the rlimits to processes
+    /// that appeared since, measures usage against the limits and kills
+    /// processes over their CPU time limit and, for memory limits without a
+    /// scope, trees over the limit.
+    pub fn enforce_limits(&mut self, tabs: &[(u64, u32, ResourceLimits, MemoryEnforcement)]) {
+        self.refresh_processes_if_needed();
+        if self.enforced_generation == self.refresh_generation {
+            return;
+        }
+        self.enforced_generation = self.refresh_generation;
+
+        self.limited_tabs.retain(|id, tab| {
+            let keep = tabs
+                .iter()
+                .any(|(tab_id, pid, _, _)| tab_id == id && *pid == tab.root_pid);
+            if !keep {
+                resource_limits::release(&tab.memory);
+            }
+            keep
+        });
+
+        for (tab_id, root_pid, limits, memory) in tabs {
+            let (tab_id, root_pid, limits) = (*tab_id, *root_pid, *limits);
+            self.limited_tabs
+                .entry(tab_id)
+                .or_insert_with(|| LimitedTab {
+                    root_pid,
+                    limits,
+                    memory: memory.clone(),
+                    applied: HashSet::new(),
+                    usage: LimitUsage::default(),
+                    kills: KillRecord::default(),
+                });
+
+            let pids: Vec<Pid> = self
+                .walk_tree(root_pid)
+                .into_iter()
+                .map(|(pid, _)| pid)
+                .collect();
+            let mut usage = LimitUsage::default();
+            let mut over_cpu_time = Vec::new();
+            for &pid in &pids {
+                if let Some(p) = self.system.process(pid) {
+                    usage.memory_kb += p.memory() / 1024;
+                }
+                let raw = pid.as_u32();
+                let cpu_time_secs = resource_limits::cpu_time_secs(raw).unwrap_or(0);
+                if limits.cpu_time_secs > 0 && cpu_time_secs >= limits.cpu_time_secs {
+                    over_cpu_time.push(pid);
+                }
+                usage.cpu_time_secs = usage.cpu_time_secs.max(cpu_time_secs);
+                usage.open_files = usage
+                    .open_files
+                    .max(resource_limits::open_files(raw).unwrap_or(0));
+            }
+
+            let tab = self.limited_tabs.get_mut(&tab_id).unwrap();
+            for &pid in &pids {
+                if tab.applied.insert(pid) {
+                    resource_limits::apply(pid.as_u32(), &tab.limits);
+                }
+            }
+            tab.applied.retain(|pid| pids.contains(pid));
+            tab.usage = usage;
+
+            if tab.memory == MemoryEnforcement::Watchdog
+                && tab.kills.root.is_none()
+                && usage.memory_kb > limits.memory_kb
+            {
+                tab.kills.record(LimitKill::Memory, true);
+                for &pid in &pids {
+                    if let Some(p) = self.system.process(pid) {
+                        p.kill_with(Signal::Kill);
+                    }
+                }
+            }
+
+            // Killed here rather than left to RLIMIT_CPU, so a kill is known
+            // instead of guessed from how the process exited.
+            if !over_cpu_time.is_empty() {
+                let took_root = over_cpu_time.contains(&Pid::from_u32(root_pid));
+                tab.kills.record(LimitKill::CpuTime, took_root);
+                for pid in over_cpu_time {
+                    if let Some(p) = self.system.process(pid) {
+                        p.kill_with(Signal::Kill);
+                    }
+                }
+            }
         }
     }
 
+    /// Limits of tab `tab_id` and its usage at the last refresh, if the tab
+    /// has limits.
+    pub fn tab_limit_usage(&self, tab_id: u64) -> Option<(ResourceLimits, LimitUsage)> {
+        self.limited_tabs
+            .get(&tab_id)
+            .map(|tab| (tab.limits, tab.usage))
+    }
+
+    /// The limit a process of tab `tab_id` was last killed for, if any.
+    pub fn tab_limit_kill(&self, tab_id: u64) -> Option<LimitKill> {
+        self.limited_tabs.get(&tab_id)?.kills.last
+    }
+
+    /// Called when the root process of tab `tab_id` has exited: whether it
+    /// was killed for exceeding a limit. Each kill is reported once.
+    pub fn exit_limit_kill(&mut self, tab_id: u64) -> Option<LimitKill> {
+        let tab = self.limited_tabs.get_mut(&tab_id)?;
+        if resource_limits::oom_killed(&tab.memory) {
+            tab.kills.record(LimitKill::Memory, true);
+        }
+        tab.kills.take_exit()
+    }
+
     /// Global system memory. Refreshed at most once per second.
     pub fn memory(&mut self) -> &MemoryInfo {
         let now = Instant::now();
@@ -67,53 +571,100 @@ impl SystemMonitor {
     /// a shell or agent process usually spawns child processes (compilers,
     /// servers, AI models, etc.).
     pub fn process_tree_memory_kb(&mut self, root_pid: u32) -> u64 {
-        self.refresh_processes_if_needed();
-        let root = Pid::from_u32(root_pid);
-
-        // Build a parent -> children map from the full process list. sysinfo
-        // exposes `tasks()` only on Linux, so we walk parent links ourselves to
-        // make this work on macOS and Windows too. The map is cached until the
-        // next process refresh, so multiple tab lookups per frame are cheap.
-        if self.children_cache.is_none() {
-            let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
-            for (pid, process) in self.system.processes() {
-                // On Linux sysinfo enumerates individual threads as separate
-                // processes under /proc/[PID]/task. They share the same address
-                // space as the main process, so counting them would multiply
-                // the reported RSS by the number of threads.
-                if process.thread_kind().is_some() {
-                    continue;
-                }
-                if let Some(parent) = process.parent() {
-                    children.entry(parent).or_default().push(*pid);
-                }
-            }
-            self.children_cache = Some(children);
-        }
-        let children = self.children_cache.as_ref().unwrap();
+        let total_bytes: u64 = self
+            .walk_tree(root_pid)
+            .into_iter()
+            .filter_map(|(pid, _)| self.system.process(pid))
+            // Same thread-guard as in `walk_tree`: never count thread entries.
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| p.memory())
+            .sum();
+        total_bytes / 1024
+    }
 
-        let mut total_bytes = 0u64;
-        let mut to_visit = vec![root];
-        let mut visited = HashSet::new();
+    /// CPU usage of a process and all its descendants, from the difference
+    /// between the last two process refreshes. 100% is one fully busy core,
+    /// so a parallel build can report several hundred percent.
+    pub fn process_tree_cpu_percent(&mut self, root_pid: u32) -> f32 {
+        self.walk_tree(root_pid)
+            .into_iter()
+            .filter_map(|(pid, _)| self.system.process(pid))
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| p.cpu_usage())
+            .sum()
+    }
 
-        while let Some(pid) = to_visit.pop() {
-            if !visited.insert(pid) {
-                continue;
-            }
-            if let Some(p) = self.system.process(pid) {
-                // Same thread-guard as above: never count thread entries.
-                if p.thread_kind().is_none() {
-                    total_bytes += p.memory();
-                }
-            }
-            if let Some(kids) = children.get(&pid) {
-                for child in kids {
-                    to_visit.push(*child);
-                }
-            }
+    /// The process tree below `root_pid` in depth-first order, children sorted
+    /// by PID. Empty if the root process no longer exists.
+    pub fn process_tree(&mut self, root_pid: u32) -> Vec<ProcessEntry> {
+        self.walk_tree(root_pid)
+            .into_iter()
+            .filter_map(|(pid, depth)| {
+                let p = self.system.process(pid)?;
+                let name = p.name().to_string_lossy().into_owned();
+                let cmd = p
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Some(ProcessEntry {
+                    pid: pid.as_u32(),
+                    depth,
+                    cmd: if cmd.is_empty() { name.clone() } else { cmd },
+                    name,
+                    cpu_percent: p.cpu_usage(),
+                    memory_kb: p.memory() / 1024,
+                    run_time_secs: p.run_time(),
+                })
+            })
+            .collect()
+    }
+
+    /// Processes that closing a tab would kill besides its idle shell: the
+    /// descendants of `root_pid`, plus the root itself if `include_root` (an
+    /// agent tab runs the agent directly). Programs named in `ignored` are
+    /// skipped together with their descendants. Process data is refreshed
+    /// first so a command started a moment ago is not missed.
+    pub fn busy_processes(
+        &mut self,
+        root_pid: u32,
+        include_root: bool,
+        ignored: &[String],
+    ) -> Vec<ProcessEntry> {
+        self.last_process_refresh = Instant::now()
+            .checked_sub(PROCESS_REFRESH_INTERVAL)
+            .unwrap_or(self.last_process_refresh);
+        filter_busy(self.process_tree(root_pid), include_root, ignored)
+    }
+
+    /// Send `signal` to process `pid`. Fails if the process is gone, the
+    /// platform does not support the signal (only SIGKILL exists on Windows)
+    /// or the OS refused it.
+    pub fn send_signal(&self, pid: u32, signal: ProcessSignal) -> Result<(), String> {
+        let process = self
+            .system
+            .process(Pid::from_u32(pid))
+            .ok_or_else(|| format!("Process {} no longer exists", pid))?;
+        match process.kill_with(signal.to_sysinfo()) {
+            Some(true) => Ok(()),
+            Some(false) => Err(format!("Failed to send {} to {}", signal.label(), pid)),
+            None => Err(format!(
+                "{} is not supported on this platform",
+                signal.label()
+            )),
         }
+    }
 
-        total_bytes / 1024
+    /// PIDs of `root_pid` and its descendants with their depth, depth-first.
+    fn walk_tree(&mut self, root_pid: u32) -> Vec<(Pid, usize)> {
+        self.refresh_processes_if_needed();
+        // The map is cached until the next process refresh, so multiple tab
+        // lookups per frame are cheap.
+        let children = self
+            .children_cache
+            .get_or_insert_with(|| children_map(&self.system));
+        walk_tree(&self.system, children, Pid::from_u32(root_pid))
     }
 
     fn read_memory(system: &System) -> MemoryInfo {
@@ -128,18 +679,126 @@ impl SystemMonitor {
         MemoryInfo { percent }
     }
 
+    /// Memory and CPU for the tab labels, plus command lines (read once per
+    /// process) for the Processes window.
+    fn process_refresh_kind() -> ProcessRefreshKind {
+        ProcessRefreshKind::nothing()
+            .with_memory()
+            .with_cpu()
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+    }
+
     fn refresh_processes_if_needed(&mut self) {
         let now = Instant::now();
-        if now.duration_since(self.last_process_refresh) >= Duration::from_secs(2) {
+        if now.duration_since(self.last_process_refresh) >= PROCESS_REFRESH_INTERVAL {
             self.system.refresh_processes_specifics(
                 ProcessesToUpdate::All,
                 true,
-                ProcessRefreshKind::nothing().with_memory(),
+                Self::process_refresh_kind(),
             );
+            self.system.refresh_cpu_usage();
             self.children_cache = None;
             self.last_process_refresh = now;
+            self.refresh_generation += 1;
+        }
+    }
+}
+
+impl Drop for SystemMonitor {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self._sampler.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Parent -> children map of all processes in `system`, children sorted by
+/// PID.
+///
+/// sysinfo exposes `tasks()` only on Linux, so parent links are walked instead
+/// to make this work on macOS and Windows too.
+fn children_map(system: &System) -> HashMap<Pid, Vec<Pid>> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        // On Linux sysinfo enumerates individual threads as separate
+        // processes under /proc/[PID]/task. They share the same address
+        // space as the main process, so counting them would multiply
+        // the reported RSS by the number of threads.
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
         }
     }
+    for kids in children.values_mut() {
+        kids.sort();
+    }
+    children
+}
+
+/// `root` and its descendants in `children` with their depth, depth-first.
+/// Empty if the root process no longer exists.
+fn walk_tree(system: &System, children: &HashMap<Pid, Vec<Pid>>, root: Pid) -> Vec<(Pid, usize)> {
+    if system.process(root).is_none() {
+        return Vec::new();
+    }
+
+    let mut tree = Vec::new();
+    let mut to_visit = vec![(root, 0)];
+    let mut visited = HashSet::new();
+
+    while let Some((pid, depth)) = to_visit.pop() {
+        if !visited.insert(pid) {
+            continue;
+        }
+        tree.push((pid, depth));
+        if let Some(kids) = children.get(&pid) {
+            // Reversed so the lowest PID is visited first.
+            for child in kids.iter().rev() {
+                to_visit.push((*child, depth + 1));
+            }
+        }
+    }
+
+    tree
+}
+
+/// See [`SystemMonitor::busy_processes`]. `tree` is in depth-first order. An
+/// ignored name ending in `*` matches any program starting with the rest.
+fn filter_busy(
+    tree: Vec<ProcessEntry>,
+    include_root: bool,
+    ignored: &[String],
+) -> Vec<ProcessEntry> {
+    let program = |p: &ProcessEntry| {
+        let first = p.cmd.split_whitespace().next().unwrap_or_default();
+        first.rsplit('/').next().unwrap_or(first).to_string()
+    };
+    let matches = |pattern: &str, name: &str| match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    };
+    let mut skip_below: Option<usize> = None;
+    tree.into_iter()
+        .filter(|p| {
+            if let Some(depth) = skip_below {
+                if p.depth > depth {
+                    return false;
+                }
+                skip_below = None;
+            }
+            if ignored
+                .iter()
+                .any(|i| matches(i, &p.name) || matches(i, &program(p)))
+            {
+                skip_below = Some(p.depth);
+                return false;
+            }
+            include_root || p.depth > 0
+        })
+        .collect()
 }
 
 pub fn format_kb(kb: u64) -> String {
@@ -151,3 +810,266 @@ pub fn format_kb(kb: u64) -> String {
         format!("{} KB", kb)
     }
 }
+
+/// Format a duration in seconds compactly, e.g. "42s", "5m 03s", "2h 10m".
+pub fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 60 * 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}d {:02}h", secs / 86400, (secs % 86400) / 3600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(samples: &[(u64, f32)]) -> UsageHistory {
+        let mut history = UsageHistory::default();
+        for &(memory_kb, cpu_percent) in samples {
+            history.push(UsageSample {
+                memory_kb,
+                cpu_percent,
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn test_filter_busy() {
+        let entry = |depth, name: &str, cmd: &str| ProcessEntry {
+            pid: 0,
+            depth,
+            name: name.to_string(),
+            cmd: cmd.to_string(),
+            cpu_percent: 0.0,
+            memory_kb: 0,
+            run_time_secs: 0,
+        };
+        let tree = || {
+            vec![
+                entry(0, "zsh", "/bin/zsh"),
+                entry(
+                    1,
+                    "gitstatusd-linu",
+                    "/home/me/.cache/gitstatus/gitstatusd-linux-x86_64",
+                ),
+                entry(2, "helper", "helper"),
+                entry(1, "npm", "npm run dev"),
+                entry(2, "node", "/usr/bin/node vite"),
+            ]
+        };
+        let names = |v: Vec<ProcessEntry>| v.into_iter().map(|p| p.name).collect::<Vec<_>>();
+
+        assert_eq!(
+            names(filter_busy(tree(), false, &[])),
+            ["gitstatusd-linu", "helper", "npm", "node"]
+        );
+        // An ignored program hides its descendants; matched by name or by
+        // the executable in its command line.
+        let ignored = ["gitstatusd*".to_string(), "node".to_string()];
+        assert_eq!(names(filter_busy(tree(), false, &ignored)), ["npm"]);
+        assert_eq!(
+            names(filter_busy(vec![entry(0, "claude", "claude")], true, &[])),
+            ["claude"]
+        );
+        assert!(filter_busy(vec![entry(0, "zsh", "zsh")], false, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut h = UsageHistory::default();
+        for i in 0..(HISTORY_LEN as u64 + 10) {
+            h.push(UsageSample {
+                memory_kb: i,
+                cpu_percent: 0.0,
+            });
+        }
+        assert_eq!(h.samples().count(), HISTORY_LEN);
+        assert_eq!(h.samples().next().unwrap().memory_kb, 10);
+    }
+
+    #[test]
+    fn test_sustained_breach() {
+        let limits = UsageLimits {
+            memory_kb: 1000,
+            cpu_percent: 100.0,
+            sustained: Duration::from_secs(6),
+        };
+
+        // Three samples (6 s) are needed; a dip below the limit resets it.
+        assert_eq!(
+            history(&[(2000, 0.0), (2000, 0.0)]).sustained_breach(&limits),
+            None
+        );
+        assert_eq!(
+            history(&[(2000, 0.0), (500, 0.0), (2000, 0.0), (2000, 0.0)]).sustained_breach(&limits),
+            None
+        );
+        assert_eq!(
+            history(&[(500, 0.0), (2000, 0.0), (2000, 0.0), (2000, 0.0)]).sustained_breach(&limits),
+            Some(UsageAlert::Memory)
+        );
+        assert_eq!(
+            history(&[(0, 150.0), (0, 180.0), (0, 120.0)]).sustained_breach(&limits),
+            Some(UsageAlert::Cpu)
+        );
+
+        let disabled = UsageLimits {
+            memory_kb: 0,
+            cpu_percent: 0.0,
+            ..limits
+        };
+        assert_eq!(
+            history(&[(2000, 150.0), (2000, 150.0), (2000, 150.0)]).sustained_breach(&disabled),
+            None
+        );
+    }
+
+    #[test]
+    fn test_usage_state_records_alerts_per_tab() {
+        let strict = UsageLimits {
+            memory_kb: 1000,
+            cpu_percent: 0.0,
+            sustained: Duration::from_secs(4),
+        };
+        let mut state = UsageState {
+            tabs: vec![(1, 10, strict), (2, 20, UsageLimits::default())],
+            ..Default::default()
+        };
+        let sample = |memory_kb| UsageSample {
+            memory_kb,
+            cpu_percent: 0.0,
+        };
+        let samples = [(1, sample(2000)), (2, sample(2000)), (3, sample(2000))];
+
+        state.record(sample(0), &samples, HashMap::new());
+        assert!(state.raised.is_empty());
+        state.record(sample(0), &samples, HashMap::new());
+        // Only tab 1 has a memory limit; tab 3 is not tracked.
+        assert_eq!(state.raised, [(1, UsageAlert::Memory)]);
+        assert_eq!(state.tab_histories.len(), 2);
+        assert_eq!(state.system_history.samples().count(), 2);
+        // Raised once while the breach lasts.
+        state.record(sample(0), &samples, HashMap::new());
+        assert_eq!(state.raised.len(), 1);
+
+        state.tabs.remove(0);
+        state.record(sample(0), &samples, HashMap::new());
+        assert!(state.tab_alerts.is_empty());
+        assert!(!state.tab_histories.contains_key(&1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sampler_records_process_tree() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("sleep should start");
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let state = Mutex::new(UsageState {
+            tabs: vec![(1, std::process::id(), UsageLimits::default())],
+            ..Default::default()
+        });
+        let mut sampler = UsageSampler::new();
+        sampler.sample(&state);
+        child.kill().expect("sleep should be killed");
+        let _ = child.wait();
+
+        let state = state.lock().unwrap();
+        let history = &state.tab_histories[&1];
+        assert_eq!(history.samples().count(), 1);
+        assert!(history.samples().next().unwrap().memory_kb > 0);
+        if cfg!(target_os = "linux") {
+            assert!(state.tab_ports[&1].iter().any(|p| p.port == port));
+        }
+    }
+
+    #[test]
+    fn test_kill_record() {
+        let mut kills = KillRecord::default();
+        assert_eq!(kills.take_exit(), None);
+
+        // A child over its CPU time does not make the shell's exit a kill.
+        kills.record(LimitKill::CpuTime, false);
+        assert_eq!(kills.last, Some(LimitKill::CpuTime));
+        assert_eq!(kills.take_exit(), None);
+
+        kills.record(LimitKill::Memory, true);
+        assert_eq!(kills.take_exit(), Some(LimitKill::Memory));
+        // Reported once; the sidebar still shows it.
+        assert_eq!(kills.take_exit(), None);
+        assert_eq!(kills.last, Some(LimitKill::Memory));
+    }
+
+    #[test]
+    fn test_exit_limit_kill_is_reported_once() {
+        let mut monitor = SystemMonitor::new();
+        monitor.limited_tabs.insert(
+            1,
+            LimitedTab {
+                root_pid: 0,
+                limits: ResourceLimits::default(),
+                memory: MemoryEnforcement::Watchdog,
+                applied: HashSet::new(),
+                usage: LimitUsage::default(),
+                kills: KillRecord::default(),
+            },
+        );
+        assert_eq!(monitor.exit_limit_kill(1), None);
+        assert_eq!(monitor.exit_limit_kill(2), None);
+
+        monitor
+            .limited_tabs
+            .get_mut(&1)
+            .unwrap()
+            .kills
+            .record(LimitKill::CpuTime, true);
+        assert_eq!(monitor.exit_limit_kill(1), Some(LimitKill::CpuTime));
+        assert_eq!(monitor.tab_limit_kill(1), Some(LimitKill::CpuTime));
+        // A restarted tab that exits normally is removed.
+        assert_eq!(monitor.exit_limit_kill(1), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(5 * 60 + 3), "5m 03s");
+        assert_eq!(format_duration(2 * 3600 + 10 * 60), "2h 10m");
+        assert_eq!(format_duration(3 * 86400 + 3600), "3d 01h");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_tree_lists_children_and_signals() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("sleep should start");
+
+        // Created after the spawn so the first refresh already sees the child.
+        let mut monitor = SystemMonitor::new();
+        let tree = monitor.process_tree(std::process::id());
+        assert_eq!(tree[0].pid, std::process::id());
+        assert_eq!(tree[0].depth, 0);
+        let entry = tree
+            .iter()
+            .find(|e| e.pid == child.id())
+            .expect("child should be listed");
+        assert_eq!(entry.depth, 1);
+        assert_eq!(entry.cmd, "sleep 30");
+        assert!(monitor.process_tree_cpu_percent(std::process::id()) >= 0.0);
+
+        monitor
+            .send_signal(child.id(), ProcessSignal::Terminate)
+            .expect("signal should be delivered");
+        let status = child.wait().expect("child should exit");
+        assert!(!status.success());
+    }
+}
@@ -1,12 +1,15 @@
-use crate::config::settings::{AgentConfig, MAX_AGENTS};
-use crate::constants::GROUPS_FILE;
+use crate::config::settings::{AgentConfig, PreloadLimits, MAX_AGENTS};
+use crate::constants::MAIN_WINDOW;
+use crate::resource_limits::ResourceLimits;
+use crate::terminal::spawn::SpawnError;
 use crate::terminal::tab::Tab;
 use egui_term::PtyEvent;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
-    path::PathBuf,
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
     sync::mpsc::Sender,
+    time::Instant,
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -17,6 +20,9 @@ pub struct TabInfo {
     pub agent_index: Option<usize>,
     #[serde(default)]
     pub display_name: String,
+    /// Start this tab with the app even when restored tabs start lazily.
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -25,6 +31,21 @@ pub struct TabGroup {
     pub name: String,
     pub path: PathBuf,
     pub tabs: Vec<TabInfo>,
+    /// Periodically `git fetch` this project in the background.
+    #[serde(default)]
+    pub auto_fetch: bool,
+    /// Sidebar accent; `None` picks one from the path.
+    #[serde(default)]
+    pub accent: Option<egui::Color32>,
+    /// Emoji or short text shown before the group name.
+    #[serde(default)]
+    pub icon: String,
+    /// Sidebar shows only the group header, not its tabs.
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Top-level window the group is shown in.
+    #[serde(default)]
+    pub window: u64,
 }
 
 impl TabGroup {
@@ -34,9 +55,18 @@ impl TabGroup {
             name,
             path,
             tabs: Vec::new(),
+            auto_fetch: false,
+            accent: None,
+            icon: String::new(),
+            collapsed: false,
+            window: MAIN_WINDOW,
         }
     }
 
+    pub fn accent_color(&self) -> egui::Color32 {
+        self.accent.unwrap_or_else(|| auto_accent(&self.path))
+    }
+
     pub fn name_from_path(path: &PathBuf) -> String {
         path.file_name()
             .and_then(|n| n.to_str())
@@ -45,20 +75,88 @@ impl TabGroup {
     }
 }
 
+/// Stable accent for a project path: FNV-1a of the path picks the hue, so
+/// a project keeps its color across restarts and machines.
+pub fn auto_accent(path: &std::path::Path) -> egui::Color32 {
+    let hash = path
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    let hue = (hash % 360) as f32 / 360.0;
+    egui::ecolor::Hsva::new(hue, 0.55, 0.85, 1.0).into()
+}
+
+/// A workspace switched away from while its terminals keep running.
+struct ParkedWorkspace {
+    groups_file: Option<PathBuf>,
+    groups: BTreeMap<u64, TabGroup>,
+    tabs: BTreeMap<u64, Tab>,
+    active_group_id: Option<u64>,
+    active_tab_id: Option<u64>,
+}
+
+impl ParkedWorkspace {
+    /// Drop an exited tab and keep the workspace's groups file in sync.
+    fn remove_tab(&mut self, id: u64) {
+        if self.tabs.remove(&id).is_none() {
+            return;
+        }
+        for group in self.groups.values_mut() {
+            group.tabs.retain(|t| t.id != id);
+        }
+        if self.active_tab_id == Some(id) {
+            self.active_tab_id = None;
+        }
+        write_groups(self.groups_file.as_ref(), &self.groups);
+    }
+}
+
+fn write_groups(groups_file: Option<&PathBuf>, groups: &BTreeMap<u64, TabGroup>) {
+    if let Some(groups_file) = groups_file {
+        if let Ok(groups) = serde_json::to_string_pretty(&groups.values().collect::<Vec<_>>()) {
+            let _ = std::fs::write(groups_file, groups);
+        }
+    }
+}
+
 pub struct TabManager {
     command_sender: Sender<(u64, PtyEvent)>,
+    /// Name of the open workspace and the file its groups are saved to.
+    pub workspace: String,
+    groups_file: Option<PathBuf>,
+    /// Other workspaces whose terminals were kept running, by name.
+    parked: HashMap<String, ParkedWorkspace>,
     pub groups: BTreeMap<u64, TabGroup>,
     tabs: BTreeMap<u64, Tab>,
+    /// Tabs whose program could not be started; they show the error and a
+    /// retry button instead of a terminal.
+    failed: HashMap<u64, SpawnError>,
     /// Key: (group_id, agent_index) where agent_index is None for terminal.
     preload_pool: HashMap<(u64, Option<usize>), (u64, Tab)>,
+    preload_limits: PreloadLimits,
+    /// Preload keys that failed to start or whose tab exited unused; not
+    /// retried until the pool is cleared, e.g. by changed settings.
+    preload_failed: HashSet<(u64, Option<usize>)>,
+    /// When each group last had the active tab; decides which groups keep
+    /// preloaded tabs.
+    group_used: HashMap<u64, Instant>,
     pub active_group_id: Option<u64>,
     pub active_tab_id: Option<u64>,
+    /// Window the active ids above belong to; the selection of the other
+    /// windows is kept aside while they are not being drawn.
+    window: u64,
+    window_selection: HashMap<u64, (Option<u64>, Option<u64>)>,
     next_group_id: u64,
     next_tab_id: u64,
     pub default_shell_cmd: String,
     pub agents: [AgentConfig; MAX_AGENTS],
     pub run_as_login_shell: bool,
     preload_enabled: bool,
+    /// Restored tabs get their PTY only once they are first shown, except
+    /// pinned ones. Until then they exist only in `groups`.
+    lazy_spawn: bool,
     /// Last known terminal content size, used to seed new terminals at the
     /// correct column/row count so the PTY does not boot at the 80x50 default.
     terminal_layout_hint: Option<egui_term::Size>,
@@ -71,26 +169,40 @@ impl TabManager {
     pub fn new(
         command_sender: Sender<(u64, PtyEvent)>,
         cc: &eframe::CreationContext<'_>,
+        workspace: String,
+        groups_file: Option<PathBuf>,
         default_shell_cmd: String,
         agents: [AgentConfig; MAX_AGENTS],
         run_as_login_shell: bool,
         preload_enabled: bool,
+        preload_limits: PreloadLimits,
+        lazy_spawn: bool,
         terminal_layout_hint: Option<egui_term::Size>,
         cell_metrics_hint: Option<egui_term::Size>,
     ) -> Self {
         let mut manager = Self {
             command_sender,
+            workspace,
+            groups_file,
+            parked: HashMap::new(),
             groups: BTreeMap::new(),
             tabs: BTreeMap::new(),
+            failed: HashMap::new(),
             preload_pool: HashMap::new(),
+            preload_limits,
+            preload_failed: HashSet::new(),
+            group_used: HashMap::new(),
             active_group_id: None,
             active_tab_id: None,
+            window: MAIN_WINDOW,
+            window_selection: HashMap::new(),
             next_group_id: 0,
             next_tab_id: 0,
             default_shell_cmd,
             agents,
             run_as_login_shell,
             preload_enabled,
+            lazy_spawn,
             terminal_layout_hint,
             cell_metrics_hint,
         };
@@ -98,63 +210,7 @@ impl TabManager {
         let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
         if let Some(groups_data) = manager.load_groups() {
-            for mut group in groups_data {
-                manager.next_group_id = manager.next_group_id.max(group.id + 1);
-                for tab_info in &mut group.tabs {
-                    manager.next_tab_id = manager.next_tab_id.max(tab_info.id + 1);
-
-                    let (use_agent, agent_index) = if let Some(idx) = tab_info.agent_index {
-                        manager
-                            .agents
-                            .get(idx)
-                            .map(|a| (a.enabled && !a.cmd.trim().is_empty(), Some(idx)))
-                            .unwrap_or((false, None))
-                    } else if tab_info.is_agent {
-                        // Legacy session: agent tab with no index defaults to agent 0.
-                        let idx = 0usize;
-                        let enabled = manager
-                            .agents
-                            .get(idx)
-                            .map(|a| a.enabled && !a.cmd.trim().is_empty())
-                            .unwrap_or(false);
-                        (enabled, Some(idx))
-                    } else {
-                        (false, None)
-                    };
-
-                    tab_info.is_agent = use_agent;
-                    tab_info.agent_index = agent_index;
-
-                    let shell_cmd = if use_agent {
-                        agent_index
-                            .and_then(|idx| manager.agents.get(idx))
-                            .map(|a| a.cmd.clone())
-                            .unwrap_or_default()
-                    } else {
-                        manager.default_shell_cmd.clone()
-                    };
-
-                    let tab = Tab::new(
-                        cc.egui_ctx.clone(),
-                        manager.command_sender.clone(),
-                        tab_info.id,
-                        Some(group.path.clone()),
-                        &shell_cmd,
-                        use_agent,
-                        !use_agent && manager.run_as_login_shell,
-                        manager.terminal_layout_hint,
-                        manager.cell_metrics_hint,
-                    );
-                    manager.tabs.insert(tab_info.id, tab);
-                }
-                manager.groups.insert(group.id, group);
-            }
-            if let Some(first_group) = manager.groups.first_key_value() {
-                manager.active_group_id = Some(*first_group.0);
-                manager.active_tab_id = first_group.1.tabs.first().map(|t| t.id);
-            }
-
-            manager.refresh_all_display_names();
+            manager.open_groups(groups_data, &cc.egui_ctx);
         }
 
         let current_dir_exists_in_groups = manager.groups.values().any(|g| g.path == current_dir);
@@ -170,16 +226,13 @@ impl TabManager {
             manager.add_tab_to_group(group_id, cc.egui_ctx.clone(), None);
         }
 
-        manager.populate_preload_pool(cc.egui_ctx.clone());
-
         manager
     }
 
     fn load_groups(&mut self) -> Option<Vec<TabGroup>> {
-        if let Some(config_dir) = crate::config::config_dir() {
-            let groups_file = config_dir.join(GROUPS_FILE);
+        if let Some(groups_file) = &self.groups_file {
             if groups_file.exists() {
-                if let Ok(content) = std::fs::read_to_string(&groups_file) {
+                if let Ok(content) = std::fs::read_to_string(groups_file) {
                     if let Ok(groups) = serde_json::from_str::<Vec<TabGroup>>(&content) {
                         return Some(groups);
                     }
@@ -189,17 +242,224 @@ impl TabManager {
         None
     }
 
-    pub fn save_groups(&self) {
-        if let Some(config_dir) = crate::config::config_dir() {
-            let groups_file = config_dir.join(GROUPS_FILE);
-            if let Ok(groups) =
-                serde_json::to_string_pretty(&self.groups.values().collect::<Vec<_>>())
-            {
-                let _ = std::fs::write(&groups_file, groups);
+    /// Spawn the tabs of loaded groups and make the first group active. In
+    /// lazy mode only pinned tabs and the active one are started.
+    /// Groups and tabs keep their saved ids unless one is already taken, e.g.
+    /// by a workspace running in the background.
+    fn open_groups(&mut self, groups: Vec<TabGroup>, ctx: &egui::Context) {
+        let existing = || {
+            self.groups
+                .values()
+                .chain(self.parked.values().flat_map(|p| p.groups.values()))
+        };
+        let mut group_ids: HashSet<u64> = existing().map(|g| g.id).collect();
+        let mut tab_ids: HashSet<u64> = existing()
+            .flat_map(|g| g.tabs.iter().map(|t| t.id))
+            .chain(self.preload_pool.values().map(|(id, _)| *id))
+            .collect();
+
+        for mut group in groups {
+            group.id = keep_or_next_id(group.id, &mut group_ids, &mut self.next_group_id);
+            for tab_info in &mut group.tabs {
+                tab_info.id = keep_or_next_id(tab_info.id, &mut tab_ids, &mut self.next_tab_id);
+                // A tab of a closed workspace may have failed under this id.
+                self.failed.remove(&tab_info.id);
+
+                let (use_agent, agent_index) = if let Some(idx) = tab_info.agent_index {
+                    self.agents
+                        .get(idx)
+                        .map(|a| (a.enabled && !a.cmd.trim().is_empty(), Some(idx)))
+                        .unwrap_or((false, None))
+                } else if tab_info.is_agent {
+                    // Legacy session: agent tab with no index defaults to agent 0.
+                    let idx = 0usize;
+                    let enabled = self
+                        .agents
+                        .get(idx)
+                        .map(|a| a.enabled && !a.cmd.trim().is_empty())
+                        .unwrap_or(false);
+                    (enabled, Some(idx))
+                } else {
+                    (false, None)
+                };
+
+                tab_info.is_agent = use_agent;
+                tab_info.agent_index = agent_index;
+
+                if !self.lazy_spawn || tab_info.pinned {
+                    match self.spawn_saved_tab(ctx, tab_info, &group.path) {
+                        Ok(tab) => {
+                            self.tabs.insert(tab_info.id, tab);
+                        }
+                        Err(e) => {
+                            self.failed.insert(tab_info.id, e);
+                        }
+                    }
+                }
+            }
+            self.groups.insert(group.id, group);
+        }
+        if let Some(first_group) = self.groups.first_key_value() {
+            self.active_group_id = Some(*first_group.0);
+            self.active_tab_id = first_group.1.tabs.first().map(|t| t.id);
+        }
+        self.spawn_active(ctx);
+
+        self.refresh_all_display_names();
+    }
+
+    /// Resource limits of a new tab: its agent's, none for terminals.
+    fn agent_limits(&self, is_agent: bool, agent_index: Option<usize>) -> ResourceLimits {
+        agent_index
+            .filter(|_| is_agent)
+            .and_then(|idx| self.agents.get(idx))
+            .map(|a| a.limits.limits())
+            .unwrap_or_default()
+    }
+
+    fn spawn_saved_tab(
+        &self,
+        ctx: &egui::Context,
+        tab_info: &TabInfo,
+        path: &Path,
+    ) -> Result<Tab, SpawnError> {
+        let shell_cmd = if tab_info.is_agent {
+            tab_info
+                .agent_index
+                .and_then(|idx| self.agents.get(idx))
+                .map(|a| a.cmd.clone())
+                .unwrap_or_default()
+        } else {
+            self.default_shell_cmd.clone()
+        };
+
+        Tab::new(
+            ctx.clone(),
+            self.command_sender.clone(),
+            tab_info.id,
+            Some(path.to_path_buf()),
+            &shell_cmd,
+            tab_info.is_agent,
+            !tab_info.is_agent && self.run_as_login_shell,
+            self.terminal_layout_hint,
+            self.cell_metrics_hint,
+            self.agent_limits(tab_info.is_agent, tab_info.agent_index),
+        )
+    }
+
+    /// Start the active tab if it was restored without a PTY. Tabs that
+    /// failed to start wait for an explicit retry.
+    pub fn spawn_active(&mut self, ctx: &egui::Context) {
+        let Some(tab_id) = self.active_tab_id else {
+            return;
+        };
+        if !self.tabs.contains_key(&tab_id) && !self.failed.contains_key(&tab_id) {
+            self.start_tab(tab_id, ctx);
+        }
+    }
+
+    pub fn retry_tab(&mut self, tab_id: u64, ctx: &egui::Context) {
+        if self.failed.remove(&tab_id).is_some() {
+            self.start_tab(tab_id, ctx);
+        }
+    }
+
+    /// Why the tab could not be started, if it failed.
+    pub fn spawn_error(&self, tab_id: u64) -> Option<&SpawnError> {
+        self.failed.get(&tab_id)
+    }
+
+    fn start_tab(&mut self, tab_id: u64, ctx: &egui::Context) {
+        let Some(group) = self
+            .groups
+            .values()
+            .find(|g| g.tabs.iter().any(|t| t.id == tab_id))
+        else {
+            return;
+        };
+        let Some(tab_info) = group.tabs.iter().find(|t| t.id == tab_id) else {
+            return;
+        };
+        match self.spawn_saved_tab(ctx, tab_info, &group.path) {
+            Ok(tab) => {
+                self.tabs.insert(tab_id, tab);
             }
+            Err(e) => {
+                self.failed.insert(tab_id, e);
+            }
+        }
+    }
+
+    /// Whether the tab has a running PTY; restored tabs may not have one yet.
+    pub fn is_started(&self, tab_id: u64) -> bool {
+        self.tabs.contains_key(&tab_id)
+    }
+
+    /// Running terminals, including preloaded ones and those of workspaces
+    /// kept in the background.
+    pub fn pty_count(&self) -> usize {
+        self.tabs.len()
+            + self.preload_pool.len()
+            + self.parked.values().map(|p| p.tabs.len()).sum::<usize>()
+    }
+
+    pub fn save_groups(&self) {
+        write_groups(self.groups_file.as_ref(), &self.groups);
+    }
+
+    /// Make `workspace` the open one, saving the current workspace first.
+    /// With `keep_alive` the current terminals keep running in the
+    /// background, otherwise they are closed. A workspace that was left
+    /// running resumes as it was; any other is loaded from `groups_file`.
+    pub fn switch_workspace(
+        &mut self,
+        workspace: String,
+        groups_file: Option<PathBuf>,
+        keep_alive: bool,
+        ctx: egui::Context,
+    ) {
+        if workspace == self.workspace {
+            return;
+        }
+        self.save_groups();
+        self.clear_preload_pool();
+
+        let current = ParkedWorkspace {
+            groups_file: std::mem::replace(&mut self.groups_file, groups_file),
+            groups: std::mem::take(&mut self.groups),
+            tabs: std::mem::take(&mut self.tabs),
+            active_group_id: self.active_group_id.take(),
+            active_tab_id: self.active_tab_id.take(),
+        };
+        self.window_selection.clear();
+        self.group_used.clear();
+        let previous = std::mem::replace(&mut self.workspace, workspace);
+        if keep_alive {
+            self.parked.insert(previous, current);
+        }
+
+        if let Some(parked) = self.parked.remove(&self.workspace) {
+            self.groups = parked.groups;
+            self.tabs = parked.tabs;
+            self.active_group_id = parked.active_group_id;
+            self.active_tab_id = parked.active_tab_id.or_else(|| {
+                parked
+                    .active_group_id
+                    .and_then(|id| self.groups.get(&id))
+                    .and_then(|g| g.tabs.first())
+                    .map(|t| t.id)
+            });
+            self.refresh_all_display_names();
+        } else if let Some(groups) = self.load_groups() {
+            self.open_groups(groups, &ctx);
         }
     }
 
+    /// Close the terminals of a workspace running in the background.
+    pub fn close_parked_workspace(&mut self, workspace: &str) {
+        self.parked.remove(workspace);
+    }
+
     pub fn add_group_with_path(&mut self, ctx: egui::Context, path: Option<PathBuf>) {
         let group_id = self.next_group_id;
         self.next_group_id += 1;
@@ -208,12 +468,12 @@ impl TabManager {
             path.unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
         let name = TabGroup::name_from_path(&path);
 
-        let group = TabGroup::new(group_id, name, path);
+        let mut group = TabGroup::new(group_id, name, path);
+        group.window = self.window;
         self.groups.insert(group_id, group);
         self.active_group_id = Some(group_id);
 
-        self.add_tab_to_group(group_id, ctx.clone(), None);
-        self.populate_preload_for_group(group_id, ctx);
+        self.add_tab_to_group(group_id, ctx, None);
     }
 
     pub fn rename_group(&mut self, group_id: u64, new_name: String) {
@@ -222,6 +482,129 @@ impl TabManager {
         }
     }
 
+    pub fn set_group_auto_fetch(&mut self, group_id: u64, auto_fetch: bool) {
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.auto_fetch = auto_fetch;
+        }
+    }
+
+    pub fn set_group_accent(&mut self, group_id: u64, accent: Option<egui::Color32>) {
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.accent = accent;
+        }
+    }
+
+    pub fn set_group_icon(&mut self, group_id: u64, icon: String) {
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.icon = icon;
+        }
+    }
+
+    pub fn set_tab_pinned(&mut self, tab_id: u64, pinned: bool) {
+        if let Some(tab_info) = self
+            .groups
+            .values_mut()
+            .flat_map(|g| g.tabs.iter_mut())
+            .find(|t| t.id == tab_id)
+        {
+            tab_info.pinned = pinned;
+        }
+    }
+
+    pub fn set_group_collapsed(&mut self, group_id: u64, collapsed: bool) {
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.collapsed = collapsed;
+        }
+    }
+
+    /// Make the active ids refer to the selection of `window`, so the
+    /// sidebar, hotkeys and terminal of that window work on its own tabs.
+    pub fn focus_window(&mut self, window: u64) {
+        if window != self.window {
+            self.window_selection
+                .insert(self.window, (self.active_group_id, self.active_tab_id));
+            (self.active_group_id, self.active_tab_id) =
+                self.window_selection.remove(&window).unwrap_or_default();
+            self.window = window;
+        }
+
+        // The selection may point at a group that was closed or moved away.
+        let group = self
+            .active_group_id
+            .and_then(|id| self.groups.get(&id))
+            .filter(|g| g.window == window)
+            .or_else(|| self.groups.values().find(|g| g.window == window));
+        self.active_group_id = group.map(|g| g.id);
+        if !group.is_some_and(|g| g.tabs.iter().any(|t| Some(t.id) == self.active_tab_id)) {
+            self.active_tab_id = group.and_then(|g| g.tabs.first()).map(|t| t.id);
+        }
+    }
+
+    pub fn move_group_to_window(&mut self, group_id: u64, window: u64) {
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.window = window;
+        }
+    }
+
+    /// Move every group of `from` to `to`, e.g. when a window is closed.
+    pub fn move_window_groups(&mut self, from: u64, to: u64) {
+        for group in self.groups.values_mut().filter(|g| g.window == from) {
+            group.window = to;
+        }
+    }
+
+    /// Move a tab into `window`, joining the group of the same project
+    /// there or creating one like its current group.
+    pub fn move_tab_to_window(&mut self, tab_id: u64, window: u64) {
+        let Some(source) = self
+            .groups
+            .values()
+            .find(|g| g.tabs.iter().any(|t| t.id == tab_id))
+        else {
+            return;
+        };
+        if source.window == window {
+            return;
+        }
+        let source_id = source.id;
+        let target_id = match self
+            .groups
+            .values()
+            .find(|g| g.window == window && g.path == source.path)
+        {
+            Some(target) => target.id,
+            None => {
+                let mut group = source.clone();
+                group.id = self.next_group_id;
+                self.next_group_id += 1;
+                group.tabs.clear();
+                group.collapsed = false;
+                group.window = window;
+                let id = group.id;
+                self.groups.insert(id, group);
+                id
+            }
+        };
+
+        let Some(source) = self.groups.get_mut(&source_id) else {
+            return;
+        };
+        let Some(index) = source.tabs.iter().position(|t| t.id == tab_id) else {
+            return;
+        };
+        let tab_info = source.tabs.remove(index);
+        let source_empty = source.tabs.is_empty();
+        if let Some(target) = self.groups.get_mut(&target_id) {
+            target.tabs.push(tab_info);
+        }
+        if source_empty {
+            self.remove_group(source_id);
+        } else {
+            self.refresh_display_names(source_id);
+        }
+        self.refresh_display_names(target_id);
+    }
+
     /// Add a tab to a group.
     /// `agent_index` is `None` for a terminal tab, or `Some(i)` to open agent `i`.
     pub fn add_tab_to_group(
@@ -230,6 +613,7 @@ impl TabManager {
         ctx: egui::Context,
         agent_index: Option<usize>,
     ) {
+        self.touch_group(group_id);
         let preload_key = (group_id, agent_index);
 
         if self.preload_enabled {
@@ -247,14 +631,13 @@ impl TabManager {
                         is_agent: use_agent,
                         agent_index: if use_agent { agent_index } else { None },
                         display_name: String::new(),
+                        pinned: false,
                     });
                 }
 
                 self.refresh_display_names(group_id);
                 self.active_group_id = Some(group_id);
                 self.active_tab_id = Some(tab_id);
-
-                self.spawn_preload_tab(group_id, agent_index, ctx);
                 return;
             }
         }
@@ -284,8 +667,16 @@ impl TabManager {
             !use_agent && self.run_as_login_shell,
             self.terminal_layout_hint,
             self.cell_metrics_hint,
+            self.agent_limits(use_agent, agent_index),
         );
-        self.tabs.insert(tab_id, tab);
+        match tab {
+            Ok(tab) => {
+                self.tabs.insert(tab_id, tab);
+            }
+            Err(e) => {
+                self.failed.insert(tab_id, e);
+            }
+        }
 
         if let Some(group) = self.groups.get_mut(&group_id) {
             group.tabs.push(TabInfo {
@@ -293,6 +684,7 @@ impl TabManager {
                 is_agent: use_agent,
                 agent_index: if use_agent { agent_index } else { None },
                 display_name: String::new(),
+                pinned: false,
             });
         }
 
@@ -306,9 +698,11 @@ impl TabManager {
         if let Some(group) = self.groups.get(&group_id) {
             for tab_info in &group.tabs {
                 self.tabs.remove(&tab_info.id);
+                self.failed.remove(&tab_info.id);
             }
         }
         self.clear_preload_for_group(group_id);
+        self.group_used.remove(&group_id);
         self.groups.remove(&group_id);
 
         if self.active_group_id == Some(group_id) {
@@ -323,6 +717,17 @@ impl TabManager {
     }
 
     pub fn remove(&mut self, id: u64) {
+        if !self
+            .groups
+            .values()
+            .any(|g| g.tabs.iter().any(|t| t.id == id))
+        {
+            for parked in self.parked.values_mut() {
+                parked.remove_tab(id);
+            }
+            return;
+        }
+
         let mut group_id_to_remove = None;
         let mut affected_group_id = None;
         let mut group_tabs = None;
@@ -331,6 +736,7 @@ impl TabManager {
             if group.tabs.iter().any(|t| t.id == id) {
                 group.tabs.retain(|t| t.id != id);
                 self.tabs.remove(&id);
+                self.failed.remove(&id);
                 group_tabs = Some(group.tabs.clone());
 
                 if group.tabs.is_empty() {
@@ -359,8 +765,10 @@ impl TabManager {
     }
 
     pub fn clear(&mut self) {
+        self.parked.clear();
         self.groups.clear();
         self.tabs.clear();
+        self.failed.clear();
         self.preload_pool.clear();
         self.active_group_id = None;
         self.active_tab_id = None;
@@ -369,6 +777,8 @@ impl TabManager {
     pub fn set_title(&mut self, id: u64, title: String) {
         if let Some(tab) = self.get_tab_mut(id) {
             tab.set_title(title);
+        } else if let Some(tab) = self.parked.values_mut().find_map(|p| p.tabs.get_mut(&id)) {
+            tab.set_title(title);
         }
     }
 
@@ -381,6 +791,9 @@ impl TabManager {
                 break;
             }
         }
+        if let Some(group_id) = self.active_group_id {
+            self.touch_group(group_id);
+        }
 
         if let Some(tab) = self.tabs.get_mut(&id) {
             let is_alternate = tab.is_alternate_screen();
@@ -487,16 +900,19 @@ impl TabManager {
 
     pub fn set_default_shell_cmd(&mut self, shell_cmd: String) {
         self.default_shell_cmd = shell_cmd;
+        self.preload_failed.clear();
     }
 
-    pub fn set_agents(&mut self, agents: [AgentConfig; MAX_AGENTS], ctx: egui::Context) {
+    /// Preloaded tabs are dropped so they are started again with the new
+    /// agent commands.
+    pub fn set_agents(&mut self, agents: [AgentConfig; MAX_AGENTS]) {
         self.agents = agents;
         self.refresh_all_display_names();
+        self.clear_preload_pool();
+    }
 
-        if self.preload_enabled {
-            self.clear_preload_pool();
-            self.populate_preload_pool(ctx);
-        }
+    pub fn set_lazy_spawn(&mut self, lazy_spawn: bool) {
+        self.lazy_spawn = lazy_spawn;
     }
 
     pub fn set_run_as_login_shell(&mut self, run_as_login_shell: bool) {
@@ -557,40 +973,60 @@ impl TabManager {
             !use_agent && self.run_as_login_shell,
             self.terminal_layout_hint,
             self.cell_metrics_hint,
+            self.agent_limits(use_agent, agent_index),
         );
 
-        self.preload_pool.insert((group_id, agent_index), (tab_id, tab));
+        match tab {
+            Ok(tab) => {
+                self.preload_pool
+                    .insert((group_id, agent_index), (tab_id, tab));
+            }
+            Err(_) => {
+                self.preload_failed.insert((group_id, agent_index));
+            }
+        }
+    }
+
+    fn touch_group(&mut self, group_id: u64) {
+        self.group_used.insert(group_id, Instant::now());
+    }
+
+    fn wanted_preload_keys(&self) -> Vec<(u64, Option<usize>)> {
+        let group_used: HashMap<u64, Instant> = self
+            .group_used
+            .iter()
+            .filter(|(id, _)| self.groups.contains_key(id))
+            .map(|(id, used)| (*id, *used))
+            .collect();
+        preload_keys(
+            &group_used,
+            &self.agents,
+            &self.preload_limits,
+            Instant::now(),
+        )
     }
 
-    pub fn populate_preload_for_group(&mut self, group_id: u64, ctx: egui::Context) {
+    /// Bring the preload pool in line with the limits. Called every frame:
+    /// drops tabs of groups that fell out of the recently used ones or went
+    /// idle, and preloads missing ones unless system memory is short.
+    pub fn maintain_preload_pool(&mut self, ctx: &egui::Context, memory_percent: f32) {
         if !self.preload_enabled {
             return;
         }
-
-        let key = (group_id, None);
-        if !self.preload_pool.contains_key(&key) {
-            self.spawn_preload_tab(group_id, None, ctx.clone());
+        if let Some(group_id) = self.active_group_id {
+            self.touch_group(group_id);
         }
 
-        for i in 0..MAX_AGENTS {
-            let key = (group_id, Some(i));
-            if !self.preload_pool.contains_key(&key) {
-                if let Some(agent) = self.agents.get(i) {
-                    if agent.enabled && !agent.cmd.trim().is_empty() {
-                        self.spawn_preload_tab(group_id, Some(i), ctx.clone());
-                    }
-                }
-            }
-        }
-    }
-
-    pub fn populate_preload_pool(&mut self, ctx: egui::Context) {
-        if !self.preload_enabled {
+        let wanted = self.wanted_preload_keys();
+        self.preload_pool.retain(|key, _| wanted.contains(key));
+        if self.preload_limits.memory_exceeded(memory_percent) {
             return;
         }
-        let group_ids: Vec<u64> = self.groups.keys().copied().collect();
-        for group_id in group_ids {
-            self.populate_preload_for_group(group_id, ctx.clone());
+        for (group_id, agent_index) in wanted {
+            let key = (group_id, agent_index);
+            if !self.preload_pool.contains_key(&key) && !self.preload_failed.contains(&key) {
+                self.spawn_preload_tab(group_id, agent_index, ctx.clone());
+            }
         }
     }
 
@@ -600,18 +1036,298 @@ impl TabManager {
 
     pub fn clear_preload_pool(&mut self) {
         self.preload_pool.clear();
+        self.preload_failed.clear();
     }
 
+    /// A preloaded tab exited before it was used, e.g. because the agent is
+    /// not installed or not logged in. Its key is not preloaded again, which
+    /// would otherwise respawn it every frame.
     pub fn remove_preload_tab(&mut self, tab_id: u64) {
-        self.preload_pool.retain(|_, (id, _)| *id != tab_id);
+        let key = self
+            .preload_pool
+            .iter()
+            .find(|(_, (id, _))| *id == tab_id)
+            .map(|(key, _)| *key);
+        if let Some(key) = key {
+            self.preload_pool.remove(&key);
+            self.preload_failed.insert(key);
+        }
     }
 
-    pub fn set_preload_enabled(&mut self, enabled: bool, ctx: egui::Context) {
+    pub fn set_preload_enabled(&mut self, enabled: bool) {
         self.preload_enabled = enabled;
-        if enabled {
-            self.populate_preload_pool(ctx);
-        } else {
+        if !enabled {
             self.clear_preload_pool();
         }
     }
+
+    pub fn set_preload_limits(&mut self, limits: PreloadLimits) {
+        self.preload_limits = limits;
+    }
+}
+
+/// `id` if it is not in `used` yet, else the next free id. Either way the
+/// id is marked used and `next` moves past it.
+fn keep_or_next_id(id: u64, used: &mut HashSet<u64>, next: &mut u64) -> u64 {
+    let id = if used.contains(&id) { *next } else { id };
+    used.insert(id);
+    *next = (*next).max(id + 1);
+    id
+}
+
+/// Preload keys wanted under `limits`: a terminal and every enabled,
+/// preloadable agent for the most recently used groups in `group_used`,
+/// most recent first, up to the global cap.
+fn preload_keys(
+    group_used: &HashMap<u64, Instant>,
+    agents: &[AgentConfig],
+    limits: &PreloadLimits,
+    now: Instant,
+) -> Vec<(u64, Option<usize>)> {
+    let idle_timeout = limits.idle_timeout();
+    let mut groups: Vec<(u64, Instant)> = group_used
+        .iter()
+        .filter(|(_, used)| idle_timeout.is_none_or(|t| now.duration_since(**used) < t))
+        .map(|(id, used)| (*id, *used))
+        .collect();
+    groups.sort_by_key(|(_, used)| std::cmp::Reverse(*used));
+    groups.truncate(limits.recent_groups);
+
+    let mut keys = Vec::new();
+    for (group_id, _) in groups {
+        keys.push((group_id, None));
+        for (i, agent) in agents.iter().enumerate() {
+            if agent.enabled && agent.preload && !agent.cmd.trim().is_empty() {
+                keys.push((group_id, Some(i)));
+            }
+        }
+    }
+    keys.truncate(limits.max_tabs);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn agent(cmd: &str, enabled: bool, preload: bool) -> AgentConfig {
+        AgentConfig {
+            cmd: cmd.to_string(),
+            enabled,
+            preload,
+            ..Default::default()
+        }
+    }
+
+    fn limits(max_tabs: usize, recent_groups: usize, idle_mins: u64) -> PreloadLimits {
+        PreloadLimits {
+            max_tabs,
+            recent_groups,
+            idle_mins,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_preload_keys_recent_groups_and_agents() {
+        let now = Instant::now();
+        let group_used = HashMap::from([
+            (1, now - Duration::from_secs(30)),
+            (2, now - Duration::from_secs(10)),
+            (3, now - Duration::from_secs(20)),
+        ]);
+        let agents = [
+            agent("claude", true, true),
+            agent("codex", true, false),
+            agent("gemini", false, true),
+            agent("  ", true, true),
+        ];
+
+        // Only the two most recent groups; opted-out, disabled and empty
+        // agents are skipped.
+        assert_eq!(
+            preload_keys(&group_used, &agents, &limits(10, 2, 0), now),
+            [(2, None), (2, Some(0)), (3, None), (3, Some(0))]
+        );
+        // The global cap cuts the least recent group short.
+        assert_eq!(
+            preload_keys(&group_used, &agents, &limits(3, 3, 0), now),
+            [(2, None), (2, Some(0)), (3, None)]
+        );
+        assert!(preload_keys(&group_used, &agents, &limits(0, 3, 0), now).is_empty());
+        assert!(preload_keys(&HashMap::new(), &agents, &limits(10, 3, 0), now).is_empty());
+    }
+
+    #[test]
+    fn test_preload_keys_idle_groups() {
+        let now = Instant::now();
+        let group_used = HashMap::from([
+            (1, now - Duration::from_secs(5 * 60)),
+            (2, now - Duration::from_secs(60)),
+        ]);
+
+        assert_eq!(
+            preload_keys(&group_used, &[], &limits(10, 3, 2), now),
+            [(2, None)]
+        );
+        // 0 keeps idle groups.
+        assert_eq!(
+            preload_keys(&group_used, &[], &limits(10, 3, 0), now),
+            [(2, None), (1, None)]
+        );
+    }
+
+    /// A manager with `groups` of (id, window, path, tab ids) whose tabs
+    /// never started.
+    fn manager(groups: &[(u64, u64, &str, &[u64])]) -> TabManager {
+        let mut manager = TabManager {
+            command_sender: std::sync::mpsc::channel().0,
+            workspace: String::new(),
+            groups_file: None,
+            parked: HashMap::new(),
+            groups: BTreeMap::new(),
+            tabs: BTreeMap::new(),
+            failed: HashMap::new(),
+            preload_pool: HashMap::new(),
+            preload_limits: PreloadLimits::default(),
+            preload_failed: HashSet::new(),
+            group_used: HashMap::new(),
+            active_group_id: None,
+            active_tab_id: None,
+            window: MAIN_WINDOW,
+            window_selection: HashMap::new(),
+            next_group_id: 0,
+            next_tab_id: 0,
+            default_shell_cmd: String::new(),
+            agents: Default::default(),
+            run_as_login_shell: false,
+            preload_enabled: false,
+            lazy_spawn: true,
+            terminal_layout_hint: None,
+            cell_metrics_hint: None,
+        };
+        for &(id, window, path, tab_ids) in groups {
+            let mut group = TabGroup::new(id, path.to_string(), PathBuf::from(path));
+            group.window = window;
+            group.tabs = tab_ids
+                .iter()
+                .map(|&id| TabInfo {
+                    id,
+                    is_agent: false,
+                    agent_index: None,
+                    display_name: String::new(),
+                    pinned: false,
+                })
+                .collect();
+            manager.next_group_id = manager.next_group_id.max(id + 1);
+            manager.next_tab_id = tab_ids
+                .iter()
+                .fold(manager.next_tab_id, |n, &t| n.max(t + 1));
+            manager.groups.insert(id, group);
+        }
+        manager
+    }
+
+    fn tab_ids(manager: &TabManager, group_id: u64) -> Vec<u64> {
+        manager.groups[&group_id]
+            .tabs
+            .iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_focus_window_keeps_each_selection() {
+        let mut manager = manager(&[
+            (0, MAIN_WINDOW, "/a", &[10, 11]),
+            (1, MAIN_WINDOW, "/c", &[12]),
+            (2, 1, "/b", &[20]),
+        ]);
+        manager.active_group_id = Some(0);
+        manager.active_tab_id = Some(11);
+        let selection = |m: &TabManager| (m.active_group_id, m.active_tab_id);
+
+        manager.focus_window(1);
+        assert_eq!(selection(&manager), (Some(2), Some(20)));
+        manager.focus_window(MAIN_WINDOW);
+        assert_eq!(selection(&manager), (Some(0), Some(11)));
+
+        // A selected group moved away falls back to the window's first one.
+        manager.focus_window(1);
+        manager.move_group_to_window(0, 1);
+        manager.focus_window(MAIN_WINDOW);
+        assert_eq!(selection(&manager), (Some(1), Some(12)));
+
+        manager.focus_window(2);
+        assert_eq!(selection(&manager), (None, None));
+    }
+
+    #[test]
+    fn test_move_tab_to_window() {
+        let mut manager = manager(&[(0, MAIN_WINDOW, "/a", &[10, 11]), (1, 1, "/b", &[20])]);
+
+        // No group of the project in the window yet: one is made like it.
+        manager.move_tab_to_window(10, 1);
+        assert_eq!(tab_ids(&manager, 0), [11]);
+        assert_eq!(manager.groups[&0].tabs[0].display_name, "1. Terminal");
+        let target = &manager.groups[&2];
+        assert_eq!((target.window, target.path.as_path()), (1, Path::new("/a")));
+        assert_eq!(tab_ids(&manager, 2), [10]);
+
+        // The last tab joins that group and the emptied source is removed.
+        manager.move_tab_to_window(11, 1);
+        assert!(!manager.groups.contains_key(&0));
+        assert_eq!(tab_ids(&manager, 2), [10, 11]);
+        assert_eq!(manager.groups[&2].tabs[1].display_name, "2. Terminal");
+
+        // Moving within the same window or an unknown tab does nothing.
+        manager.move_tab_to_window(20, 1);
+        manager.move_tab_to_window(99, MAIN_WINDOW);
+        assert_eq!(manager.groups.len(), 2);
+        assert_eq!(tab_ids(&manager, 1), [20]);
+    }
+
+    #[test]
+    fn test_move_window_groups() {
+        let mut manager = manager(&[
+            (0, MAIN_WINDOW, "/a", &[10]),
+            (1, 1, "/b", &[20]),
+            (2, 1, "/c", &[]),
+            (3, 2, "/d", &[30]),
+        ]);
+
+        manager.move_window_groups(1, MAIN_WINDOW);
+        let windows: Vec<u64> = manager.groups.values().map(|g| g.window).collect();
+        assert_eq!(windows, [MAIN_WINDOW, MAIN_WINDOW, MAIN_WINDOW, 2]);
+        assert_eq!(tab_ids(&manager, 1), [20]);
+    }
+
+    #[test]
+    fn test_keep_or_next_id() {
+        let mut used = HashSet::from([0, 1]);
+        let mut next = 2;
+        assert_eq!(keep_or_next_id(5, &mut used, &mut next), 5);
+        assert_eq!(next, 6);
+        // Taken ids, including a duplicate in the same file, get new ones.
+        assert_eq!(keep_or_next_id(1, &mut used, &mut next), 6);
+        assert_eq!(keep_or_next_id(5, &mut used, &mut next), 7);
+        assert_eq!(keep_or_next_id(3, &mut used, &mut next), 3);
+        assert_eq!(next, 8);
+        assert_eq!(used, HashSet::from([0, 1, 3, 5, 6, 7]));
+    }
+
+    #[test]
+    fn test_auto_accent() {
+        let path = PathBuf::from("/home/me/project");
+        assert_eq!(auto_accent(&path), auto_accent(&path));
+        assert_ne!(auto_accent(&path), auto_accent(Path::new("/home/me/other")));
+        let hsva = egui::ecolor::Hsva::from(auto_accent(&path));
+        assert!((hsva.s - 0.55).abs() < 0.02 && (hsva.v - 0.85).abs() < 0.02);
+
+        let mut group = TabGroup::new(1, "project".to_string(), path.clone());
+        assert_eq!(group.accent_color(), auto_accent(&path));
+        group.accent = Some(egui::Color32::RED);
+        assert_eq!(group.accent_color(), egui::Color32::RED);
+    }
 }
@@ -1,4 +1,5 @@
 pub mod manager;
+pub mod spawn;
 pub mod tab;
 
 pub use manager::TabManager;
@@ -0,0 +1,147 @@
+use std::fmt::Display;
+
+/// A candidate program that could not be started, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnAttempt {
+    pub command: String,
+    pub error: String,
+}
+
+/// No candidate could be started. The tab shows this instead of a terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnError {
+    pub is_agent: bool,
+    pub attempts: Vec<SpawnAttempt>,
+}
+
+/// Try `candidates` in order until `spawn` succeeds. For agents the first
+/// candidate is the configured command and may include arguments; later
+/// candidates are bare shell paths. Shells get `--login` when requested.
+pub fn spawn_with_fallback<T, E: Display>(
+    candidates: &[String],
+    is_agent: bool,
+    run_as_login_shell: bool,
+    mut spawn: impl FnMut(&str, Vec<String>) -> Result<T, E>,
+) -> Result<T, SpawnError> {
+    let mut attempts = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let (program, mut args) = if is_agent && index == 0 {
+            let mut parts = candidate.split_whitespace();
+            let program = parts.next().unwrap_or(candidate).to_string();
+            (program, parts.map(str::to_string).collect())
+        } else {
+            (candidate.clone(), Vec::new())
+        };
+        if run_as_login_shell && !is_agent {
+            args.push("--login".to_string());
+        }
+
+        match spawn(&program, args) {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                log::warn!("Failed to start '{}': {}", candidate, e);
+                attempts.push(SpawnAttempt {
+                    command: candidate.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    Err(SpawnError { is_agent, attempts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_first_candidate_wins() {
+        let mut calls = Vec::new();
+        let result = spawn_with_fallback(
+            &candidates(&["/bin/zsh", "/bin/bash"]),
+            false,
+            false,
+            |program, args| {
+                calls.push((program.to_string(), args));
+                Ok::<_, String>(program.to_string())
+            },
+        );
+        assert_eq!(result.unwrap(), "/bin/zsh");
+        assert_eq!(calls, [("/bin/zsh".to_string(), vec![])]);
+    }
+
+    #[test]
+    fn test_agent_falls_back_to_bare_shells() {
+        let mut calls = Vec::new();
+        let result = spawn_with_fallback(
+            &candidates(&["missing-agent --model x", "/nope/bash", "/bin/bash"]),
+            true,
+            true,
+            |program, args| {
+                calls.push((program.to_string(), args));
+                if program == "/bin/bash" {
+                    Ok(())
+                } else {
+                    Err("not found")
+                }
+            },
+        );
+        assert!(result.is_ok());
+        assert_eq!(
+            calls,
+            [
+                ("missing-agent".to_string(), candidates(&["--model", "x"])),
+                ("/nope/bash".to_string(), vec![]),
+                ("/bin/bash".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_login_flag_on_every_shell() {
+        let mut calls = Vec::new();
+        let _ = spawn_with_fallback(
+            &candidates(&["/bin/zsh", "/bin/bash"]),
+            false,
+            true,
+            |_, args| {
+                calls.push(args);
+                Err::<(), _>("failed")
+            },
+        );
+        assert_eq!(calls, [candidates(&["--login"]), candidates(&["--login"])]);
+    }
+
+    #[test]
+    fn test_all_failures_are_reported() {
+        let result = spawn_with_fallback(
+            &candidates(&["/bin/zsh", "/bin/bash"]),
+            false,
+            false,
+            |program, _| Err::<(), _>(format!("{} is missing", program)),
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            SpawnError {
+                is_agent: false,
+                attempts: vec![
+                    SpawnAttempt {
+                        command: "/bin/zsh".to_string(),
+                        error: "/bin/zsh is missing".to_string(),
+                    },
+                    SpawnAttempt {
+                        command: "/bin/bash".to_string(),
+                        error: "/bin/bash is missing".to_string(),
+                    },
+                ],
+            }
+        );
+
+        let result = spawn_with_fallback(&[], true, false, |_, _| Ok::<_, String>(()));
+        assert!(result.unwrap_err().attempts.is_empty());
+    }
+}
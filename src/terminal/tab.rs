@@ -1,3 +1,5 @@
+use crate::resource_limits::{self, MemoryEnforcement, ResourceLimits};
+use crate::terminal::spawn::{spawn_with_fallback, SpawnError};
 use alacritty_terminal::grid::Dimensions;
 use egui_term::{PtyEvent, TerminalBackend, TerminalMode};
 use std::{path::PathBuf, sync::mpsc::Sender};
@@ -47,6 +49,10 @@ impl TabScrollState {
 
 pub struct Tab {
     pub backend: TerminalBackend,
+    /// Resource limits the tab was started with, and how its memory limit is
+    /// enforced.
+    pub limits: ResourceLimits,
+    pub memory: MemoryEnforcement,
     pub title: String,
     pub scroll_state: TabScrollState,
     pub was_alternate_last_frame: bool,
@@ -65,26 +71,7 @@ impl Tab {
     }
 
     pub fn command_exists(cmd: &str) -> bool {
-        // Extract just the program name (first word) from the command
-        let program = cmd.split_whitespace().next().unwrap_or(cmd);
-
-        #[cfg(unix)]
-        {
-            use std::process::Command;
-            if let Ok(output) = Command::new("which").arg(program).output() {
-                output.status.success()
-            } else {
-                false
-            }
-        }
-        #[cfg(windows)]
-        {
-            use std::process::Command;
-            Command::new("where")
-                .arg(program)
-                .output()
-                .map_or(false, |output| output.status.success())
-        }
+        crate::executables::resolve(cmd).is_some()
     }
 
     fn shell_candidates(shell_cmd: &str, is_agent: bool) -> Vec<String> {
@@ -143,6 +130,8 @@ impl Tab {
         return "cmd.exe".to_string();
     }
 
+    /// Start `shell_cmd`, falling back to the usual shells, under `limits`.
+    /// Fails with every attempted command when none of them could be started.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         ctx: egui::Context,
@@ -154,68 +143,48 @@ impl Tab {
         run_as_login_shell: bool,
         layout_hint: Option<egui_term::Size>,
         cell_hint: Option<egui_term::Size>,
-    ) -> Self {
-        let mut candidates = Self::shell_candidates(shell_cmd, is_agent).into_iter();
-
-        // For agents the first candidate is the configured agent command and may
-        // include arguments. For regular shells the candidate is just the shell path.
-        let first = candidates
-            .next()
-            .unwrap_or_else(|| Self::resolve_shell("", false));
-        let mut shell = first.clone();
-        let mut args: Vec<String> = Vec::new();
-        if is_agent {
-            let parts: Vec<&str> = first.split_whitespace().collect();
-            if parts.len() > 1 {
-                shell = parts[0].to_string();
-                args = parts[1..].iter().map(|s| s.to_string()).collect();
-            }
-        }
-
-        // Add login shell flag if needed (only for non-agent shells)
-        if run_as_login_shell && !is_agent {
-            args.push("--login".to_string());
+        limits: ResourceLimits,
+    ) -> Result<Self, SpawnError> {
+        let mut candidates = Self::shell_candidates(shell_cmd, is_agent);
+        if candidates.is_empty() {
+            candidates.push(Self::resolve_shell("", false));
         }
 
-        let backend = loop {
-            let result = TerminalBackend::new(
-                id,
-                ctx.clone(),
-                command_sender.clone(),
-                egui_term::BackendSettings {
-                    shell: shell.clone(),
-                    args: args.clone(),
-                    working_directory: working_dir.clone(),
-                    initial_layout_size: layout_hint,
-                    initial_cell_metrics: cell_hint,
-                    ..Default::default()
-                },
-            );
-
-            match result {
-                Ok(backend) => break backend,
-                Err(e) => {
-                    eprintln!(
-                        "Failed to create terminal backend with shell '{}': {}",
-                        shell, e
-                    );
-
-                    let Some(next) = candidates.next() else {
-                        panic!("All fallback shells failed. Last error: {}", e);
-                    };
-
-                    // Subsequent fallbacks are bare shell paths; clear agent args.
-                    shell = next;
-                    if is_agent {
-                        args.clear();
-                    }
-                    eprintln!("Retrying with fallback shell: {}", shell);
-                }
-            }
-        };
-
-        Self {
+        let (backend, memory) =
+            spawn_with_fallback(&candidates, is_agent, run_as_login_shell, |shell, args| {
+                // Resolved here, as the login-shell PATH captured in this run
+                // may find programs the PATH terminals inherit does not.
+                let shell = crate::executables::resolve(shell)
+                    .ok_or_else(|| "not found on PATH or not executable".to_string())?;
+                let (program, args, memory) = resource_limits::limited_command(
+                    id,
+                    &limits,
+                    shell.to_string_lossy().into_owned(),
+                    args,
+                );
+                let backend = TerminalBackend::new(
+                    id,
+                    ctx.clone(),
+                    command_sender.clone(),
+                    egui_term::BackendSettings {
+                        shell: program,
+                        args,
+                        working_directory: working_dir.clone(),
+                        initial_layout_size: layout_hint,
+                        initial_cell_metrics: cell_hint,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|e| e.to_string())?;
+                Ok::<_, String>((backend, memory))
+            })?;
+        // Set before the program gets far, so its children inherit them.
+        resource_limits::apply(backend.pty_id(), &limits);
+
+        Ok(Self {
             backend,
+            limits,
+            memory,
             title: format!("tab: {}", id),
             scroll_state: TabScrollState::default(),
             was_alternate_last_frame: false,
@@ -223,7 +192,7 @@ impl Tab {
             search_active: false,
             search_query: String::new(),
             search_just_opened: false,
-        }
+        })
     }
 
     pub fn set_title(&mut self, title: String) {
@@ -1,5 +1,8 @@
 use egui::Color32;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::constants::TERMINAL_FONT_FAMILY;
 
 /// The default terminal foreground color.
 pub const DEFAULT_TERMINAL_FG: Color32 = Color32::from_rgb(0xd8, 0xd8, 0xd8);
@@ -32,6 +35,9 @@ pub struct AppTheme {
     /// Terminal foreground color.
     #[serde(with = "color32_hex")]
     pub terminal_fg: Color32,
+    /// ANSI, dim, cursor and selection colors of the terminal.
+    #[serde(default)]
+    pub terminal_palette: TerminalPalette,
     /// Font sizes used throughout the app.
     #[serde(default)]
     pub fonts: AppFonts,
@@ -64,6 +70,7 @@ impl Default for AppTheme {
             tab_text: Color32::from_rgb(0xd8, 0xd8, 0xd8),
             tab_active_bg: Color32::from_rgb(0x02, 0x5f, 0x99),
             terminal_fg: DEFAULT_TERMINAL_FG,
+            terminal_palette: TerminalPalette::default(),
             fonts: AppFonts::default(),
             tab_button: AppButtonStyle {
                 bg: Color32::TRANSPARENT,
@@ -91,10 +98,22 @@ impl AppTheme {
         with_alpha(self.app_bg, self.app_bg_opacity)
     }
 
-    /// Apply UI colors to the current egui visuals. This gives an immediate
-    /// preview while the user is editing the theme.
-    pub fn apply_to_visuals(&self, ctx: &egui::Context) {
-        let mut visuals = ctx.global_style().visuals.clone();
+    /// Whether the background is light, so the UI needs egui's light visuals
+    /// (and a light window title bar) underneath the theme colors.
+    pub fn is_light(&self) -> bool {
+        let bg = self.app_bg;
+        let luma = 0.299 * bg.r() as f32 + 0.587 * bg.g() as f32 + 0.114 * bg.b() as f32;
+        luma > 127.5
+    }
+
+    /// Build egui visuals from this theme, on top of egui's light or dark
+    /// visuals depending on the background.
+    pub fn visuals(&self) -> egui::Visuals {
+        let mut visuals = if self.is_light() {
+            egui::Visuals::light()
+        } else {
+            egui::Visuals::dark()
+        };
         let app_bg = self.app_bg_with_opacity();
         visuals.panel_fill = app_bg;
         visuals.window_fill = app_bg;
@@ -103,26 +122,321 @@ impl AppTheme {
         visuals.override_text_color = Some(self.panel_text);
         visuals.selection.bg_fill = self.tab_active_bg;
         visuals.selection.stroke.color = self.tab_active_bg;
-        ctx.set_visuals(visuals);
+        visuals
+    }
+
+    /// Apply UI colors to the current egui visuals. This gives an immediate
+    /// preview while the user is editing the theme.
+    pub fn apply_to_visuals(&self, ctx: &egui::Context) {
+        ctx.set_visuals(self.visuals());
     }
 
     /// Build the terminal theme from the configured terminal colors.
+    ///
+    /// egui_term draws the cursor and selection by inverting the cell, so its
+    /// palette has no slots for `cursor`, `cursor_text` and `selection` yet;
+    /// they are kept in the theme for when it does.
     pub fn build_terminal_theme(&self) -> egui_term::TerminalTheme {
+        let p = &self.terminal_palette;
         let mut palette = egui_term::ColorPalette::default();
         palette.foreground = color_to_hex(self.terminal_fg);
         palette.background = color_to_hex(self.app_bg_with_opacity());
+        palette.dim_foreground = color_to_hex(p.dim_foreground);
+        [
+            palette.black,
+            palette.red,
+            palette.green,
+            palette.yellow,
+            palette.blue,
+            palette.magenta,
+            palette.cyan,
+            palette.white,
+        ] = p.normal.map(color_to_hex);
+        [
+            palette.bright_black,
+            palette.bright_red,
+            palette.bright_green,
+            palette.bright_yellow,
+            palette.bright_blue,
+            palette.bright_magenta,
+            palette.bright_cyan,
+            palette.bright_white,
+        ] = p.bright.map(color_to_hex);
+        [
+            palette.dim_black,
+            palette.dim_red,
+            palette.dim_green,
+            palette.dim_yellow,
+            palette.dim_blue,
+            palette.dim_magenta,
+            palette.dim_cyan,
+            palette.dim_white,
+        ] = p.dim.map(color_to_hex);
         egui_term::TerminalTheme::new(Box::new(palette))
     }
 
-    /// Build the terminal font from the configured terminal font size.
+    /// Build the terminal font from the configured terminal font size. The
+    /// family is registered by `font_setup::setup_fonts`.
     pub fn terminal_font(&self) -> egui_term::TerminalFont {
         egui_term::TerminalFont::new(egui_term::FontSettings {
-            font_type: egui::FontId::monospace(self.fonts.terminal_font_size),
+            font_type: egui::FontId::new(
+                self.fonts.terminal_font_size,
+                egui::FontFamily::Name(TERMINAL_FONT_FAMILY.into()),
+            ),
         })
     }
 }
 
-/// Font sizes used throughout the application.
+/// The bundled presets of the theme library, in menu order. "Dark" is the
+/// default theme.
+pub fn builtin_themes() -> Vec<(&'static str, AppTheme)> {
+    vec![
+        ("Dark", AppTheme::default()),
+        ("Light", light_theme()),
+        ("Solarized Dark", solarized_theme(false)),
+        ("Solarized Light", solarized_theme(true)),
+        ("High Contrast", high_contrast_theme()),
+    ]
+}
+
+const fn rgb(hex: u32) -> Color32 {
+    Color32::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+/// A filled action button; the border matches `page_bg` so it is invisible.
+fn filled_button(
+    bg: u32,
+    text: u32,
+    page_bg: u32,
+    bg_hover: u32,
+    text_hover: u32,
+) -> AppButtonStyle {
+    AppButtonStyle {
+        bg: rgb(bg),
+        text: rgb(text),
+        border: rgb(page_bg),
+        bg_hover: rgb(bg_hover),
+        text_hover: rgb(text_hover),
+        border_hover: rgb(page_bg),
+    }
+}
+
+/// A sidebar tab button without fill or border until hovered.
+fn flat_button(text: u32, bg_hover: u32) -> AppButtonStyle {
+    AppButtonStyle {
+        bg: Color32::TRANSPARENT,
+        text: rgb(text),
+        border: Color32::TRANSPARENT,
+        bg_hover: rgb(bg_hover),
+        text_hover: rgb(text),
+        border_hover: Color32::TRANSPARENT,
+    }
+}
+
+/// Terminal palette from normal and bright colors, with derived dim colors.
+fn palette(
+    normal: [u32; 8],
+    bright: [u32; 8],
+    fg: u32,
+    cursor: u32,
+    bg: u32,
+    selection: u32,
+) -> TerminalPalette {
+    let mut palette = TerminalPalette {
+        normal: normal.map(rgb),
+        bright: bright.map(rgb),
+        cursor: rgb(cursor),
+        cursor_text: rgb(bg),
+        selection: rgb(selection),
+        ..TerminalPalette::default()
+    };
+    palette.derive_dim(rgb(fg));
+    palette
+}
+
+/// Based on Atom's One Light.
+fn light_theme() -> AppTheme {
+    let (bg, fg) = (0xfafafa, 0x383a42);
+    let button = filled_button(0xe5e5e6, fg, bg, 0xd4d4d6, 0x000000);
+    AppTheme {
+        app_bg: rgb(bg),
+        panel_text: rgb(fg),
+        panel_text_selected: rgb(0x0184bc),
+        panel_text_hover: rgb(0x4078f2),
+        tab_text: rgb(fg),
+        tab_active_bg: rgb(0xcfe3f7),
+        terminal_fg: rgb(fg),
+        terminal_palette: palette(
+            [
+                0x000000, 0xe45649, 0x50a14f, 0xc18401, 0x4078f2, 0xa626a4, 0x0184bc, 0xa0a1a7,
+            ],
+            [
+                0x5c6370, 0xe06c75, 0x98c379, 0xd19a66, 0x61afef, 0xc678dd, 0x56b6c2, 0xffffff,
+            ],
+            fg,
+            0x526eff,
+            bg,
+            0xe5e5e6,
+        ),
+        tab_button: flat_button(fg, 0xe5e5e6),
+        close_button: button,
+        agent_button: button,
+        terminal_button: button,
+        ..AppTheme::default()
+    }
+}
+
+/// Ethan Schoonover's Solarized, dark or light.
+fn solarized_theme(light: bool) -> AppTheme {
+    let (bg, bg_highlight, fg, emphasis) = if light {
+        (0xfdf6e3, 0xeee8d5, 0x657b83, 0x586e75)
+    } else {
+        (0x002b36, 0x073642, 0x839496, 0x93a1a1)
+    };
+    let button = filled_button(bg_highlight, emphasis, bg, 0x93a1a1, 0x002b36);
+    AppTheme {
+        app_bg: rgb(bg),
+        panel_text: rgb(emphasis),
+        panel_text_selected: rgb(0xb58900),
+        panel_text_hover: rgb(0xcb4b16),
+        tab_text: rgb(emphasis),
+        tab_active_bg: rgb(bg_highlight),
+        terminal_fg: rgb(fg),
+        terminal_palette: palette(
+            [
+                0x073642, 0xdc322f, 0x859900, 0xb58900, 0x268bd2, 0xd33682, 0x2aa198, 0xeee8d5,
+            ],
+            [
+                0x002b36, 0xcb4b16, 0x586e75, 0x657b83, 0x839496, 0x6c71c4, 0x93a1a1, 0xfdf6e3,
+            ],
+            fg,
+            emphasis,
+            bg,
+            bg_highlight,
+        ),
+        tab_button: flat_button(emphasis, bg_highlight),
+        close_button: button,
+        agent_button: button,
+        terminal_button: button,
+        ..AppTheme::default()
+    }
+}
+
+/// White on black with saturated colors and visible button borders.
+fn high_contrast_theme() -> AppTheme {
+    let (bg, fg) = (0x000000, 0xffffff);
+    let button = AppButtonStyle {
+        border: rgb(fg),
+        border_hover: rgb(fg),
+        ..filled_button(bg, fg, bg, fg, bg)
+    };
+    AppTheme {
+        app_bg: rgb(bg),
+        panel_text: rgb(fg),
+        panel_text_selected: rgb(0xffff00),
+        panel_text_hover: rgb(0x00ffff),
+        tab_text: rgb(fg),
+        tab_active_bg: rgb(0x0037da),
+        terminal_fg: rgb(fg),
+        terminal_palette: palette(
+            [
+                0x000000, 0xff5555, 0x55ff55, 0xffff55, 0x5c5cff, 0xff55ff, 0x55ffff, 0xe5e5e5,
+            ],
+            [
+                0x7f7f7f, 0xff8080, 0x80ff80, 0xffff80, 0x8080ff, 0xff80ff, 0x80ffff, 0xffffff,
+            ],
+            fg,
+            fg,
+            bg,
+            0x0037da,
+        ),
+        tab_button: AppButtonStyle {
+            text_hover: rgb(bg),
+            ..flat_button(fg, fg)
+        },
+        close_button: button,
+        agent_button: button,
+        terminal_button: button,
+        ..AppTheme::default()
+    }
+}
+
+/// Names of the eight ANSI colors, in palette order.
+pub const ANSI_COLOR_NAMES: [&str; 8] = [
+    "Black", "Red", "Green", "Yellow", "Blue", "Magenta", "Cyan", "White",
+];
+
+/// How much darker dim colors are than normal ones, as in Alacritty.
+const DIM_FACTOR: f32 = 0.66;
+
+/// The terminal's 16 ANSI colors with their dim variants, plus cursor and
+/// selection colors. The defaults match egui_term's built-in palette.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TerminalPalette {
+    #[serde(
+        serialize_with = "color32_hex_array::serialize",
+        deserialize_with = "color32_hex_array::normal"
+    )]
+    pub normal: [Color32; 8],
+    #[serde(
+        serialize_with = "color32_hex_array::serialize",
+        deserialize_with = "color32_hex_array::bright"
+    )]
+    pub bright: [Color32; 8],
+    #[serde(
+        serialize_with = "color32_hex_array::serialize",
+        deserialize_with = "color32_hex_array::dim"
+    )]
+    pub dim: [Color32; 8],
+    #[serde(with = "color32_hex")]
+    pub dim_foreground: Color32,
+    #[serde(with = "color32_hex")]
+    pub cursor: Color32,
+    /// Color of the character under a block cursor.
+    #[serde(with = "color32_hex")]
+    pub cursor_text: Color32,
+    #[serde(with = "color32_hex")]
+    pub selection: Color32,
+}
+
+impl Default for TerminalPalette {
+    fn default() -> Self {
+        let rgb = |hex: u32| Color32::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8);
+        Self {
+            normal: [
+                0x181818, 0xac4242, 0x90a959, 0xf4bf75, 0x6a9fb5, 0xaa759f, 0x75b5aa, 0xd8d8d8,
+            ]
+            .map(rgb),
+            bright: [
+                0x6b6b6b, 0xc55555, 0xaac474, 0xfeca88, 0x82b8c8, 0xc28cb8, 0x93d3c3, 0xf8f8f8,
+            ]
+            .map(rgb),
+            dim: [
+                0x0f0f0f, 0x712b2b, 0x5f6f3a, 0xa17e4d, 0x456877, 0x704d68, 0x4d7770, 0x8e8e8e,
+            ]
+            .map(rgb),
+            dim_foreground: rgb(0x828482),
+            cursor: DEFAULT_TERMINAL_FG,
+            cursor_text: rgb(0x1d1d1d),
+            selection: rgb(0x44475a),
+        }
+    }
+}
+
+impl TerminalPalette {
+    /// Recompute the dim colors from the normal ones and `foreground`.
+    pub fn derive_dim(&mut self, foreground: Color32) {
+        self.dim = self.normal.map(dim);
+        self.dim_foreground = dim(foreground);
+    }
+}
+
+fn dim(color: Color32) -> Color32 {
+    let scale = |c: u8| (c as f32 * DIM_FACTOR).round() as u8;
+    Color32::from_rgb(scale(color.r()), scale(color.g()), scale(color.b()))
+}
+
+/// Font sizes and terminal spacing used throughout the application.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct AppFonts {
     /// General UI font size (menu, buttons, body text).
@@ -137,6 +451,12 @@ pub struct AppFonts {
     /// Terminal font size.
     #[serde(default = "default_terminal_font_size")]
     pub terminal_font_size: f32,
+    /// Terminal row height as a multiple of the font's own line height.
+    #[serde(default = "default_line_height")]
+    pub line_height: f32,
+    /// Extra space between terminal columns, in points.
+    #[serde(default)]
+    pub letter_spacing: f32,
 }
 
 fn default_ui_font_size() -> f32 {
@@ -155,6 +475,10 @@ fn default_terminal_font_size() -> f32 {
     14.0
 }
 
+fn default_line_height() -> f32 {
+    1.0
+}
+
 impl Default for AppFonts {
     fn default() -> Self {
         Self {
@@ -162,6 +486,8 @@ impl Default for AppFonts {
             group_name_font_size: default_group_name_font_size(),
             tab_font_size: default_tab_font_size(),
             terminal_font_size: default_terminal_font_size(),
+            line_height: default_line_height(),
+            letter_spacing: 0.0,
         }
     }
 }
@@ -195,6 +521,37 @@ impl AppFonts {
     }
 }
 
+/// Where a font is loaded from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FontSource {
+    /// An installed font, by fontconfig name.
+    System(String),
+    /// A TrueType or OpenType file.
+    File(PathBuf),
+}
+
+impl FontSource {
+    pub fn label(&self) -> String {
+        match self {
+            FontSource::System(name) => name.clone(),
+            FontSource::File(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+        }
+    }
+}
+
+/// Fonts of the UI and the terminal; `None` keeps egui's defaults. Kept out
+/// of `AppFonts` so the theme stays `Copy`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FontFaces {
+    #[serde(default)]
+    pub ui: Option<FontSource>,
+    #[serde(default)]
+    pub terminal: Option<FontSource>,
+}
+
 /// Render a label plus a font-size slider.
 pub fn font_size_slider(ui: &mut egui::Ui, label: &str, size: &mut f32) {
     ui.horizontal(|ui| {
@@ -361,6 +718,42 @@ pub fn opacity_slider(ui: &mut egui::Ui, label: &str, opacity: &mut u8) {
     });
 }
 
+/// A bare color swatch that opens the color picker (with a hex field) on
+/// click. For dense grids where `color_picker_button`'s label and text field
+/// do not fit; `id_salt` must be unique within the parent `Ui`.
+pub fn color_swatch_picker(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    color: &mut Color32,
+) -> egui::Response {
+    let popup_id = ui.make_persistent_id(id_salt);
+    let button_response = color_button_with_black_border(ui, *color);
+
+    egui::Popup::from_toggle_button_response(&button_response)
+        .id(popup_id)
+        .align(egui::RectAlign::BOTTOM_START)
+        .layout(egui::Layout::top_down(egui::Align::Min))
+        .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+        .show(|ui| {
+            ui.set_min_width(320.0);
+            ui.spacing_mut().slider_width = 300.0;
+            let mut hex = color_to_hex(*color);
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut hex)
+                        .desired_width(80.0)
+                        .font(egui::TextStyle::Monospace),
+                )
+                .changed()
+            {
+                *color = color_from_hex(&hex, *color);
+            }
+            egui::color_picker::color_picker_color32(ui, color, egui::color_picker::Alpha::Opaque);
+        });
+
+    button_response.on_hover_text(color_to_hex(*color))
+}
+
 /// A color preview button with a fixed 1px black border.
 fn color_button_with_black_border(ui: &mut egui::Ui, color: Color32) -> egui::Response {
     let size = egui::vec2(28.0, 28.0);
@@ -419,6 +812,47 @@ pub fn color_from_hex(hex: &str, fallback: Color32) -> Color32 {
     }
 }
 
+mod color32_hex_array {
+    use egui::Color32;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::color_from_hex;
+    use super::color_to_hex;
+    use super::TerminalPalette;
+
+    pub fn serialize<S: Serializer>(
+        colors: &[Color32; 8],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().map(|c| color_to_hex(*c)))
+    }
+
+    pub fn normal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Color32; 8], D::Error> {
+        deserialize(deserializer, TerminalPalette::default().normal)
+    }
+
+    pub fn bright<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Color32; 8], D::Error> {
+        deserialize(deserializer, TerminalPalette::default().bright)
+    }
+
+    pub fn dim<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Color32; 8], D::Error> {
+        deserialize(deserializer, TerminalPalette::default().dim)
+    }
+
+    /// Missing and unparsable entries keep the `defaults` entry.
+    fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        defaults: [Color32; 8],
+    ) -> Result<[Color32; 8], D::Error> {
+        let hex = Vec::<String>::deserialize(deserializer)?;
+        let mut colors = defaults;
+        for (color, hex) in colors.iter_mut().zip(&hex) {
+            *color = color_from_hex(hex, *color);
+        }
+        Ok(colors)
+    }
+}
+
 mod color32_hex {
     use egui::Color32;
     use serde::{Deserialize, Deserializer, Serializer};
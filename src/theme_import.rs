@@ -0,0 +1,700 @@
+use crate::theme::{color_from_hex, AppTheme};
+use egui::Color32;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Supported terminal color scheme formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeFormat {
+    AlacrittyToml,
+    AlacrittyYaml,
+    /// iTerm2 `.itermcolors` (XML property list).
+    ITerm2,
+    /// A Windows Terminal scheme object, or a settings file with `schemes`.
+    WindowsTerminal,
+    Base16,
+}
+
+impl SchemeFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SchemeFormat::AlacrittyToml => "Alacritty (TOML)",
+            SchemeFormat::AlacrittyYaml => "Alacritty (YAML)",
+            SchemeFormat::ITerm2 => "iTerm2",
+            SchemeFormat::WindowsTerminal => "Windows Terminal",
+            SchemeFormat::Base16 => "base16",
+        }
+    }
+}
+
+/// A color of `AppTheme` that a scheme can set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSlot {
+    Foreground,
+    Background,
+    DimForeground,
+    Cursor,
+    CursorText,
+    Selection,
+    Normal(usize),
+    Bright(usize),
+    Dim(usize),
+}
+
+/// The colors read from a scheme file, ready to preview and apply.
+#[derive(Debug, Clone)]
+pub struct ImportedTheme {
+    pub name: String,
+    pub format: SchemeFormat,
+    pub colors: Vec<(ColorSlot, Color32)>,
+    /// Entries of the file that have no place in `AppTheme` or could not be
+    /// read, e.g. "colors.search.matches.foreground = #000000".
+    pub unknown: Vec<String>,
+}
+
+impl ImportedTheme {
+    /// Overwrite the imported colors of `theme`, keeping everything else. Dim
+    /// colors are derived from the new normal colors unless the scheme has
+    /// its own.
+    pub fn apply_to(&self, theme: &mut AppTheme) {
+        let palette = &mut theme.terminal_palette;
+        for &(slot, color) in &self.colors {
+            match slot {
+                ColorSlot::Foreground => theme.terminal_fg = color,
+                ColorSlot::Background => theme.app_bg = color,
+                ColorSlot::DimForeground => palette.dim_foreground = color,
+                ColorSlot::Cursor => palette.cursor = color,
+                ColorSlot::CursorText => palette.cursor_text = color,
+                ColorSlot::Selection => palette.selection = color,
+                ColorSlot::Normal(i) => palette.normal[i] = color,
+                ColorSlot::Bright(i) => palette.bright[i] = color,
+                ColorSlot::Dim(i) => palette.dim[i] = color,
+            }
+        }
+        let has_dim = self
+            .colors
+            .iter()
+            .any(|(slot, _)| matches!(slot, ColorSlot::Dim(_) | ColorSlot::DimForeground));
+        if !has_dim {
+            palette.derive_dim(theme.terminal_fg);
+        }
+    }
+}
+
+/// Guess the format of `path` from its extension (and, for YAML, content) and
+/// import it.
+pub fn import_file(path: &Path) -> Result<ImportedTheme, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let format = match extension.as_str() {
+        "toml" => SchemeFormat::AlacrittyToml,
+        "itermcolors" | "plist" => SchemeFormat::ITerm2,
+        "json" => SchemeFormat::WindowsTerminal,
+        "yml" | "yaml" => {
+            let is_base16 = flatten_yaml(&text)
+                .iter()
+                .any(|(key, _)| base16_index(key).is_some());
+            if is_base16 {
+                SchemeFormat::Base16
+            } else {
+                SchemeFormat::AlacrittyYaml
+            }
+        }
+        _ => return Err(format!("Unsupported color scheme file: .{}", extension)),
+    };
+    let fallback_name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    import_str(format, &text, &fallback_name)
+}
+
+/// Import `text` in the given format. `fallback_name` is used when the scheme
+/// does not name itself.
+pub fn import_str(
+    format: SchemeFormat,
+    text: &str,
+    fallback_name: &str,
+) -> Result<ImportedTheme, String> {
+    let mut imported = ImportedTheme {
+        name: fallback_name.to_string(),
+        format,
+        colors: Vec::new(),
+        unknown: Vec::new(),
+    };
+    match format {
+        SchemeFormat::AlacrittyToml => import_alacritty(&mut imported, flatten_toml(text)),
+        SchemeFormat::AlacrittyYaml => import_alacritty(&mut imported, flatten_yaml(text)),
+        SchemeFormat::Base16 => import_base16(&mut imported, flatten_yaml(text)),
+        SchemeFormat::ITerm2 => import_iterm2(&mut imported, flatten_plist(text)?),
+        SchemeFormat::WindowsTerminal => {
+            let value: serde_json::Value =
+                serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+            import_windows_terminal(&mut imported, value)?
+        }
+    }
+    if imported.colors.is_empty() {
+        return Err(format!("No {} colors found", format.label()));
+    }
+    Ok(imported)
+}
+
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+fn ansi_index(name: &str) -> Option<usize> {
+    let name = name.to_ascii_lowercase();
+    // Windows Terminal calls magenta "purple".
+    let name = if name == "purple" { "magenta" } else { &name };
+    ANSI_NAMES.iter().position(|n| *n == name)
+}
+
+/// Parse "#rrggbb", "0xrrggbb" or bare "rrggbb".
+fn parse_color(value: &str) -> Option<Color32> {
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(color_from_hex(&format!("#{}", hex), Color32::BLACK))
+}
+
+impl ImportedTheme {
+    /// Record `value` for `slot`, or report the entry if it is not a color.
+    fn set(&mut self, key: &str, slot: ColorSlot, value: &str) {
+        match parse_color(value) {
+            Some(color) => self.colors.push((slot, color)),
+            None => self.unknown.push(format!("{} = {}", key, value)),
+        }
+    }
+}
+
+fn import_alacritty(imported: &mut ImportedTheme, entries: Vec<(String, String)>) {
+    for (key, value) in entries {
+        let slot = key.strip_prefix("colors.").and_then(|rest| match rest {
+            "primary.foreground" => Some(ColorSlot::Foreground),
+            "primary.background" => Some(ColorSlot::Background),
+            "primary.dim_foreground" => Some(ColorSlot::DimForeground),
+            "cursor.cursor" => Some(ColorSlot::Cursor),
+            "cursor.text" => Some(ColorSlot::CursorText),
+            "selection.background" => Some(ColorSlot::Selection),
+            _ => {
+                let (group, name) = rest.split_once('.')?;
+                let i = ansi_index(name)?;
+                match group {
+                    "normal" => Some(ColorSlot::Normal(i)),
+                    "bright" => Some(ColorSlot::Bright(i)),
+                    "dim" => Some(ColorSlot::Dim(i)),
+                    _ => None,
+                }
+            }
+        });
+        match slot {
+            Some(slot) => imported.set(&key, slot, &value),
+            None => imported.unknown.push(format!("{} = {}", key, value)),
+        }
+    }
+}
+
+/// Index 0-15 of a base16 key like "base0A" or "palette.base0a".
+fn base16_index(key: &str) -> Option<usize> {
+    let key = key.strip_prefix("palette.").unwrap_or(key);
+    let digit = key.strip_prefix("base0")?;
+    if digit.len() != 1 {
+        return None;
+    }
+    usize::from_str_radix(digit, 16).ok()
+}
+
+/// Standard base16 terminal mapping (as in base16-shell).
+fn import_base16(imported: &mut ImportedTheme, entries: Vec<(String, String)>) {
+    const METADATA: [&str; 7] = [
+        "scheme",
+        "name",
+        "author",
+        "slug",
+        "system",
+        "variant",
+        "description",
+    ];
+    let mut base = [None; 16];
+    for (key, value) in entries {
+        if let Some(i) = base16_index(&key) {
+            match parse_color(&value) {
+                Some(color) => base[i] = Some(color),
+                None => imported.unknown.push(format!("{} = {}", key, value)),
+            }
+        } else if METADATA.contains(&key.as_str()) {
+            if key == "scheme" || key == "name" {
+                imported.name = value;
+            }
+        } else {
+            imported.unknown.push(format!("{} = {}", key, value));
+        }
+    }
+
+    // ANSI color -> base16 slot, for normal and bright.
+    const NORMAL: [usize; 8] = [0x0, 0x8, 0xB, 0xA, 0xD, 0xE, 0xC, 0x5];
+    const BRIGHT: [usize; 8] = [0x3, 0x8, 0xB, 0xA, 0xD, 0xE, 0xC, 0x7];
+    let mut push = |slot, index: usize| {
+        if let Some(color) = base[index] {
+            imported.colors.push((slot, color));
+        }
+    };
+    push(ColorSlot::Background, 0x0);
+    push(ColorSlot::Foreground, 0x5);
+    push(ColorSlot::Cursor, 0x5);
+    push(ColorSlot::CursorText, 0x0);
+    push(ColorSlot::Selection, 0x2);
+    for i in 0..8 {
+        push(ColorSlot::Normal(i), NORMAL[i]);
+        push(ColorSlot::Bright(i), BRIGHT[i]);
+    }
+}
+
+fn import_iterm2(imported: &mut ImportedTheme, entries: Vec<(String, String)>) {
+    // "Ansi 1 Color.Red Component" -> components of "Ansi 1 Color".
+    let mut colors: BTreeMap<String, [Option<f32>; 3]> = BTreeMap::new();
+    for (key, value) in entries {
+        let component = key.rsplit_once('.').and_then(|(color, component)| {
+            let index = match component {
+                "Red Component" => 0,
+                "Green Component" => 1,
+                "Blue Component" => 2,
+                "Alpha Component" | "Color Space" => return Some(None),
+                _ => return None,
+            };
+            Some(Some((color.to_string(), index)))
+        });
+        match component {
+            Some(Some((color, index))) => match value.parse::<f32>() {
+                Ok(v) => colors.entry(color).or_default()[index] = Some(v),
+                Err(_) => imported.unknown.push(format!("{} = {}", key, value)),
+            },
+            Some(None) => {}
+            None => imported.unknown.push(format!("{} = {}", key, value)),
+        }
+    }
+
+    for (name, components) in colors {
+        let slot = match name.as_str() {
+            "Foreground Color" => Some(ColorSlot::Foreground),
+            "Background Color" => Some(ColorSlot::Background),
+            "Cursor Color" => Some(ColorSlot::Cursor),
+            "Cursor Text Color" => Some(ColorSlot::CursorText),
+            "Selection Color" => Some(ColorSlot::Selection),
+            _ => name
+                .strip_prefix("Ansi ")
+                .and_then(|rest| rest.strip_suffix(" Color"))
+                .and_then(|n| n.parse::<usize>().ok())
+                .and_then(|n| match n {
+                    0..=7 => Some(ColorSlot::Normal(n)),
+                    8..=15 => Some(ColorSlot::Bright(n - 8)),
+                    _ => None,
+                }),
+        };
+        let channel = |c: Option<f32>| c.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        match (
+            slot,
+            channel(components[0]),
+            channel(components[1]),
+            channel(components[2]),
+        ) {
+            (Some(slot), Some(r), Some(g), Some(b)) => {
+                imported.colors.push((slot, Color32::from_rgb(r, g, b)))
+            }
+            (Some(_), ..) => imported.unknown.push(format!("{} (incomplete)", name)),
+            (None, ..) => imported.unknown.push(name),
+        }
+    }
+}
+
+fn import_windows_terminal(
+    imported: &mut ImportedTheme,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    // A whole settings.json: take its first scheme.
+    let scheme = match value.get("schemes").and_then(|s| s.as_array()) {
+        Some(schemes) => {
+            for other in schemes.iter().skip(1) {
+                let name = other.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+                imported
+                    .unknown
+                    .push(format!("scheme \"{}\" (only the first is imported)", name));
+            }
+            schemes.first().cloned().ok_or("The file has no schemes")?
+        }
+        None => value,
+    };
+    let object = scheme.as_object().ok_or("Expected a JSON object")?;
+
+    for (key, value) in object {
+        let text = match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if key == "name" {
+            imported.name = text;
+            continue;
+        }
+        let slot = match key.as_str() {
+            "foreground" => Some(ColorSlot::Foreground),
+            "background" => Some(ColorSlot::Background),
+            "cursorColor" => Some(ColorSlot::Cursor),
+            "selectionBackground" => Some(ColorSlot::Selection),
+            _ => match key.strip_prefix("bright") {
+                Some(name) => ansi_index(name).map(ColorSlot::Bright),
+                None => ansi_index(key).map(ColorSlot::Normal),
+            },
+        };
+        match slot {
+            Some(slot) => imported.set(key, slot, &text),
+            None => imported.unknown.push(format!("{} = {}", key, text)),
+        }
+    }
+    Ok(())
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Flatten the subset of TOML used by color schemes (tables, `key = value`
+/// and one level of inline tables) into dotted keys.
+fn flatten_toml(text: &str) -> Vec<(String, String)> {
+    let mut table = String::new();
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = strip_comment(line).trim();
+        if line.starts_with('[') {
+            table = line.trim_matches(['[', ']']).trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = join(&table, &unquote(key));
+        let value = value.trim();
+        match value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+            Some(inline) => {
+                for pair in inline.split(',') {
+                    if let Some((k, v)) = pair.split_once('=') {
+                        entries.push((join(&key, &unquote(k)), unquote(v)));
+                    }
+                }
+            }
+            None => entries.push((key, unquote(value))),
+        }
+    }
+    entries
+}
+
+/// Flatten the subset of YAML used by color schemes (nested maps of scalars)
+/// into dotted keys. Anchors on maps are skipped; list items are ignored.
+fn flatten_yaml(text: &str) -> Vec<(String, String)> {
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = strip_comment(line);
+        let content = line.trim();
+        if content.is_empty() || content.starts_with("---") || content.starts_with('-') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let Some((key, value)) = content.split_once(':') else {
+            continue;
+        };
+        while parents.last().is_some_and(|(i, _)| *i >= indent) {
+            parents.pop();
+        }
+        let prefix = parents
+            .iter()
+            .map(|(_, k)| k.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        let key = join(&prefix, &unquote(key));
+        let value = value.trim();
+        if value.is_empty() || value.starts_with('&') {
+            parents.push((indent, unquote(content.split_once(':').unwrap().0)));
+        } else {
+            entries.push((key, unquote(value)));
+        }
+    }
+    entries
+}
+
+/// Flatten an XML property list of nested dicts into dotted keys.
+fn flatten_plist(text: &str) -> Result<Vec<(String, String)>, String> {
+    let malformed = || "Malformed property list".to_string();
+    let mut entries = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut key: Option<String> = None;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        let end = rest[start..].find('>').ok_or_else(malformed)? + start;
+        let tag = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+        match tag {
+            "dict" => path.push(key.take().unwrap_or_default()),
+            "/dict" => {
+                path.pop();
+            }
+            "true/" | "false/" => {
+                if let Some(k) = key.take() {
+                    entries.push((plist_key(&path, &k), tag.trim_end_matches('/').into()));
+                }
+            }
+            "key" | "real" | "integer" | "string" => {
+                let close = format!("</{}>", tag);
+                let len = rest.find(&close).ok_or_else(malformed)?;
+                let content = xml_unescape(rest[..len].trim());
+                rest = &rest[len + close.len()..];
+                if tag == "key" {
+                    key = Some(content);
+                } else if let Some(k) = key.take() {
+                    entries.push((plist_key(&path, &k), content));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+fn plist_key(path: &[String], key: &str) -> String {
+    path.iter()
+        .filter(|p| !p.is_empty())
+        .map(String::as_str)
+        .chain([key])
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(hex: u32) -> Color32 {
+        Color32::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    fn slot(imported: &ImportedTheme, slot: ColorSlot) -> Option<Color32> {
+        imported
+            .colors
+            .iter()
+            .find(|(s, _)| *s == slot)
+            .map(|(_, c)| *c)
+    }
+
+    #[test]
+    fn test_alacritty_toml() {
+        let text = r##"
+# Tomorrow Night
+[colors.primary]
+background = "#1d1f21"
+foreground = '0xc5c8c6'
+
+[colors.cursor]
+text = "CellBackground"
+cursor = "#c5c8c6"
+
+[colors.normal]
+black = "#1d1f21" # comment
+red   = "#cc6666"
+
+[colors.bright]
+magenta = "#b294bb"
+
+[colors.search]
+matches = { foreground = "#000000", background = "#ffffff" }
+"##;
+        let imported = import_str(SchemeFormat::AlacrittyToml, text, "tomorrow").unwrap();
+        assert_eq!(
+            slot(&imported, ColorSlot::Background),
+            Some(color(0x1d1f21))
+        );
+        assert_eq!(
+            slot(&imported, ColorSlot::Foreground),
+            Some(color(0xc5c8c6))
+        );
+        assert_eq!(slot(&imported, ColorSlot::Normal(1)), Some(color(0xcc6666)));
+        assert_eq!(slot(&imported, ColorSlot::Bright(5)), Some(color(0xb294bb)));
+        assert_eq!(
+            imported.unknown,
+            [
+                "colors.cursor.text = CellBackground",
+                "colors.search.matches.foreground = #000000",
+                "colors.search.matches.background = #ffffff",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alacritty_yaml() {
+        let text = "colors:\n  primary:\n    background: '0x282a36'\n  normal:\n    cyan: '0x8be9fd'\n  dim:\n    red: '0x992222'\nfont:\n  size: 12\n";
+        let imported = import_str(SchemeFormat::AlacrittyYaml, text, "dracula").unwrap();
+        assert_eq!(
+            slot(&imported, ColorSlot::Background),
+            Some(color(0x282a36))
+        );
+        assert_eq!(slot(&imported, ColorSlot::Normal(6)), Some(color(0x8be9fd)));
+        assert_eq!(slot(&imported, ColorSlot::Dim(1)), Some(color(0x992222)));
+        assert_eq!(imported.unknown, ["font.size = 12"]);
+    }
+
+    #[test]
+    fn test_base16() {
+        let mut text = String::from("scheme: \"Ocean\"\nauthor: \"Chris Kempson\"\n");
+        for i in 0..16 {
+            text.push_str(&format!("base0{:X}: \"{:02x}{:02x}{:02x}\"\n", i, i, i, i));
+        }
+        let imported = import_str(SchemeFormat::Base16, &text, "file").unwrap();
+        assert_eq!(imported.name, "Ocean");
+        assert!(imported.unknown.is_empty());
+        let gray = |i: u8| Some(Color32::from_rgb(i, i, i));
+        assert_eq!(slot(&imported, ColorSlot::Background), gray(0x0));
+        assert_eq!(slot(&imported, ColorSlot::Foreground), gray(0x5));
+        assert_eq!(slot(&imported, ColorSlot::Normal(1)), gray(0x8));
+        assert_eq!(slot(&imported, ColorSlot::Bright(0)), gray(0x3));
+        assert_eq!(slot(&imported, ColorSlot::Bright(7)), gray(0x7));
+    }
+
+    #[test]
+    fn test_iterm2() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>Ansi 9 Color</key>
+	<dict>
+		<key>Alpha Component</key>
+		<real>1</real>
+		<key>Blue Component</key>
+		<real>0.0</real>
+		<key>Color Space</key>
+		<string>sRGB</string>
+		<key>Green Component</key>
+		<real>0.5</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Background Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>0.2</real>
+		<key>Green Component</key>
+		<real>0.2</real>
+		<key>Red Component</key>
+		<real>0.2</real>
+	</dict>
+	<key>Badge Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>0</real>
+		<key>Green Component</key>
+		<real>0</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+</dict>
+</plist>"#;
+        let imported = import_str(SchemeFormat::ITerm2, text, "scheme").unwrap();
+        assert_eq!(
+            slot(&imported, ColorSlot::Bright(1)),
+            Some(Color32::from_rgb(255, 128, 0))
+        );
+        assert_eq!(
+            slot(&imported, ColorSlot::Background),
+            Some(Color32::from_rgb(51, 51, 51))
+        );
+        assert_eq!(imported.unknown, ["Badge Color"]);
+    }
+
+    #[test]
+    fn test_windows_terminal() {
+        let text = r##"{
+            "name": "Campbell",
+            "foreground": "#CCCCCC",
+            "background": "#0C0C0C",
+            "cursorColor": "#FFFFFF",
+            "purple": "#881798",
+            "brightPurple": "#B4009E",
+            "brightBlack": "#767676"
+        }"##;
+        let imported = import_str(SchemeFormat::WindowsTerminal, text, "file").unwrap();
+        assert_eq!(imported.name, "Campbell");
+        assert_eq!(slot(&imported, ColorSlot::Normal(5)), Some(color(0x881798)));
+        assert_eq!(slot(&imported, ColorSlot::Bright(5)), Some(color(0xb4009e)));
+        assert_eq!(slot(&imported, ColorSlot::Bright(0)), Some(color(0x767676)));
+        assert!(imported.unknown.is_empty());
+
+        let settings = r##"{"schemes": [{"name": "A", "red": "#ff0000"}, {"name": "B"}]}"##;
+        let imported = import_str(SchemeFormat::WindowsTerminal, settings, "file").unwrap();
+        assert_eq!(imported.name, "A");
+        assert_eq!(
+            imported.unknown,
+            ["scheme \"B\" (only the first is imported)"]
+        );
+    }
+
+    #[test]
+    fn test_apply_derives_missing_dim_colors() {
+        let text = "[colors.normal]\nred = \"#cc6666\"\n";
+        let imported = import_str(SchemeFormat::AlacrittyToml, text, "x").unwrap();
+        let mut theme = AppTheme::default();
+        imported.apply_to(&mut theme);
+        assert_eq!(theme.terminal_palette.normal[1], color(0xcc6666));
+        assert_eq!(
+            theme.terminal_palette.dim[1],
+            Color32::from_rgb(0x87, 0x43, 0x43)
+        );
+
+        assert!(import_str(SchemeFormat::AlacrittyToml, "[font]\nsize = 3\n", "x").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::config::settings::AgentConfig;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// How long `--version` may take before the check gives up on it.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Checks of the agent commands for the Agents settings window: the binary
+/// each one resolves to, what it prints for `--version` and why starting it
+/// would fail. Version checks run in the background, once per binary.
+#[derive(Default)]
+pub struct AgentDiagnostics {
+    versions: HashMap<PathBuf, VersionCheck>,
+}
+
+enum VersionCheck {
+    Running(Receiver<Result<String, String>>),
+    Done(Result<String, String>),
+}
+
+impl AgentDiagnostics {
+    /// Forget all results so every agent is checked again.
+    pub fn refresh(&mut self) {
+        self.versions.clear();
+        crate::executables::clear_cache();
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, agent: &AgentConfig) {
+        let program = agent.cmd.split_whitespace().next().unwrap_or_default();
+        if program.is_empty() {
+            problem(
+                ui,
+                "No command set; tabs of this agent open a shell instead.",
+            );
+            return;
+        }
+        let Some(path) = crate::executables::resolve(program) else {
+            problem(
+                ui,
+                &format!(
+                    "\"{}\" was not found on PATH or is not executable; tabs of this \
+                     agent open a shell instead.",
+                    program
+                ),
+            );
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Binary:");
+            ui.label(egui::RichText::new(path.display().to_string()).monospace());
+        });
+
+        let check = self
+            .versions
+            .entry(path.clone())
+            .or_insert_with(|| check_version(ui.ctx().clone(), path));
+        if let VersionCheck::Running(receiver) = check {
+            if let Ok(result) = receiver.try_recv() {
+                *check = VersionCheck::Done(result);
+            }
+        }
+        match check {
+            VersionCheck::Running(_) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Checking version…");
+                });
+            }
+            VersionCheck::Done(Ok(version)) => {
+                ui.horizontal(|ui| {
+                    ui.label("Version:");
+                    ui.label(egui::RichText::new(version.as_str()).monospace());
+                });
+            }
+            VersionCheck::Done(Err(error)) => {
+                problem(ui, &format!("\"{} --version\" failed: {}", program, error));
+            }
+        }
+
+        if !agent.enabled {
+            ui.label(egui::RichText::new("Disabled, so it is not offered in the sidebar.").weak());
+        }
+    }
+}
+
+fn problem(ui: &mut egui::Ui, text: &str) {
+    let color = ui.visuals().warn_fg_color;
+    ui.label(egui::RichText::new(format!("⚠ {}", text)).color(color));
+}
+
+/// Run `path --version` on a background thread and report its first line.
+fn check_version(ctx: egui::Context, path: PathBuf) -> VersionCheck {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(run_version(&path));
+        ctx.request_repaint();
+    });
+    VersionCheck::Running(receiver)
+}
+
+/// First line `path --version` prints. Killed if it has not exited within
+/// [`VERSION_TIMEOUT`], e.g. because it waits for a login.
+fn run_version(path: &Path) -> Result<String, String> {
+    let mut child = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let deadline = Instant::now() + VERSION_TIMEOUT;
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("no answer after {} s", VERSION_TIMEOUT.as_secs()));
+            }
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    };
+
+    // Processes it left behind may keep the pipes open; take what is there
+    // by the deadline.
+    let mut output = stdout
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .unwrap_or_default();
+    if output.is_empty() {
+        output = stderr
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .unwrap_or_default();
+    }
+    let text = String::from_utf8_lossy(&output);
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    if status.success() {
+        Ok(line)
+    } else {
+        Err(format!("{} {}", status, line).trim_end().to_string())
+    }
+}
+
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        let _ = sender.send(output);
+    });
+    receiver
+}
@@ -0,0 +1,255 @@
+use crate::git_branches::{checkout_branch, create_branch, fuzzy_score, list_branches, BranchInfo};
+use crate::git_status::compute_git_status;
+use std::path::PathBuf;
+
+/// A branch operation that changes the working tree.
+enum BranchOperation {
+    Checkout(BranchInfo),
+    Create { name: String, base: Option<String> },
+}
+
+impl BranchOperation {
+    /// Creating a branch from HEAD never touches the working tree, so it is the
+    /// only operation that is safe to run without asking about local changes.
+    fn touches_worktree(&self) -> bool {
+        !matches!(self, BranchOperation::Create { base: None, .. })
+    }
+}
+
+/// State of the "Branches" window opened from a group's branch label: lists
+/// local and remote branches with fuzzy filtering, checks them out and creates
+/// new branches from HEAD or a chosen base.
+#[derive(Default)]
+pub struct BranchSwitcher {
+    pub open: bool,
+    was_open: bool,
+    group_name: String,
+    path: PathBuf,
+    branches: Vec<BranchInfo>,
+    filter: String,
+    new_branch_name: String,
+    /// Base revision for a new branch; `None` means HEAD.
+    new_branch_base: Option<String>,
+    /// Operation waiting for confirmation because the working tree has this
+    /// many files with uncommitted changes.
+    pending: Option<(BranchOperation, usize)>,
+    error: Option<String>,
+}
+
+impl BranchSwitcher {
+    /// Open the window for the repository at `path`.
+    pub fn open_for(&mut self, group_name: String, path: PathBuf) {
+        self.group_name = group_name;
+        self.path = path;
+        self.filter.clear();
+        self.new_branch_name.clear();
+        self.new_branch_base = None;
+        self.pending = None;
+        self.error = None;
+        self.reload();
+        self.open = true;
+    }
+
+    fn reload(&mut self) {
+        match list_branches(&self.path) {
+            Ok(branches) => self.branches = branches,
+            Err(e) => {
+                self.branches.clear();
+                self.error = Some(e.message().to_string());
+            }
+        }
+    }
+
+    /// Run `op`, or park it for confirmation when it would switch the working
+    /// tree while there are uncommitted changes. Returns `true` if the
+    /// repository was changed.
+    fn request(&mut self, op: BranchOperation) -> bool {
+        if op.touches_worktree() {
+            let dirty = compute_git_status(&self.path)
+                .map(|s| s.staged + s.modified + s.conflicted)
+                .unwrap_or(0);
+            if dirty > 0 {
+                self.pending = Some((op, dirty));
+                return false;
+            }
+        }
+        self.run(op)
+    }
+
+    fn run(&mut self, op: BranchOperation) -> bool {
+        let result = match &op {
+            BranchOperation::Checkout(branch) => checkout_branch(&self.path, branch),
+            BranchOperation::Create { name, base } => {
+                create_branch(&self.path, name, base.as_deref(), true)
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.error = None;
+                self.open = false;
+                true
+            }
+            Err(e) => {
+                self.error = Some(e.message().to_string());
+                self.reload();
+                false
+            }
+        }
+    }
+
+    /// Draw the window. Returns the repository path when a branch operation
+    /// changed it, so the caller can refresh its Git status right away.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<PathBuf> {
+        let mut changed = false;
+        let mut requested: Option<BranchOperation> = None;
+        let mut confirm = false;
+        let mut cancel_pending = false;
+        let mut open = self.open;
+
+        let window_id = egui::Id::new("branch_switcher_window");
+        let filter_id = egui::Id::new("branch_switcher_filter");
+        if self.open && !self.was_open {
+            ctx.memory_mut(|m| m.request_focus(filter_id));
+        }
+        self.was_open = self.open;
+
+        egui::Window::new(format!("Branches — {}", self.group_name))
+            .id(window_id)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Frame::NONE.inner_margin(20.0).show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.filter)
+                            .id(filter_id)
+                            .hint_text("Filter branches...")
+                            .desired_width(320.0),
+                    );
+                    ui.add_space(8.0);
+
+                    let mut matches: Vec<(i64, &BranchInfo)> = self
+                        .branches
+                        .iter()
+                        .filter_map(|b| fuzzy_score(&self.filter, &b.name).map(|s| (s, b)))
+                        .collect();
+                    // Stable sort keeps locals before remotes and names in order
+                    // for equal scores.
+                    matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+
+                    egui::ScrollArea::vertical()
+                        .id_salt("branch_switcher_scroll")
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            if matches.is_empty() {
+                                ui.weak("No matching branches");
+                            }
+                            for (_, branch) in &matches {
+                                let label = if branch.is_remote {
+                                    format!("☁ {}", branch.name)
+                                } else {
+                                    branch.name.clone()
+                                };
+                                let response = ui
+                                    .selectable_label(branch.is_head, label)
+                                    .on_hover_cursor(egui::CursorIcon::PointingHand);
+                                if response.clicked() && !branch.is_head {
+                                    requested = Some(BranchOperation::Checkout((*branch).clone()));
+                                }
+                                response.context_menu(|ui| {
+                                    if ui.button("Use as base for new branch").clicked() {
+                                        self.new_branch_base = Some(branch.name.clone());
+                                        ui.close();
+                                    }
+                                });
+                            }
+                        });
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(8.0);
+
+                    ui.label("New branch");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_branch_name)
+                                .hint_text("feature/my-task")
+                                .desired_width(200.0),
+                        );
+                        let can_create = !self.new_branch_name.trim().is_empty();
+                        if ui
+                            .add_enabled(can_create, egui::Button::new("Create"))
+                            .clicked()
+                        {
+                            requested = Some(BranchOperation::Create {
+                                name: self.new_branch_name.trim().to_string(),
+                                base: self.new_branch_base.clone(),
+                            });
+                        }
+                    });
+                    ui.horizontal(|ui| match &self.new_branch_base {
+                        Some(base) => {
+                            ui.label(format!("from {}", base));
+                            if ui.small_button("Use HEAD").clicked() {
+                                self.new_branch_base = None;
+                            }
+                        }
+                        None => {
+                            ui.weak("from HEAD (right-click a branch to change)");
+                        }
+                    });
+
+                    if let Some((_, dirty)) = &self.pending {
+                        ui.add_space(10.0);
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "⚠ {} file(s) have uncommitted changes. Switching keeps them \
+                                 only if they do not conflict with the target branch.",
+                                dirty
+                            ))
+                            .color(egui::Color32::from_rgb(0xff, 0x98, 0x00)),
+                        );
+                        ui.horizontal(|ui| {
+                            if ui.button("Switch anyway").clicked() {
+                                confirm = true;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                cancel_pending = true;
+                            }
+                        });
+                    }
+
+                    if let Some(error) = &self.error {
+                        ui.add_space(10.0);
+                        ui.label(
+                            egui::RichText::new(error)
+                                .color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                        );
+                    }
+
+                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        cancel_pending = true;
+                        self.open = false;
+                    }
+                });
+            });
+
+        if !open {
+            self.open = false;
+        }
+
+        if cancel_pending {
+            self.pending = None;
+        }
+        if confirm {
+            if let Some((op, _)) = self.pending.take() {
+                changed |= self.run(op);
+            }
+        }
+        if let Some(op) = requested {
+            self.pending = None;
+            changed |= self.request(op);
+        }
+
+        changed.then(|| self.path.clone())
+    }
+}
@@ -0,0 +1,313 @@
+use crate::git_commit::{self, ChangeKind, ChangedFile, CommitOptions};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// State of the per-group "Commit" window: a tree of changed files with
+/// checkboxes, a message editor and amend / sign-off options. The commit runs
+/// on a background thread because hooks may take a while.
+#[derive(Default)]
+pub struct CommitDialog {
+    pub open: bool,
+    was_open: bool,
+    group_name: String,
+    path: PathBuf,
+    files: Vec<ChangedFile>,
+    /// Checkbox state, parallel to `files`.
+    selected: Vec<bool>,
+    message: String,
+    amend: bool,
+    sign_off: bool,
+    identity: Option<String>,
+    error: Option<String>,
+    in_flight: Option<mpsc::Receiver<Result<(), String>>>,
+}
+
+impl CommitDialog {
+    /// Open the window for the repository at `path`.
+    pub fn open_for(&mut self, group_name: String, path: PathBuf) {
+        if self.path != path {
+            self.message.clear();
+            self.amend = false;
+        }
+        self.group_name = group_name;
+        self.path = path;
+        self.error = None;
+        self.reload();
+        self.open = true;
+    }
+
+    /// Re-read the changed files. Already staged files start out checked; if
+    /// nothing is staged, everything is.
+    fn reload(&mut self) {
+        self.identity = git_commit::identity(&self.path);
+        match git_commit::list_changes(&self.path) {
+            Ok(files) => {
+                let any_staged = files.iter().any(|f| f.staged);
+                self.selected = files.iter().map(|f| f.staged || !any_staged).collect();
+                self.files = files;
+            }
+            Err(e) => {
+                self.files.clear();
+                self.selected.clear();
+                self.error = Some(e.message().to_string());
+            }
+        }
+    }
+
+    fn start_commit(&mut self, ctx: &egui::Context) {
+        let selected: Vec<String> = self
+            .files
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(file, _)| file.path.clone())
+            .collect();
+        let options = CommitOptions {
+            message: self.message.clone(),
+            amend: self.amend,
+            sign_off: self.sign_off,
+        };
+        let path = self.path.clone();
+        let files = self.files.clone();
+        let ctx = ctx.clone();
+        let (tx, rx) = mpsc::channel();
+
+        self.error = None;
+        self.in_flight = Some(rx);
+        std::thread::spawn(move || {
+            let result = git_commit::commit(&path, &files, &selected, &options)
+                .map(|_| ())
+                .map_err(|e| e.to_string());
+            let _ = tx.send(result);
+            ctx.request_repaint();
+        });
+    }
+
+    /// Draw the window. Returns the repository path after a successful commit
+    /// so the caller can refresh its Git status right away.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<PathBuf> {
+        let mut committed = false;
+        if let Some(rx) = &self.in_flight {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.in_flight = None;
+                    match result {
+                        Ok(()) => {
+                            committed = true;
+                            self.message.clear();
+                            self.amend = false;
+                            self.open = false;
+                        }
+                        Err(e) => self.error = Some(e),
+                    }
+                    self.reload();
+                }
+                Err(mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                }
+                Err(mpsc::TryRecvError::Disconnected) => self.in_flight = None,
+            }
+        }
+
+        let message_id = egui::Id::new("commit_dialog_message");
+        if self.open && !self.was_open {
+            ctx.memory_mut(|m| m.request_focus(message_id));
+        }
+        self.was_open = self.open;
+
+        let mut open = self.open;
+        let mut do_commit = false;
+        let busy = self.in_flight.is_some();
+
+        egui::Window::new(format!("Commit — {}", self.group_name))
+            .id(egui::Id::new("commit_dialog_window"))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Frame::NONE.inner_margin(20.0).show(ui, |ui| {
+                    match &self.identity {
+                        Some(identity) => {
+                            ui.weak(format!("Committing as {}", identity));
+                        }
+                        None => {
+                            ui.label(
+                                egui::RichText::new(
+                                    "⚠ user.name / user.email are not configured for this repository",
+                                )
+                                .color(egui::Color32::from_rgb(0xff, 0x98, 0x00)),
+                            );
+                        }
+                    }
+                    ui.add_space(8.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Changes ({})", self.files.len()));
+                        if ui.small_button("All").clicked() {
+                            self.selected.iter_mut().for_each(|s| *s = true);
+                        }
+                        if ui.small_button("None").clicked() {
+                            self.selected.iter_mut().for_each(|s| *s = false);
+                        }
+                        if ui
+                            .small_button("⟳")
+                            .on_hover_text("Reload changed files")
+                            .clicked()
+                        {
+                            self.error = None;
+                            self.reload();
+                        }
+                    });
+
+                    egui::ScrollArea::vertical()
+                        .id_salt("commit_dialog_files")
+                        .max_height(250.0)
+                        .show(ui, |ui| {
+                            ui.set_min_width(360.0);
+                            if self.files.is_empty() {
+                                ui.weak("No changes");
+                            }
+                            let all: Vec<usize> = (0..self.files.len()).collect();
+                            show_tree(ui, &self.files, &mut self.selected, &all, 0);
+                        });
+
+                    ui.add_space(8.0);
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.message)
+                            .id(message_id)
+                            .hint_text("Commit message")
+                            .font(egui::TextStyle::Monospace)
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY),
+                    );
+
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut self.amend, "Amend last commit").changed()
+                            && self.amend
+                            && self.message.trim().is_empty()
+                        {
+                            self.message =
+                                git_commit::head_message(&self.path).unwrap_or_default();
+                        }
+                        ui.checkbox(&mut self.sign_off, "Sign off");
+                    });
+
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        let any_selected = self.selected.iter().any(|s| *s);
+                        let can_commit = !busy
+                            && !self.message.trim().is_empty()
+                            && (any_selected || self.amend);
+                        let label = if self.amend { "Amend" } else { "Commit" };
+                        if ui
+                            .add_enabled(can_commit, egui::Button::new(label))
+                            .clicked()
+                        {
+                            do_commit = true;
+                        }
+                        if busy {
+                            ui.spinner();
+                            ui.label("Running hooks and committing...");
+                        }
+                    });
+
+                    if let Some(error) = &self.error {
+                        ui.add_space(10.0);
+                        egui::ScrollArea::vertical()
+                            .id_salt("commit_dialog_error")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(error)
+                                        .monospace()
+                                        .color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                                );
+                            });
+                    }
+
+                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        self.open = false;
+                    }
+                });
+            });
+
+        if !open {
+            self.open = false;
+        }
+        if do_commit {
+            self.start_commit(ctx);
+        }
+
+        committed.then(|| self.path.clone())
+    }
+}
+
+/// Draw `indices` (into `files`) as a directory tree below path depth
+/// `depth`. Directory checkboxes toggle every file beneath them and show an
+/// indeterminate state when only some are selected.
+fn show_tree(
+    ui: &mut egui::Ui,
+    files: &[ChangedFile],
+    selected: &mut [bool],
+    indices: &[usize],
+    depth: usize,
+) {
+    let mut dirs: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut leaves = Vec::new();
+    for &i in indices {
+        let mut parts = files[i].path.split('/').skip(depth);
+        let first = parts.next().unwrap_or_default();
+        if parts.next().is_some() {
+            dirs.entry(first).or_default().push(i);
+        } else {
+            leaves.push(i);
+        }
+    }
+
+    for (name, children) in dirs {
+        let dir_path = files[children[0]]
+            .path
+            .split('/')
+            .take(depth + 1)
+            .collect::<Vec<_>>()
+            .join("/");
+        let all = children.iter().all(|&i| selected[i]);
+        let any = children.iter().any(|&i| selected[i]);
+        let id = ui.make_persistent_id(("commit_tree", dir_path));
+
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+            .show_header(ui, |ui| {
+                let mut value = all;
+                if ui
+                    .add(
+                        egui::Checkbox::new(&mut value, format!("📁 {}", name))
+                            .indeterminate(any && !all),
+                    )
+                    .changed()
+                {
+                    for &i in &children {
+                        selected[i] = value;
+                    }
+                }
+            })
+            .body(|ui| show_tree(ui, files, selected, &children, depth + 1));
+    }
+
+    for i in leaves {
+        let file = &files[i];
+        let name = file.path.rsplit('/').next().unwrap_or(&file.path);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut selected[i], name)
+                .on_hover_text(&file.path);
+            ui.label(
+                egui::RichText::new(file.kind.letter())
+                    .monospace()
+                    .color(file.kind.color()),
+            );
+            if file.kind == ChangeKind::Conflicted {
+                ui.weak("conflict");
+            }
+        });
+    }
+}
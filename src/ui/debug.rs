@@ -6,6 +6,7 @@ pub fn show_debug_panel(
     show_fps: bool,
     show_terminal_lines: bool,
     tab_manager: &mut crate::terminal::TabManager,
+    startup_time: Option<std::time::Duration>,
     theme: &AppTheme,
 ) {
     if !show_fps && !show_terminal_lines {
@@ -64,9 +65,12 @@ pub fn show_debug_panel(
                         debug_parts.push(format!("FPS: {:.1}", fps));
                     }
 
-                    if !debug_parts.is_empty() {
-                        ui.label(format!("📊 {}", debug_parts.join(" | ")));
+                    if let Some(startup_time) = startup_time {
+                        debug_parts.push(format!("Startup: {} ms", startup_time.as_millis()));
                     }
+                    debug_parts.push(format!("PTYs: {}", tab_manager.pty_count()));
+
+                    ui.label(format!("📊 {}", debug_parts.join(" | ")));
                 });
             });
         });
@@ -1,7 +1,17 @@
+pub mod agent_diagnostics;
+pub mod branches;
+pub mod commit;
 pub mod debug;
 pub mod panels;
+pub mod ports;
+pub mod processes;
+pub mod sparkline;
+pub mod toasts;
 pub mod windows;
 
 pub use debug::show_debug_panel;
-pub use panels::{show_central_panel, show_left_panel, show_search_panel, GroupAction, PanelActions};
-pub use windows::{WindowActions, WindowManager};
+pub use panels::{
+    show_central_panel, show_left_panel, show_search_panel, window_label, GroupAction,
+    PanelActions,
+};
+pub use windows::{CloseTarget, PendingClose, WindowActions, WindowManager};
@@ -1,7 +1,14 @@
 use crate::config::settings::{AgentConfig, MAX_AGENTS};
+use crate::constants::{COMPACT_SIDEBAR_WIDTH, MAIN_WINDOW, MAX_SIDEBAR_WIDTH, MIN_SIDEBAR_WIDTH};
 use crate::menu::apply_menu_style;
-use crate::git_status::GitStatusCache;
-use crate::system_monitor::{format_kb, SystemMonitor};
+use crate::resource_limits::{LimitUsage, ResourceLimits};
+use crate::git_fetch::{format_ago, FetchState, GitFetcher};
+use crate::git_status::{GitStatus, GitStatusCache, GitSyncStatus};
+use crate::system_monitor::{
+    format_duration, format_kb, SystemMonitor, UsageAlert, UsageHistory, UsageLimits,
+};
+use crate::ui::sparkline::sparkline;
+use crate::terminal::spawn::SpawnError;
 use crate::terminal::{TabManager, TerminalBackendExt};
 use crate::theme::AppTheme;
 use egui::text::{LayoutJob, TextFormat};
@@ -18,21 +25,184 @@ fn paste_from_clipboard() -> Option<String> {
         .ok()
 }
 
-/// Severity color for a tab's memory usage, applied to the inline memory text.
-/// Red above 500 MB, yellow above 200 MB, otherwise a placeholder that inherits
-/// the tab's normal (state-based) text color.
-fn mem_color_for(mem_kb: u64) -> egui::Color32 {
-    if mem_kb > 500 * 1024 {
+/// Severity color for a tab's memory or CPU usage, applied to the inline
+/// usage text. Red above the configured alert limit, yellow above half of it, otherwise a
+/// placeholder that inherits the tab's normal (state-based) text color. A
+/// disabled limit (0) never tints.
+fn usage_color_for(value: f32, limit: f32) -> egui::Color32 {
+    if limit <= 0.0 {
+        egui::Color32::PLACEHOLDER
+    } else if value > limit {
         egui::Color32::from_rgb(0xff, 0x55, 0x55)
-    } else if mem_kb > 200 * 1024 {
+    } else if value > limit / 2.0 {
         egui::Color32::from_rgb(0xff, 0xd6, 0x33)
     } else {
         egui::Color32::PLACEHOLDER
     }
 }
 
+/// Hover tooltip for a tab: memory and CPU sparklines of its process tree
+/// over the last couple of minutes, plus the active alert if any.
+fn tab_usage_tooltip(
+    ui: &mut egui::Ui,
+    history: Option<&UsageHistory>,
+    limits: UsageLimits,
+    alert: Option<UsageAlert>,
+) {
+    if let Some(alert) = alert {
+        let text = match alert {
+            UsageAlert::Memory => format!(
+                "⚠ Memory above {} for over {}",
+                format_kb(limits.memory_kb),
+                format_duration(limits.sustained.as_secs())
+            ),
+            UsageAlert::Cpu => format!(
+                "⚠ CPU above {:.0}% for over {}",
+                limits.cpu_percent,
+                format_duration(limits.sustained.as_secs())
+            ),
+        };
+        ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(0xff, 0x55, 0x55)));
+        ui.add_space(4.0);
+    }
+
+    let Some(history) = history else {
+        return;
+    };
+    let memory: Vec<f32> = history.samples().map(|s| s.memory_kb as f32).collect();
+    let cpu: Vec<f32> = history.samples().map(|s| s.cpu_percent).collect();
+    let size = egui::vec2(180.0, 32.0);
+    let limit = |l: f32| (l > 0.0).then_some(l);
+
+    ui.label(format!(
+        "Memory: {}",
+        format_kb(history.samples().last().map_or(0, |s| s.memory_kb))
+    ));
+    sparkline(
+        ui,
+        &memory,
+        0.0,
+        limit(limits.memory_kb as f32),
+        egui::Color32::from_rgb(0x42, 0xa5, 0xf5),
+        size,
+    );
+    ui.label(format!("CPU: {:.0}%", cpu.last().copied().unwrap_or(0.0)));
+    sparkline(
+        ui,
+        &cpu,
+        100.0,
+        limit(limits.cpu_percent),
+        egui::Color32::from_rgb(0x4c, 0xaf, 0x50),
+        size,
+    );
+}
+
+/// Usage of an agent tab against its resource limits, one line per limit.
+fn tab_limits_tooltip(ui: &mut egui::Ui, limits: ResourceLimits, usage: LimitUsage) {
+    ui.add_space(4.0);
+    ui.label(egui::RichText::new("Limits").strong());
+    if limits.memory_kb > 0 {
+        ui.label(format!(
+            "Memory: {} / {}",
+            format_kb(usage.memory_kb),
+            format_kb(limits.memory_kb)
+        ));
+    }
+    if limits.cpu_time_secs > 0 {
+        ui.label(format!(
+            "CPU time: {} / {}",
+            format_duration(usage.cpu_time_secs),
+            format_duration(limits.cpu_time_secs)
+        ));
+    }
+    if limits.open_files > 0 {
+        ui.label(format!("Open files: {} / {}", usage.open_files, limits.open_files));
+    }
+}
+
+/// Rich hover tooltip for a group's Git status: branch/upstream, ahead/behind,
+/// per-category file counts, stash entries, any operation in progress and the
+/// outcome of the last background fetch.
+fn git_status_tooltip(
+    ui: &mut egui::Ui,
+    status: &GitStatus,
+    auto_fetch: bool,
+    fetch_state: Option<&FetchState>,
+) {
+    let head = match (&status.branch, &status.detached_commit) {
+        (Some(branch), _) => format!("🔀 {}", branch),
+        (None, Some(commit)) => format!("🔀 detached at {}", commit),
+        (None, None) => "🔀 detached HEAD".to_string(),
+    };
+    ui.label(egui::RichText::new(head).strong());
+    match &status.upstream {
+        Some(upstream) => ui.label(format!("Tracking {}", upstream)),
+        None => ui.weak("No upstream branch"),
+    };
+
+    ui.add_space(4.0);
+    ui.label(
+        egui::RichText::new(format!("{} {}", status.sync_status.icon(), status.summary()))
+            .color(status.sync_status.color()),
+    );
+    if let Some(operation) = status.operation {
+        ui.label(egui::RichText::new(operation.label()).color(GitSyncStatus::Conflicts.color()));
+    }
+
+    ui.add_space(4.0);
+    egui::Grid::new("git_status_tooltip_grid")
+        .num_columns(2)
+        .spacing([16.0, 2.0])
+        .show(ui, |ui| {
+            let rows = [
+                ("Ahead", status.ahead),
+                ("Behind", status.behind),
+                ("Staged", status.staged),
+                ("Modified", status.modified),
+                ("Untracked", status.untracked),
+                ("Conflicted", status.conflicted),
+                ("Stashes", status.stash_count),
+            ];
+            for (label, count) in rows {
+                ui.label(label);
+                if count > 0 {
+                    ui.label(egui::RichText::new(count.to_string()).strong());
+                } else {
+                    ui.weak("0");
+                }
+                ui.end_row();
+            }
+        });
+
+    if auto_fetch {
+        ui.add_space(4.0);
+        match fetch_state.and_then(|s| s.last_fetch) {
+            Some(time) => ui.label(format!("🔄 Fetched {}", format_ago(time))),
+            None => ui.weak("🔄 Not fetched yet"),
+        };
+        if let Some(error) = fetch_state.and_then(|s| s.last_error.as_ref()) {
+            ui.label(
+                egui::RichText::new(format!("Fetch failed: {}", error))
+                    .color(GitSyncStatus::Conflicts.color()),
+            );
+        }
+    }
+}
+
+/// Emoji offered in the group context menu; any text can be typed instead.
+const GROUP_ICONS: [&str; 8] = ["📁", "🚀", "🧪", "📦", "🔧", "🌐", "📝", "⭐"];
+
 pub enum GroupAction {
     RemoveGroup,
+    SetAutoFetch(bool),
+    SetAccent(Option<egui::Color32>),
+    SetIcon(String),
+    SetCollapsed(bool),
+    /// Move the group to a window, `None` for a new one.
+    MoveToWindow(Option<u64>),
+    MoveTabToWindow(u64, Option<u64>),
+    /// Start the tab with the app even when restored tabs start lazily.
+    SetTabPinned(u64, bool),
     SelectTab(u64),
     RemoveTab(u64),
 }
@@ -43,17 +213,60 @@ pub struct PanelActions {
     pub add_tab_to_group: Option<u64>,
     pub add_agent_tab_to_group: Vec<(u64, usize)>,
     pub group_actions: Vec<(u64, GroupAction)>,
+    /// Current width of the (non-compact) sidebar, to be remembered.
+    pub sidebar_width: Option<f32>,
+    /// A live accent or icon edit is over, so the groups can be saved.
+    pub save_groups: bool,
+}
+
+pub fn window_label(window: u64) -> String {
+    if window == MAIN_WINDOW {
+        "Main window".to_string()
+    } else {
+        format!("Window {}", window + 1)
+    }
+}
+
+/// "Move to window" submenu listing the windows other than `current`.
+/// Returns the picked window, `Some(None)` for a new one.
+fn move_to_window_menu(
+    ui: &mut egui::Ui,
+    current: u64,
+    windows: &[u64],
+    font_size: f32,
+) -> Option<Option<u64>> {
+    let mut target = None;
+    ui.menu_button("🪟 Move to window", |ui| {
+        apply_menu_style(ui, font_size);
+        for &window in windows.iter().filter(|&&w| w != current) {
+            if ui.button(window_label(window)).clicked() {
+                target = Some(Some(window));
+                ui.close();
+            }
+        }
+        if ui.button("➕ New window").clicked() {
+            target = Some(None);
+            ui.close();
+        }
+    });
+    target
 }
 
+/// Sidebar of one top-level window, listing only the groups assigned to it.
 #[allow(clippy::too_many_arguments)]
 pub fn show_left_panel(
     ui: &mut egui::Ui,
+    window: u64,
+    windows: &[u64],
     tab_manager: &TabManager,
     window_manager: &mut super::windows::WindowManager,
     show_sidebar: bool,
+    compact_sidebar: bool,
+    sidebar_width: f32,
     agents: &[AgentConfig; MAX_AGENTS],
     theme: &AppTheme,
     git_cache: &mut GitStatusCache,
+    git_fetcher: &GitFetcher,
     git_enabled: bool,
     show_tab_memory: bool,
     system_monitor: &mut SystemMonitor,
@@ -63,9 +276,32 @@ pub fn show_left_panel(
     let active_group_id = tab_manager.active_group_id;
     let active_tab_id = tab_manager.active_tab_id;
 
-    if show_sidebar {
-        egui::Panel::left("left_panel")
-            .default_size(100.0)
+    if show_sidebar && compact_sidebar {
+        egui::Panel::left(egui::Id::new("left_panel_compact").with(window))
+            .resizable(false)
+            .exact_size(COMPACT_SIDEBAR_WIDTH)
+            .frame(egui::Frame {
+                fill: theme.app_bg_with_opacity(),
+                inner_margin: egui::Margin::symmetric(4, 0),
+                ..Default::default()
+            })
+            .show_inside(ui, |ui| {
+                show_compact_groups(
+                    ui,
+                    window,
+                    tab_manager,
+                    theme,
+                    git_cache,
+                    git_enabled,
+                    system_monitor,
+                    &mut actions,
+                );
+            });
+    } else if show_sidebar {
+        let panel = egui::Panel::left(egui::Id::new("left_panel").with(window))
+            .resizable(true)
+            .default_size(sidebar_width)
+            .size_range(MIN_SIDEBAR_WIDTH..=MAX_SIDEBAR_WIDTH)
             .frame(egui::Frame {
                 fill: theme.app_bg_with_opacity(),
                 inner_margin: egui::Margin::symmetric(6, 0),
@@ -82,7 +318,7 @@ pub fn show_left_panel(
                             egui::FontId::proportional(theme.fonts.ui_font_size),
                         );
                         ui.add_space(8.0);
-                        if tab_manager.groups.is_empty() {
+                        if !tab_manager.groups.values().any(|g| g.window == window) {
                             let add_project_btn = ui
                                 .button("➕ Add project")
                                 .on_hover_cursor(egui::CursorIcon::PointingHand);
@@ -96,10 +332,59 @@ pub fn show_left_panel(
 
                         ui.separator();
 
-                        for (group_id, group) in &tab_manager.groups {
+                        for (group_id, group) in tab_manager
+                            .groups
+                            .iter()
+                            .filter(|(_, g)| g.window == window)
+                        {
                             let is_selected = active_group_id == Some(*group_id);
+                            let accent = group.accent_color();
+                            let git_status = if git_enabled {
+                                git_cache.get_or_refresh(&group.path)
+                            } else {
+                                None
+                            };
+                            let nested_repos = if git_enabled {
+                                git_cache.nested(&group.path)
+                            } else {
+                                Vec::new()
+                            };
+                            // The group icon shows the most urgent state across the
+                            // group's own repository and everything nested in it.
+                            let group_sync = GitSyncStatus::worst(
+                                git_status.iter().map(|s| s.sync_status).chain(
+                                    nested_repos
+                                        .iter()
+                                        .filter_map(|(_, s)| s.as_ref().map(|s| s.sync_status)),
+                                ),
+                            );
 
                             ui.horizontal(|ui| {
+                                let toggle = ui
+                                    .add(
+                                        egui::Button::new(
+                                            egui::RichText::new(if group.collapsed {
+                                                "▶"
+                                            } else {
+                                                "▼"
+                                            })
+                                            .color(theme.panel_text),
+                                        )
+                                        .frame(false),
+                                    )
+                                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                    .on_hover_text(if group.collapsed {
+                                        format!("Expand ({} tabs)", group.tabs.len())
+                                    } else {
+                                        "Collapse".to_string()
+                                    });
+                                if toggle.clicked() {
+                                    actions.group_actions.push((
+                                        *group_id,
+                                        GroupAction::SetCollapsed(!group.collapsed),
+                                    ));
+                                }
+
                                 let centered = ui.centered_and_justified(|ui| {
                                     let sense = egui::Sense::click_and_drag();
                                     let response =
@@ -114,10 +399,33 @@ pub fn show_left_panel(
                                         theme.panel_text
                                     };
 
+                                    // Accent strip on the left; the active group's
+                                    // header is also washed with its accent.
+                                    if is_selected {
+                                        ui.painter().rect_filled(
+                                            response.rect,
+                                            4.0,
+                                            accent.gamma_multiply(0.15),
+                                        );
+                                    }
+                                    ui.painter().rect_filled(
+                                        egui::Rect::from_min_size(
+                                            response.rect.left_top(),
+                                            egui::vec2(3.0, response.rect.height()),
+                                        ),
+                                        1.5,
+                                        accent,
+                                    );
+
+                                    let title = if group.icon.is_empty() {
+                                        group.name.clone()
+                                    } else {
+                                        format!("{} {}", group.icon, group.name)
+                                    };
                                     ui.painter().text(
                                         response.rect.center(),
                                         egui::Align2::CENTER_CENTER,
-                                        &group.name,
+                                        title,
                                         egui::FontId::proportional(
                                             theme.fonts.group_name_font_size,
                                         ),
@@ -132,17 +440,16 @@ pub fn show_left_panel(
                                     response
                                 });
 
+                                let mut group_response = centered.inner;
+
                                 // Git status icon on the right side of the centered group name
                                 // area, drawn on top of the empty right part so the panel does
                                 // not get widened. Skipped entirely when the service is disabled.
                                 if git_enabled {
-                                    let (icon, icon_color) =
-                                        match git_cache.get_or_refresh(&group.path) {
-                                            Some(status) => {
-                                                (status.sync_status.icon(), status.sync_status.color())
-                                            }
-                                            None => ("…", theme.panel_text),
-                                        };
+                                    let (icon, icon_color) = match group_sync {
+                                        Some(sync) => (sync.icon(), sync.color()),
+                                        None => ("…", theme.panel_text),
+                                    };
 
                                     let icon_size = theme.fonts.group_name_font_size;
                                     let icon_pos = egui::pos2(
@@ -156,8 +463,136 @@ pub fn show_left_panel(
                                         egui::FontId::proportional(icon_size),
                                         icon_color,
                                     );
+
+                                    if let Some(status) = &git_status {
+                                        let fetch_state = if group.auto_fetch {
+                                            git_fetcher.state(&group.path)
+                                        } else {
+                                            None
+                                        };
+                                        group_response = group_response.on_hover_ui(|ui| {
+                                            git_status_tooltip(
+                                                ui,
+                                                status,
+                                                group.auto_fetch,
+                                                fetch_state.as_ref(),
+                                            )
+                                        });
+                                    }
                                 }
 
+                                group_response.context_menu(|ui| {
+                                    apply_menu_style(ui, theme.fonts.ui_font_size);
+
+                                    ui.horizontal(|ui| {
+                                        ui.label("🎨 Accent");
+                                        let mut color = accent;
+                                        if egui::color_picker::color_edit_button_srgba(
+                                            ui,
+                                            &mut color,
+                                            egui::color_picker::Alpha::Opaque,
+                                        )
+                                        .changed()
+                                        {
+                                            actions.group_actions.push((
+                                                *group_id,
+                                                GroupAction::SetAccent(Some(color)),
+                                            ));
+                                        }
+                                        // The picker is dragged inside its popup, where the
+                                        // button never sees drag_stopped().
+                                        if ui.input(|i| i.pointer.any_released()) {
+                                            actions.save_groups = true;
+                                        }
+                                        if group.accent.is_some()
+                                            && ui
+                                                .small_button("Auto")
+                                                .on_hover_text("Pick a color from the project path")
+                                                .clicked()
+                                        {
+                                            actions
+                                                .group_actions
+                                                .push((*group_id, GroupAction::SetAccent(None)));
+                                            actions.save_groups = true;
+                                        }
+                                    });
+
+                                    ui.horizontal(|ui| {
+                                        ui.label("Icon");
+                                        let mut icon = group.icon.clone();
+                                        let response = ui.add(
+                                            egui::TextEdit::singleline(&mut icon)
+                                                .desired_width(40.0),
+                                        );
+                                        if response.changed() {
+                                            actions
+                                                .group_actions
+                                                .push((*group_id, GroupAction::SetIcon(icon)));
+                                        }
+                                        if response.lost_focus() {
+                                            actions.save_groups = true;
+                                        }
+                                        if !group.icon.is_empty() && ui.small_button("×").clicked()
+                                        {
+                                            actions.group_actions.push((
+                                                *group_id,
+                                                GroupAction::SetIcon(String::new()),
+                                            ));
+                                            actions.save_groups = true;
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        for icon in GROUP_ICONS {
+                                            if ui
+                                                .selectable_label(group.icon == icon, icon)
+                                                .clicked()
+                                            {
+                                                actions.group_actions.push((
+                                                    *group_id,
+                                                    GroupAction::SetIcon(icon.to_string()),
+                                                ));
+                                                actions.save_groups = true;
+                                            }
+                                        }
+                                    });
+
+                                    if let Some(target) = move_to_window_menu(
+                                        ui,
+                                        window,
+                                        windows,
+                                        theme.fonts.ui_font_size,
+                                    ) {
+                                        actions
+                                            .group_actions
+                                            .push((*group_id, GroupAction::MoveToWindow(target)));
+                                    }
+
+                                    if git_enabled {
+                                        ui.separator();
+
+                                        let mut auto_fetch = group.auto_fetch;
+                                        if ui
+                                            .checkbox(&mut auto_fetch, "🔄 Background fetch")
+                                            .changed()
+                                        {
+                                            actions.group_actions.push((
+                                                *group_id,
+                                                GroupAction::SetAutoFetch(auto_fetch),
+                                            ));
+                                            ui.close();
+                                        }
+
+                                        if git_status.is_some()
+                                            && ui.button("📝 Commit...").clicked()
+                                        {
+                                            window_manager
+                                                .commit_dialog
+                                                .open_for(group.name.clone(), group.path.clone());
+                                            ui.close();
+                                        }
+                                    }
+                                });
+
                                 if group.tabs.is_empty()
                                     && ui
                                         .small_button("×")
@@ -171,59 +606,168 @@ pub fn show_left_panel(
                                 }
                             });
 
+                            // Current branch under the group name; clicking it opens
+                            // the branch switcher for this repository.
+                            if let Some(status) = &git_status {
+                                let branch_text = match (&status.branch, &status.detached_commit) {
+                                    (Some(branch), _) => format!("🔀 {}", branch),
+                                    (None, Some(commit)) => format!("🔀 detached at {}", commit),
+                                    (None, None) => "🔀 (no branch)".to_string(),
+                                };
+                                ui.vertical_centered(|ui| {
+                                    let response = ui
+                                        .add(
+                                            egui::Label::new(
+                                                egui::RichText::new(branch_text)
+                                                    .size(theme.fonts.ui_font_size - 2.0)
+                                                    .color(theme.panel_text),
+                                            )
+                                            .truncate()
+                                            .sense(egui::Sense::click()),
+                                        )
+                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                        .on_hover_text("Switch or create branch");
+                                    if response.clicked() {
+                                        window_manager
+                                            .branch_switcher
+                                            .open_for(group.name.clone(), group.path.clone());
+                                    }
+                                });
+                            }
+
+                            if group.collapsed {
+                                ui.separator();
+                                continue;
+                            }
+
+                            // Nested repositories and submodules, one line each with
+                            // their own status icon and branch.
+                            for (repo_path, repo_status) in &nested_repos {
+                                let rel = repo_path
+                                    .strip_prefix(&group.path)
+                                    .unwrap_or(repo_path)
+                                    .display()
+                                    .to_string();
+                                let (icon, icon_color) = match repo_status {
+                                    Some(status) => {
+                                        (status.sync_status.icon(), status.sync_status.color())
+                                    }
+                                    None => ("…", theme.panel_text),
+                                };
+                                let branch = repo_status.as_ref().and_then(|s| s.branch.as_ref());
+                                let text = match branch {
+                                    Some(branch) => format!("{} · {}", rel, branch),
+                                    None => rel.clone(),
+                                };
+                                let font_size = theme.fonts.ui_font_size - 2.0;
+                                ui.horizontal(|ui| {
+                                    ui.add_space(12.0);
+                                    ui.label(
+                                        egui::RichText::new(icon).size(font_size).color(icon_color),
+                                    );
+                                    let mut response = ui
+                                        .add(
+                                            egui::Label::new(
+                                                egui::RichText::new(text)
+                                                    .size(font_size)
+                                                    .color(theme.panel_text),
+                                            )
+                                            .truncate()
+                                            .sense(egui::Sense::click()),
+                                        )
+                                        .on_hover_cursor(egui::CursorIcon::PointingHand);
+                                    if let Some(status) = repo_status {
+                                        response = response.on_hover_ui(|ui| {
+                                            git_status_tooltip(ui, status, false, None)
+                                        });
+                                    }
+                                    if response.clicked() {
+                                        window_manager.branch_switcher.open_for(
+                                            format!("{} / {}", group.name, rel),
+                                            repo_path.clone(),
+                                        );
+                                    }
+                                });
+                            }
+
                             ui.add_space(10.0);
 
                             for tab_info in &group.tabs {
                                 let tab_id = tab_info.id;
-                                let tab_name = tab_info.display_name.clone();
+                                let alert = system_monitor.tab_alert(tab_id);
+                                let limits = system_monitor.tab_limits(tab_id);
+                                let mut tab_name = if alert.is_some() {
+                                    format!("⚠ {}", tab_info.display_name)
+                                } else {
+                                    tab_info.display_name.clone()
+                                };
+                                if tab_info.pinned {
+                                    tab_name.push_str(" 📌");
+                                }
+                                // Restored tabs that have not started yet.
+                                let started = tab_manager.is_started(tab_id);
                                 let is_active = active_tab_id == Some(tab_id);
 
                                 // When the per-tab memory mode is on, show each
-                                // tab's resident process-tree memory next to its
-                                // name, e.g. "1. Terminal (45 MB)". The memory
-                                // text is tinted by severity: red above 500 MB,
-                                // yellow above 200 MB. The name keeps the tab's
-                                // normal text color via Color32::PLACEHOLDER.
+                                // tab's resident process-tree memory and CPU next
+                                // to its name, e.g. "1. Terminal (45 MB, 3%)". Both
+                                // values are tinted against the tab's alert
+                                // thresholds (see usage_color_for). The name keeps
+                                // the tab's normal text color via
+                                // Color32::PLACEHOLDER.
                                 let display_label: egui::WidgetText = if show_tab_memory {
                                     match tab_manager.get_tab(tab_id) {
                                         Some(tab) => {
-                                            let mem_kb = system_monitor
-                                                .process_tree_memory_kb(tab.backend.pty_id());
-                                            let mem_color = mem_color_for(mem_kb);
+                                            let pid = tab.backend.pty_id();
+                                            let mem_kb = system_monitor.process_tree_memory_kb(pid);
+                                            let cpu = system_monitor.process_tree_cpu_percent(pid);
                                             let font_id = egui::FontId::proportional(
                                                 theme.fonts.tab_font_size,
                                             );
                                             let mut job = LayoutJob::default();
-                                            job.append(
-                                                &tab_name,
-                                                0.0,
-                                                TextFormat {
-                                                    font_id: font_id.clone(),
-                                                    color: egui::Color32::PLACEHOLDER,
-                                                    ..Default::default()
-                                                },
-                                            );
-                                            job.append(
-                                                &format!(" ({})", format_kb(mem_kb)),
-                                                0.0,
-                                                TextFormat {
-                                                    font_id,
-                                                    color: mem_color,
-                                                    ..Default::default()
-                                                },
-                                            );
+                                            let parts = [
+                                                (tab_name.clone(), egui::Color32::PLACEHOLDER),
+                                                (" (".to_string(), egui::Color32::PLACEHOLDER),
+                                                (
+                                                    format_kb(mem_kb),
+                                                    usage_color_for(
+                                                        mem_kb as f32,
+                                                        limits.memory_kb as f32,
+                                                    ),
+                                                ),
+                                                (", ".to_string(), egui::Color32::PLACEHOLDER),
+                                                (
+                                                    format!("{:.0}%", cpu),
+                                                    usage_color_for(cpu, limits.cpu_percent),
+                                                ),
+                                                (")".to_string(), egui::Color32::PLACEHOLDER),
+                                            ];
+                                            for (text, color) in parts {
+                                                job.append(
+                                                    &text,
+                                                    0.0,
+                                                    TextFormat {
+                                                        font_id: font_id.clone(),
+                                                        color,
+                                                        ..Default::default()
+                                                    },
+                                                );
+                                            }
                                             job.into()
                                         }
-                                        None => tab_name.into(),
+                                        None => egui::RichText::new(tab_name).italics().into(),
                                     }
-                                } else {
+                                } else if started {
                                     tab_name.into()
+                                } else {
+                                    egui::RichText::new(tab_name).italics().into()
                                 };
 
                                 ui.horizontal(|ui| {
                                     let width = ui.available_width() * 0.9;
                                     theme.tab_button.apply_to_visuals(ui);
-                                    ui.visuals_mut().selection.bg_fill = theme.tab_active_bg;
+                                    ui.visuals_mut().selection.bg_fill =
+                                        theme.tab_active_bg.lerp_to_gamma(accent, 0.3);
                                     ui.visuals_mut().selection.stroke.color = theme.tab_button.text;
                                     ui.style_mut().text_styles.insert(
                                         egui::TextStyle::Button,
@@ -251,11 +795,80 @@ pub fn show_left_panel(
                                         .on_hover_cursor(egui::CursorIcon::PointingHand);
 
                                     ui.style_mut().spacing.button_padding = old_padding;
+                                    ui.painter().rect_filled(
+                                        egui::Rect::from_min_size(
+                                            response.rect.left_top(),
+                                            egui::vec2(2.0, response.rect.height()),
+                                        ),
+                                        1.0,
+                                        if is_active {
+                                            accent
+                                        } else {
+                                            accent.gamma_multiply(0.4)
+                                        },
+                                    );
+                                    let limit_usage = system_monitor.tab_limit_usage(tab_id);
+                                    let response = if show_tab_memory
+                                        || alert.is_some()
+                                        || limit_usage.is_some()
+                                    {
+                                        response.on_hover_ui(|ui| {
+                                            tab_usage_tooltip(
+                                                ui,
+                                                system_monitor.tab_history(tab_id).as_ref(),
+                                                limits,
+                                                alert,
+                                            );
+                                            if let Some((tab_limits, usage)) = limit_usage {
+                                                tab_limits_tooltip(ui, tab_limits, usage);
+                                            }
+                                        })
+                                    } else {
+                                        response
+                                    };
                                     if response.clicked() {
                                         actions
                                             .group_actions
                                             .push((*group_id, GroupAction::SelectTab(tab_id)));
                                     }
+                                    response.context_menu(|ui| {
+                                        apply_menu_style(ui, theme.fonts.ui_font_size);
+                                        if ui.button("⚙ Processes").clicked() {
+                                            window_manager
+                                                .processes
+                                                .open_for(tab_id, tab_info.display_name.clone());
+                                            ui.close();
+                                        }
+                                        if ui.button("🔌 Ports").clicked() {
+                                            window_manager
+                                                .ports
+                                                .open_for(tab_id, tab_info.display_name.clone());
+                                            ui.close();
+                                        }
+                                        let pin_label = if tab_info.pinned {
+                                            "📌 Don't start with the app"
+                                        } else {
+                                            "📌 Start with the app"
+                                        };
+                                        if ui.button(pin_label).clicked() {
+                                            actions.group_actions.push((
+                                                *group_id,
+                                                GroupAction::SetTabPinned(tab_id, !tab_info.pinned),
+                                            ));
+                                            ui.close();
+                                        }
+                                        if let Some(target) = move_to_window_menu(
+                                            ui,
+                                            window,
+                                            windows,
+                                            theme.fonts.ui_font_size,
+                                        ) {
+                                            actions.group_actions.push((
+                                                *group_id,
+                                                GroupAction::MoveTabToWindow(tab_id, target),
+                                            ));
+                                        }
+                                    });
 
                                     theme.close_button.apply_to_visuals(ui);
                                     let close_btn = ui
@@ -267,6 +880,56 @@ pub fn show_left_panel(
                                             .push((*group_id, GroupAction::RemoveTab(tab_id)));
                                     }
                                 });
+
+                                if let Some(kill) = system_monitor.tab_limit_kill(tab_id) {
+                                    ui.horizontal(|ui| {
+                                        ui.add_space(12.0);
+                                        ui.label(
+                                            egui::RichText::new(format!(
+                                                "💀 Killed: exceeded {}",
+                                                kill.label()
+                                            ))
+                                            .color(egui::Color32::from_rgb(0xff, 0x55, 0x55)),
+                                        );
+                                    });
+                                }
+
+                                // Ports the tab's processes listen on, as
+                                // chips that open the address in a browser.
+                                let ports = system_monitor.tab_ports(tab_id);
+                                if !ports.is_empty() {
+                                    ui.horizontal_wrapped(|ui| {
+                                        ui.add_space(12.0);
+                                        for port in ports {
+                                            let chip = ui
+                                                .small_button(format!("🌐 {}", port.label()))
+                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                .on_hover_text(format!(
+                                                    "Open {} (PID {})",
+                                                    port.url(),
+                                                    port.pid
+                                                ));
+                                            if chip.clicked() {
+                                                ui.ctx()
+                                                    .open_url(egui::OpenUrl::new_tab(port.url()));
+                                            }
+                                            chip.context_menu(|ui| {
+                                                apply_menu_style(ui, theme.fonts.ui_font_size);
+                                                if ui.button("📋 Copy URL").clicked() {
+                                                    copy_to_clipboard(&port.url());
+                                                    ui.close();
+                                                }
+                                                if ui.button("🔌 Ports...").clicked() {
+                                                    window_manager.ports.open_for(
+                                                        tab_id,
+                                                        tab_info.display_name.clone(),
+                                                    );
+                                                    ui.close();
+                                                }
+                                            });
+                                        }
+                                    });
+                                }
                             }
 
                             ui.horizontal(|ui| {
@@ -318,11 +981,155 @@ pub fn show_left_panel(
                         }
                     });
             });
+        actions.sidebar_width = Some(panel.response.rect.width());
     }
 
     actions
 }
 
+/// Compact sidebar: one icon per project with its git badge, and numbered
+/// buttons for its tabs. Clicking a project icon collapses or expands it.
+fn show_compact_groups(
+    ui: &mut egui::Ui,
+    window: u64,
+    tab_manager: &TabManager,
+    theme: &AppTheme,
+    git_cache: &mut GitStatusCache,
+    git_enabled: bool,
+    system_monitor: &mut SystemMonitor,
+    actions: &mut PanelActions,
+) {
+    let active_group_id = tab_manager.active_group_id;
+    let active_tab_id = tab_manager.active_tab_id;
+
+    egui::ScrollArea::vertical()
+        .id_salt("left_panel_compact_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui.add_space(8.0);
+            if !tab_manager.groups.values().any(|g| g.window == window)
+                && ui
+                    .button("➕")
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Add project")
+                    .clicked()
+            {
+                actions.add_group_clicked = true;
+            }
+
+            for (group_id, group) in tab_manager
+                .groups
+                .iter()
+                .filter(|(_, g)| g.window == window)
+            {
+                let accent = group.accent_color();
+                let is_selected = active_group_id == Some(*group_id);
+                let group_sync = if git_enabled {
+                    GitSyncStatus::worst(
+                        git_cache
+                            .get_or_refresh(&group.path)
+                            .map(|s| s.sync_status)
+                            .into_iter()
+                            .chain(
+                                git_cache
+                                    .nested(&group.path)
+                                    .into_iter()
+                                    .filter_map(|(_, s)| s.map(|s| s.sync_status)),
+                            ),
+                    )
+                } else {
+                    None
+                };
+
+                ui.vertical_centered(|ui| {
+                    let icon = if group.icon.is_empty() {
+                        group
+                            .name
+                            .chars()
+                            .next()
+                            .map(|c| c.to_uppercase().to_string())
+                            .unwrap_or_default()
+                    } else {
+                        group.icon.clone()
+                    };
+                    let text_color = if is_selected {
+                        theme.panel_text_selected
+                    } else {
+                        theme.panel_text
+                    };
+                    let response = ui
+                        .add(
+                            egui::Button::new(
+                                egui::RichText::new(icon)
+                                    .size(theme.fonts.group_name_font_size)
+                                    .color(text_color),
+                            )
+                            .fill(if is_selected {
+                                accent.gamma_multiply(0.35)
+                            } else {
+                                egui::Color32::TRANSPARENT
+                            })
+                            .stroke(egui::Stroke::new(1.5, accent))
+                            .min_size(egui::vec2(36.0, 36.0)),
+                        )
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text(&group.name);
+                    if let Some(sync) = group_sync {
+                        ui.painter().text(
+                            response.rect.right_top() + egui::vec2(2.0, -2.0),
+                            egui::Align2::RIGHT_TOP,
+                            sync.icon(),
+                            egui::FontId::proportional(theme.fonts.ui_font_size - 4.0),
+                            sync.color(),
+                        );
+                    }
+                    if response.clicked() {
+                        actions
+                            .group_actions
+                            .push((*group_id, GroupAction::SetCollapsed(!group.collapsed)));
+                    }
+                    if group.collapsed {
+                        return;
+                    }
+
+                    theme.tab_button.apply_to_visuals(ui);
+                    ui.visuals_mut().selection.bg_fill =
+                        theme.tab_active_bg.lerp_to_gamma(accent, 0.3);
+                    for (index, tab_info) in group.tabs.iter().enumerate() {
+                        let label = if system_monitor.tab_alert(tab_info.id).is_some() {
+                            format!("⚠{}", index + 1)
+                        } else {
+                            (index + 1).to_string()
+                        };
+                        let response = ui
+                            .add(
+                                egui::Button::selectable(active_tab_id == Some(tab_info.id), label)
+                                    .min_size(egui::vec2(32.0, 24.0)),
+                            )
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text(&tab_info.display_name);
+                        if response.clicked() {
+                            actions
+                                .group_actions
+                                .push((*group_id, GroupAction::SelectTab(tab_info.id)));
+                        }
+                    }
+
+                    theme.terminal_button.apply_to_visuals(ui);
+                    if ui
+                        .small_button("➕")
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text("New terminal")
+                        .clicked()
+                    {
+                        actions.add_tab_to_group = Some(*group_id);
+                    }
+                });
+                ui.separator();
+            }
+        });
+}
+
 pub fn show_search_panel(ui: &mut egui::Ui, tab_manager: &mut TabManager, theme: &AppTheme) {
     let Some(tab) = tab_manager.get_active() else {
         return;
@@ -413,7 +1220,7 @@ pub fn show_search_panel(ui: &mut egui::Ui, tab_manager: &mut TabManager, theme:
 pub fn show_central_panel(
     ui: &mut egui::Ui,
     tab_manager: &mut TabManager,
-    window_manager: &super::windows::WindowManager,
+    window_manager: &mut super::windows::WindowManager,
     theme: &AppTheme,
     terminal_theme: &egui_term::TerminalTheme,
     terminal_font: &egui_term::TerminalFont,
@@ -471,10 +1278,14 @@ pub fn show_central_panel(
                             .set_font(terminal_font.clone())
                             .set_focus(
                                 !window_manager.show_rename_group
+                                    && !window_manager.workspace_name_focused
                                     && !window_manager.show_settings
                                     && !window_manager.show_agents_settings
                                     && !window_manager.show_theme_settings
                                     && !window_manager.show_font_settings
+                                    && !window_manager.branch_switcher.open
+                                    && !window_manager.commit_dialog.open
+                                    && window_manager.pending_close.is_none()
                                     && !should_block_input
                                     && !tab.search_active,
                             )
@@ -523,6 +1334,13 @@ pub fn show_central_panel(
                             scroll_state.user_scrolled_up = !is_at_bottom;
                         }
                     });
+            } else if let Some((tab_id, error)) = tab_manager
+                .active_tab_id
+                .and_then(|id| Some((id, tab_manager.spawn_error(id)?.clone())))
+            {
+                if spawn_error_view(ui, &error, window_manager) {
+                    tab_manager.retry_tab(tab_id, ui.ctx());
+                }
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.label("No active tab. Select a group and add a tab.");
@@ -534,3 +1352,55 @@ pub fn show_central_panel(
             }
         });
 }
+
+/// Shown in place of a terminal whose program could not be started, with
+/// every command that was tried. Returns whether a retry was requested.
+fn spawn_error_view(
+    ui: &mut egui::Ui,
+    error: &SpawnError,
+    window_manager: &mut super::windows::WindowManager,
+) -> bool {
+    let mut retry = false;
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() * 0.2);
+        ui.heading("⚠ This tab could not be started");
+        ui.add_space(10.0);
+
+        if error.attempts.is_empty() {
+            ui.label("There was no shell or agent command to start.");
+        } else {
+            ui.label("Tried, in order:");
+            ui.add_space(4.0);
+            egui::Grid::new("spawn_error_grid")
+                .num_columns(2)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    for attempt in &error.attempts {
+                        ui.label(egui::RichText::new(&attempt.command).monospace());
+                        ui.label(
+                            egui::RichText::new(&attempt.error).color(ui.visuals().error_fg_color),
+                        );
+                        ui.end_row();
+                    }
+                });
+        }
+
+        ui.add_space(12.0);
+        if ui.button("🔄 Retry").clicked() {
+            retry = true;
+        }
+        let settings_label = if error.is_agent {
+            "⚙ Agent settings"
+        } else {
+            "⚙ Shell settings"
+        };
+        if ui.button(settings_label).clicked() {
+            if error.is_agent {
+                window_manager.show_agents_settings = true;
+            } else {
+                window_manager.show_settings = true;
+            }
+        }
+    });
+    retry
+}
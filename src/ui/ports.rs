@@ -0,0 +1,145 @@
+use crate::system_monitor::{ProcessSignal, SystemMonitor};
+use std::time::Duration;
+
+/// State of the "Ports" window: the listening ports of one tab or of every
+/// tab, with links to open them and buttons to stop the owning process.
+#[derive(Default)]
+pub struct PortsWindow {
+    pub open: bool,
+    /// Tab whose ports are shown; `None` shows every tab's.
+    pub tab_id: Option<u64>,
+    tab_name: String,
+    /// Outcome of the last signal: `Ok` with a confirmation or `Err`.
+    status: Option<Result<String, String>>,
+}
+
+impl PortsWindow {
+    pub fn open_for(&mut self, tab_id: u64, tab_name: String) {
+        if self.tab_id != Some(tab_id) {
+            self.status = None;
+        }
+        self.tab_id = Some(tab_id);
+        self.tab_name = tab_name;
+        self.open = true;
+    }
+
+    /// Draw the window for `tabs`, given as (tab id, display name).
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        system_monitor: &mut SystemMonitor,
+        tabs: &[(u64, String)],
+    ) {
+        if !self.open {
+            return;
+        }
+        // Ports are rescanned every couple of seconds; keep redrawing so the
+        // table follows.
+        ctx.request_repaint_after(Duration::from_secs(1));
+
+        let rows: Vec<_> = tabs
+            .iter()
+            .filter(|(tab_id, _)| self.tab_id.is_none_or(|id| id == *tab_id))
+            .flat_map(|(tab_id, tab_name)| {
+                system_monitor
+                    .tab_ports(*tab_id)
+                    .into_iter()
+                    .map(move |port| (tab_name, port))
+            })
+            .collect();
+
+        let mut open = self.open;
+        let mut signal: Option<(u32, ProcessSignal)> = None;
+        let mut show_all = false;
+        let title = match self.tab_id {
+            Some(_) => format!("Ports — {}", self.tab_name),
+            None => "Ports".to_string(),
+        };
+
+        egui::Window::new(title)
+            .id(egui::Id::new("ports_window"))
+            .default_size([560.0, 300.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Frame::NONE.inner_margin(10.0).show(ui, |ui| {
+                    if self.tab_id.is_some() {
+                        show_all = ui.small_button("Show all tabs").clicked();
+                        ui.add_space(8.0);
+                    }
+                    if rows.is_empty() {
+                        ui.weak(match self.tab_id {
+                            Some(_) => "The tab is not listening on a TCP port.",
+                            None => "No tab is listening on a TCP port.",
+                        });
+                    } else {
+                        egui::Grid::new("ports_grid")
+                            .num_columns(5)
+                            .striped(true)
+                            .spacing([12.0, 4.0])
+                            .show(ui, |ui| {
+                                for header in ["Address", "Tab", "Process", "PID", ""] {
+                                    ui.label(egui::RichText::new(header).strong());
+                                }
+                                ui.end_row();
+
+                                for (tab_name, port) in &rows {
+                                    ui.hyperlink_to(port.label(), port.url());
+                                    ui.label(tab_name.as_str());
+                                    ui.label(
+                                        system_monitor.process_name(port.pid).unwrap_or_default(),
+                                    );
+                                    ui.label(port.pid.to_string());
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .small_button("Stop")
+                                            .on_hover_text("Send SIGTERM")
+                                            .clicked()
+                                        {
+                                            signal = Some((port.pid, ProcessSignal::Terminate));
+                                        }
+                                        if ui
+                                            .small_button("Kill")
+                                            .on_hover_text("Send SIGKILL")
+                                            .clicked()
+                                        {
+                                            signal = Some((port.pid, ProcessSignal::Kill));
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    }
+
+                    match &self.status {
+                        Some(Ok(message)) => {
+                            ui.add_space(8.0);
+                            ui.weak(message);
+                        }
+                        Some(Err(error)) => {
+                            ui.add_space(8.0);
+                            ui.label(
+                                egui::RichText::new(error)
+                                    .color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                            );
+                        }
+                        None => {}
+                    }
+                });
+            });
+
+        if let Some((pid, s)) = signal {
+            self.status = Some(
+                system_monitor
+                    .send_signal(pid, s)
+                    .map(|()| format!("Sent {} to {}", s.label(), pid)),
+            );
+        }
+        if show_all {
+            self.tab_id = None;
+        }
+        if !open {
+            self.open = false;
+            self.status = None;
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::system_monitor::{format_duration, format_kb, ProcessSignal, SystemMonitor};
+use std::time::Duration;
+
+/// Longest command line shown in the table; the full one is in the tooltip.
+const MAX_CMD_CHARS: usize = 60;
+
+/// State of the "Processes" window: a live tree of the processes running in
+/// one tab, with buttons to signal the selected one.
+#[derive(Default)]
+pub struct ProcessesWindow {
+    pub open: bool,
+    /// Tab whose process tree is shown.
+    pub tab_id: Option<u64>,
+    tab_name: String,
+    selected: Option<u32>,
+    /// Outcome of the last signal: `Ok` with a confirmation or `Err`.
+    status: Option<Result<String, String>>,
+}
+
+impl ProcessesWindow {
+    pub fn open_for(&mut self, tab_id: u64, tab_name: String) {
+        if self.tab_id != Some(tab_id) {
+            self.selected = None;
+            self.status = None;
+        }
+        self.tab_id = Some(tab_id);
+        self.tab_name = tab_name;
+        self.open = true;
+    }
+
+    /// Draw the window for the tab's root process `root_pid`, which is `None`
+    /// once the tab has been closed.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        system_monitor: &mut SystemMonitor,
+        root_pid: Option<u32>,
+    ) {
+        if !self.open {
+            return;
+        }
+        // Process data refreshes every couple of seconds; keep redrawing so
+        // the table follows it even when nothing else changes.
+        ctx.request_repaint_after(Duration::from_secs(1));
+
+        let tree = root_pid
+            .map(|pid| system_monitor.process_tree(pid))
+            .unwrap_or_default();
+        if self
+            .selected
+            .is_some_and(|pid| !tree.iter().any(|p| p.pid == pid))
+        {
+            self.selected = None;
+        }
+
+        let mut open = self.open;
+        let mut signal: Option<(u32, ProcessSignal)> = None;
+
+        egui::Window::new(format!("Processes — {}", self.tab_name))
+            .id(egui::Id::new("processes_window"))
+            .default_size([640.0, 400.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Frame::NONE.inner_margin(10.0).show(ui, |ui| {
+                    if tree.is_empty() {
+                        ui.weak("The tab's process has exited.");
+                        return;
+                    }
+
+                    egui::ScrollArea::vertical()
+                        .id_salt("processes_scroll")
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("processes_grid")
+                                .num_columns(6)
+                                .striped(true)
+                                .spacing([12.0, 4.0])
+                                .show(ui, |ui| {
+                                    for header in
+                                        ["Process", "PID", "CPU", "Memory", "Uptime", "Command"]
+                                    {
+                                        ui.label(egui::RichText::new(header).strong());
+                                    }
+                                    ui.end_row();
+
+                                    for process in &tree {
+                                        let name = format!(
+                                            "{}{}",
+                                            "    ".repeat(process.depth),
+                                            process.name
+                                        );
+                                        let is_selected = self.selected == Some(process.pid);
+                                        if ui.selectable_label(is_selected, name).clicked() {
+                                            self.selected = Some(process.pid);
+                                        }
+                                        ui.label(process.pid.to_string());
+                                        ui.label(format!("{:.1}%", process.cpu_percent));
+                                        ui.label(format_kb(process.memory_kb));
+                                        ui.label(format_duration(process.run_time_secs));
+                                        let cmd: String =
+                                            process.cmd.chars().take(MAX_CMD_CHARS).collect();
+                                        let cmd = if cmd.len() < process.cmd.len() {
+                                            format!("{}…", cmd)
+                                        } else {
+                                            cmd
+                                        };
+                                        ui.weak(cmd).on_hover_text(&process.cmd);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+
+                    let selected = self
+                        .selected
+                        .and_then(|pid| tree.iter().find(|p| p.pid == pid));
+                    ui.horizontal(|ui| {
+                        match selected {
+                            Some(process) => {
+                                ui.label(format!("{} ({})", process.name, process.pid));
+                            }
+                            None => {
+                                ui.weak("Select a process");
+                            }
+                        }
+                        for s in ProcessSignal::ALL {
+                            if ui
+                                .add_enabled(selected.is_some(), egui::Button::new(s.label()))
+                                .clicked()
+                            {
+                                signal = selected.map(|p| (p.pid, s));
+                            }
+                        }
+                    });
+
+                    match &self.status {
+                        Some(Ok(message)) => {
+                            ui.weak(message);
+                        }
+                        Some(Err(error)) => {
+                            ui.label(
+                                egui::RichText::new(error)
+                                    .color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                            );
+                        }
+                        None => {}
+                    }
+                });
+            });
+
+        if let Some((pid, s)) = signal {
+            self.status = Some(
+                system_monitor
+                    .send_signal(pid, s)
+                    .map(|()| format!("Sent {} to {}", s.label(), pid)),
+            );
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::system_monitor::HISTORY_LEN;
+
+/// Draw a small line chart of `values` (oldest first) scaled to `0..=max`, or
+/// to the largest value if that is higher. The time axis is fixed to
+/// [`HISTORY_LEN`] samples, so a short history starts at the right edge.
+/// `limit` draws a horizontal threshold line.
+pub fn sparkline(
+    ui: &mut egui::Ui,
+    values: &[f32],
+    max: f32,
+    limit: Option<f32>,
+    color: egui::Color32,
+    size: egui::Vec2,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return response;
+    }
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let scale = values
+        .iter()
+        .copied()
+        .chain(limit)
+        .fold(max, f32::max)
+        .max(f32::EPSILON);
+    let y_for = |v: f32| rect.bottom() - (v / scale).clamp(0.0, 1.0) * (rect.height() - 2.0) - 1.0;
+
+    if let Some(limit) = limit.filter(|l| *l > 0.0) {
+        let y = y_for(limit);
+        painter.hline(
+            rect.x_range(),
+            y,
+            egui::Stroke::new(
+                1.0,
+                egui::Color32::from_rgba_unmultiplied(0xff, 0x55, 0x55, 0x80),
+            ),
+        );
+    }
+
+    if values.len() >= 2 {
+        let step = rect.width() / (HISTORY_LEN - 1) as f32;
+        let start_x = rect.right() - step * (values.len() - 1) as f32;
+        let points: Vec<egui::Pos2> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| egui::pos2(start_x + step * i as f32, y_for(*v)))
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+    }
+
+    response
+}
@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+/// How long a notification stays on screen unless clicked away.
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+/// Transient notifications stacked in the bottom-right corner.
+#[derive(Default)]
+pub struct Toasts {
+    items: Vec<(String, Instant)>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, text: String) {
+        self.items.push((text, Instant::now()));
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.items
+            .retain(|(_, shown_at)| shown_at.elapsed() < TOAST_DURATION);
+        if self.items.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-16.0, -16.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for (i, (text, _)) in self.items.iter().enumerate() {
+                    let response = egui::Frame::popup(ui.style())
+                        .show(ui, |ui| {
+                            ui.set_max_width(320.0);
+                            ui.label(text);
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_cursor(egui::CursorIcon::PointingHand);
+                    if response.clicked() {
+                        dismissed = Some(i);
+                    }
+                    ui.add_space(6.0);
+                }
+            });
+        if let Some(i) = dismissed {
+            self.items.remove(i);
+        }
+
+        let oldest = self.items.iter().map(|(_, at)| *at).min();
+        if let Some(oldest) = oldest {
+            ctx.request_repaint_after(TOAST_DURATION.saturating_sub(oldest.elapsed()));
+        }
+    }
+}
@@ -1,8 +1,36 @@
-use crate::config::settings::{AgentConfig, MAX_AGENTS};
+use crate::config::settings::{AgentConfig, PreloadLimits, TabAlertSettings, MAX_AGENTS};
+use crate::config::themes::{self, ThemeLibrary};
+use crate::constants::{MAIN_WINDOW, TERMINAL_FONT_FAMILY};
+use crate::font_setup::SystemFont;
 use crate::hotkeys::get_hotkeys;
 use crate::theme::{
-    color_picker_button, font_size_slider, opacity_slider, AppButtonStyle, AppFonts, AppTheme,
+    color_picker_button, color_swatch_picker, font_size_slider, opacity_slider, AppButtonStyle,
+    AppFonts, AppTheme, FontFaces, FontSource, ANSI_COLOR_NAMES,
 };
+use crate::theme_import::{self, ImportedTheme};
+use crate::ui::agent_diagnostics::AgentDiagnostics;
+use crate::ui::branches::BranchSwitcher;
+use crate::ui::commit::CommitDialog;
+use crate::ui::ports::PortsWindow;
+use crate::ui::processes::ProcessesWindow;
+use std::path::PathBuf;
+
+/// What a pending close confirmation would close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseTarget {
+    Tab(u64),
+    Group(u64),
+}
+
+/// A tab or group close that waits for confirmation because it would kill
+/// running processes.
+pub struct PendingClose {
+    pub target: CloseTarget,
+    /// Name of the tab or group, for the window title.
+    pub name: String,
+    /// One line per process that would be terminated.
+    pub commands: Vec<String>,
+}
 
 pub struct WindowManager {
     pub show_about: bool,
@@ -12,7 +40,22 @@ pub struct WindowManager {
     pub show_theme_settings: bool,
     pub show_font_settings: bool,
     pub show_rename_group: bool,
+    /// The workspace name field of the Projects menu has keyboard focus.
+    pub workspace_name_focused: bool,
     pub show_close_confirmation: bool,
+    /// Processes the exit confirmation lists as about to be terminated.
+    pub exit_commands: Vec<String>,
+    pub pending_close: Option<PendingClose>,
+    pub branch_switcher: BranchSwitcher,
+    pub commit_dialog: CommitDialog,
+    /// Drawn by `App`, which owns the system monitor it needs.
+    pub processes: ProcessesWindow,
+    /// Drawn by `App` as well.
+    pub ports: PortsWindow,
+    /// Window the group and tab dialogs were last opened from; they are
+    /// drawn in it rather than in the main window.
+    pub dialog_window: u64,
+    pub agent_diagnostics: AgentDiagnostics,
     pub rename_group_id: Option<u64>,
     pub rename_group_name: String,
     pub editing_default_shell_cmd: String,
@@ -25,10 +68,43 @@ pub struct WindowManager {
     pub saved_enable_git_status: bool,
     pub editing_preload_tabs: bool,
     pub saved_preload_tabs: bool,
+    pub editing_preload_limits: PreloadLimits,
+    pub saved_preload_limits: PreloadLimits,
+    pub editing_lazy_tab_spawn: bool,
+    pub saved_lazy_tab_spawn: bool,
+    pub editing_keep_workspaces_alive: bool,
+    pub saved_keep_workspaces_alive: bool,
+    pub editing_git_fetch_interval_secs: u64,
+    pub saved_git_fetch_interval_secs: u64,
+    pub editing_git_nested_depth: usize,
+    pub saved_git_nested_depth: usize,
+    pub editing_tab_alerts: TabAlertSettings,
+    pub saved_tab_alerts: TabAlertSettings,
+    /// Comma-separated `Settings::close_confirm_ignore`.
+    pub editing_close_confirm_ignore: String,
+    pub saved_close_confirm_ignore: String,
     pub editing_theme: AppTheme,
     pub saved_theme: AppTheme,
+    pub theme_library: ThemeLibrary,
+    /// Library name of the editing theme; `None` when it was never picked
+    /// from or saved to the library.
+    pub editing_theme_name: Option<String>,
+    pub saved_theme_name: Option<String>,
+    /// Name field for "Save as" and "Rename".
+    theme_name_input: String,
+    /// Outcome of the last library operation.
+    theme_library_status: Option<Result<String, String>>,
+    /// A scheme picked with "Import...", previewed until applied or discarded.
+    pub theme_import: Option<Result<ImportedTheme, String>>,
     pub editing_fonts: AppFonts,
     pub saved_fonts: AppFonts,
+    pub editing_font_faces: FontFaces,
+    pub saved_font_faces: FontFaces,
+    /// Installed fonts for the font pickers, listed when the font settings
+    /// window first opens.
+    system_fonts: Option<Vec<SystemFont>>,
+    /// Why the last font setup could not load a font, set by `App`.
+    pub font_error: Option<String>,
     pub was_settings_open: bool,
     pub was_agents_settings_open: bool,
     pub was_theme_settings_open: bool,
@@ -45,7 +121,17 @@ impl WindowManager {
         run_as_login_shell: bool,
         enable_git_status: bool,
         theme: AppTheme,
+        theme_library: ThemeLibrary,
+        theme_name: Option<String>,
+        font_faces: FontFaces,
         preload_tabs: bool,
+        preload_limits: PreloadLimits,
+        lazy_tab_spawn: bool,
+        keep_workspaces_alive: bool,
+        git_fetch_interval_secs: u64,
+        git_nested_depth: usize,
+        tab_alerts: TabAlertSettings,
+        close_confirm_ignore: &[String],
     ) -> Self {
         let editing_default_shell_cmd = default_shell_cmd.clone();
         let saved_default_shell_cmd = editing_default_shell_cmd.clone();
@@ -57,6 +143,20 @@ impl WindowManager {
         let saved_enable_git_status = enable_git_status;
         let editing_preload_tabs = preload_tabs;
         let saved_preload_tabs = preload_tabs;
+        let editing_preload_limits = preload_limits;
+        let saved_preload_limits = preload_limits;
+        let editing_lazy_tab_spawn = lazy_tab_spawn;
+        let saved_lazy_tab_spawn = lazy_tab_spawn;
+        let editing_keep_workspaces_alive = keep_workspaces_alive;
+        let saved_keep_workspaces_alive = keep_workspaces_alive;
+        let editing_git_fetch_interval_secs = git_fetch_interval_secs;
+        let saved_git_fetch_interval_secs = git_fetch_interval_secs;
+        let editing_git_nested_depth = git_nested_depth;
+        let saved_git_nested_depth = git_nested_depth;
+        let editing_tab_alerts = tab_alerts;
+        let saved_tab_alerts = tab_alerts;
+        let editing_close_confirm_ignore = close_confirm_ignore.join(", ");
+        let saved_close_confirm_ignore = editing_close_confirm_ignore.clone();
         let editing_theme = theme;
         let saved_theme = editing_theme;
         let editing_fonts = editing_theme.fonts;
@@ -70,7 +170,16 @@ impl WindowManager {
             show_theme_settings: false,
             show_font_settings: false,
             show_rename_group: false,
+            workspace_name_focused: false,
             show_close_confirmation: false,
+            exit_commands: Vec::new(),
+            pending_close: None,
+            branch_switcher: BranchSwitcher::default(),
+            commit_dialog: CommitDialog::default(),
+            processes: ProcessesWindow::default(),
+            ports: PortsWindow::default(),
+            agent_diagnostics: AgentDiagnostics::default(),
+            dialog_window: MAIN_WINDOW,
             rename_group_id: None,
             rename_group_name: String::new(),
             editing_default_shell_cmd,
@@ -83,10 +192,34 @@ impl WindowManager {
             saved_enable_git_status,
             editing_preload_tabs,
             saved_preload_tabs,
+            editing_preload_limits,
+            saved_preload_limits,
+            editing_lazy_tab_spawn,
+            saved_lazy_tab_spawn,
+            editing_keep_workspaces_alive,
+            saved_keep_workspaces_alive,
+            editing_git_fetch_interval_secs,
+            saved_git_fetch_interval_secs,
+            editing_git_nested_depth,
+            saved_git_nested_depth,
+            editing_tab_alerts,
+            saved_tab_alerts,
+            editing_close_confirm_ignore,
+            saved_close_confirm_ignore,
             editing_theme,
             saved_theme,
+            theme_library,
+            theme_name_input: theme_name.clone().unwrap_or_default(),
+            editing_theme_name: theme_name.clone(),
+            saved_theme_name: theme_name,
+            theme_library_status: None,
+            theme_import: None,
             editing_fonts,
             saved_fonts,
+            saved_font_faces: font_faces.clone(),
+            editing_font_faces: font_faces,
+            system_fonts: None,
+            font_error: None,
             was_settings_open: false,
             was_agents_settings_open: false,
             was_theme_settings_open: false,
@@ -100,7 +233,6 @@ impl WindowManager {
 
         self.show_about_window(ctx);
         self.show_hotkeys_window(ctx);
-        self.show_rename_group_window(ctx, &mut actions);
         self.show_settings_window(ctx, &mut actions);
         self.show_agents_settings_window(ctx, &mut actions);
         self.show_theme_settings_window(ctx, &mut actions);
@@ -110,6 +242,41 @@ impl WindowManager {
         actions
     }
 
+    /// Draw the dialogs opened from the sidebar or a tab, which belong in
+    /// `dialog_window`. The processes and ports windows are drawn by `App`.
+    pub fn show_window_dialogs(&mut self, ctx: &egui::Context) -> WindowActions {
+        let mut actions = WindowActions::default();
+
+        self.show_rename_group_window(ctx, &mut actions);
+        self.show_pending_close_window(ctx, &mut actions);
+        if self.branch_switcher.open {
+            actions.refresh_git_path = self.branch_switcher.show(ctx);
+        }
+        // Keep polling while a commit is running so the result is picked up
+        // even if the window was closed in the meantime.
+        if let Some(path) = self.commit_dialog.show(ctx) {
+            actions.refresh_git_path = Some(path);
+        }
+
+        actions
+    }
+
+    /// Number of group and tab dialogs open, to tell which window opened
+    /// the latest one.
+    pub fn window_dialogs_open(&self) -> usize {
+        [
+            self.show_rename_group,
+            self.pending_close.is_some(),
+            self.branch_switcher.open,
+            self.commit_dialog.open,
+            self.processes.open,
+            self.ports.open,
+        ]
+        .into_iter()
+        .filter(|open| *open)
+        .count()
+    }
+
     fn show_about_window(&mut self, ctx: &egui::Context) {
         egui::Window::new("About")
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -221,6 +388,37 @@ impl WindowManager {
 
                     ui.add_space(15.0);
 
+                    ui.horizontal(|ui| {
+                        ui.label("Login shell environment:");
+                        match crate::shell_env::status() {
+                            crate::shell_env::Status::Unavailable => {
+                                ui.weak("not used on this platform");
+                            }
+                            crate::shell_env::Status::Loading => {
+                                ui.spinner();
+                                ui.label("loading…");
+                            }
+                            crate::shell_env::Status::Ready { shell, count } => {
+                                ui.label(format!("{} variables from {}", count, shell));
+                            }
+                            crate::shell_env::Status::Failed(error) => {
+                                ui.colored_label(ui.visuals().warn_fg_color, error);
+                            }
+                        }
+                        if ui
+                            .small_button("🔄 Reload")
+                            .on_hover_text(
+                                "Run the login shell again. Commands are looked up with \
+                                 its PATH right away; tabs get it after a restart.",
+                            )
+                            .clicked()
+                        {
+                            crate::shell_env::refresh(ctx.clone());
+                        }
+                    });
+
+                    ui.add_space(15.0);
+
                     ui.checkbox(
                         &mut self.editing_enable_git_status,
                         "Show Git status in sidebar",
@@ -228,10 +426,133 @@ impl WindowManager {
 
                     ui.add_space(15.0);
 
+                    ui.horizontal(|ui| {
+                        ui.label("Background fetch interval:");
+                        let mut minutes = (self.editing_git_fetch_interval_secs / 60).max(1);
+                        if ui
+                            .add(egui::DragValue::new(&mut minutes).range(1..=1440).suffix(" min"))
+                            .changed()
+                        {
+                            self.editing_git_fetch_interval_secs = minutes * 60;
+                        }
+                    })
+                    .response
+                    .on_hover_text("Enable background fetch per project from its context menu");
+
+                    ui.add_space(15.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Nested repository depth:");
+                        ui.add(egui::DragValue::new(&mut self.editing_git_nested_depth).range(0..=5));
+                    })
+                    .response
+                    .on_hover_text(
+                        "How many folder levels below a project are searched for nested \
+                         repositories and submodules (0 = off)",
+                    );
+
+                    ui.add_space(15.0);
+
                     ui.checkbox(
                         &mut self.editing_preload_tabs,
                         "Enable terminal preload",
                     );
+                    ui.add_enabled_ui(self.editing_preload_tabs, |ui| {
+                        egui::Grid::new("preload_limits_grid")
+                            .num_columns(2)
+                            .spacing([12.0, 6.0])
+                            .show(ui, |ui| {
+                                ui.label("Preloaded tabs at most");
+                                ui.add(
+                                    egui::DragValue::new(&mut self.editing_preload_limits.max_tabs)
+                                        .range(1..=20),
+                                );
+                                ui.end_row();
+                                ui.label("For the last used projects");
+                                ui.add(
+                                    egui::DragValue::new(
+                                        &mut self.editing_preload_limits.recent_groups,
+                                    )
+                                    .range(1..=10),
+                                );
+                                ui.end_row();
+                                ui.label("Drop after unused for");
+                                ui.add(
+                                    egui::DragValue::new(
+                                        &mut self.editing_preload_limits.idle_mins,
+                                    )
+                                    .range(0..=1440)
+                                    .suffix(" min"),
+                                )
+                                .on_hover_text("0 = never");
+                                ui.end_row();
+                                ui.label("Pause above system memory");
+                                ui.add(
+                                    egui::DragValue::new(
+                                        &mut self.editing_preload_limits.max_memory_percent,
+                                    )
+                                    .range(0..=100)
+                                    .suffix("%"),
+                                )
+                                .on_hover_text("0 = never");
+                                ui.end_row();
+                            });
+                    });
+                    ui.checkbox(
+                        &mut self.editing_lazy_tab_spawn,
+                        "Start restored tabs when first opened",
+                    )
+                    .on_hover_text(
+                        "Saved tabs start their shell or agent only when selected; \
+                         pinned tabs still start with the app",
+                    );
+                    ui.checkbox(
+                        &mut self.editing_keep_workspaces_alive,
+                        "Keep workspaces running in the background",
+                    )
+                    .on_hover_text(
+                        "When switching workspaces, leave the previous one's terminals \
+                         running instead of closing them",
+                    );
+
+                    ui.add_space(15.0);
+
+                    ui.label("Tab usage alerts (0 = off):");
+                    egui::Grid::new("tab_alerts_grid")
+                        .num_columns(2)
+                        .spacing([12.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.label("Memory above");
+                            ui.add(
+                                egui::DragValue::new(&mut self.editing_tab_alerts.memory_mb)
+                                    .range(0..=65536)
+                                    .speed(16)
+                                    .suffix(" MB"),
+                            );
+                            ui.end_row();
+                            ui.label("CPU above");
+                            ui.add(
+                                egui::DragValue::new(&mut self.editing_tab_alerts.cpu_percent)
+                                    .range(0..=6400)
+                                    .suffix("%"),
+                            );
+                            ui.end_row();
+                            ui.label("For at least");
+                            ui.add(
+                                egui::DragValue::new(&mut self.editing_tab_alerts.duration_secs)
+                                    .range(2..=120)
+                                    .suffix(" s"),
+                            );
+                            ui.end_row();
+                        });
+
+                    ui.add_space(15.0);
+
+                    ui.label("Close tabs without asking while only these run:");
+                    ui.text_edit_singleline(&mut self.editing_close_confirm_ignore)
+                        .on_hover_text(
+                            "Comma-separated program names; a trailing * matches by prefix",
+                        );
 
                     ui.add_space(15.0);
 
@@ -257,10 +578,29 @@ impl WindowManager {
             actions.run_as_login_shell = Some(self.editing_run_as_login_shell);
             actions.enable_git_status = Some(self.editing_enable_git_status);
             actions.preload_tabs = Some(self.editing_preload_tabs);
+            actions.preload_limits = Some(self.editing_preload_limits);
+            actions.lazy_tab_spawn = Some(self.editing_lazy_tab_spawn);
+            actions.git_fetch_interval_secs = Some(self.editing_git_fetch_interval_secs);
+            actions.git_nested_depth = Some(self.editing_git_nested_depth);
+            actions.tab_alerts = Some(self.editing_tab_alerts);
+            actions.close_confirm_ignore = Some(
+                self.editing_close_confirm_ignore
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            );
             self.saved_default_shell_cmd = self.editing_default_shell_cmd.clone();
             self.saved_run_as_login_shell = self.editing_run_as_login_shell;
             self.saved_enable_git_status = self.editing_enable_git_status;
             self.saved_preload_tabs = self.editing_preload_tabs;
+            self.saved_preload_limits = self.editing_preload_limits;
+            self.saved_lazy_tab_spawn = self.editing_lazy_tab_spawn;
+            self.saved_keep_workspaces_alive = self.editing_keep_workspaces_alive;
+            self.saved_git_fetch_interval_secs = self.editing_git_fetch_interval_secs;
+            self.saved_git_nested_depth = self.editing_git_nested_depth;
+            self.saved_tab_alerts = self.editing_tab_alerts;
+            self.saved_close_confirm_ignore = self.editing_close_confirm_ignore.clone();
             actions.should_save_settings = true;
             self.show_settings = false;
         }
@@ -269,6 +609,13 @@ impl WindowManager {
             self.editing_run_as_login_shell = self.saved_run_as_login_shell;
             self.editing_enable_git_status = self.saved_enable_git_status;
             self.editing_preload_tabs = self.saved_preload_tabs;
+            self.editing_preload_limits = self.saved_preload_limits;
+            self.editing_lazy_tab_spawn = self.saved_lazy_tab_spawn;
+            self.editing_keep_workspaces_alive = self.saved_keep_workspaces_alive;
+            self.editing_git_fetch_interval_secs = self.saved_git_fetch_interval_secs;
+            self.editing_git_nested_depth = self.saved_git_nested_depth;
+            self.editing_tab_alerts = self.saved_tab_alerts;
+            self.editing_close_confirm_ignore = self.saved_close_confirm_ignore.clone();
             self.show_settings = false;
         }
     }
@@ -283,6 +630,7 @@ impl WindowManager {
             ctx.memory_mut(|m| m.request_focus(window_id));
         }
         self.was_agents_settings_open = self.show_agents_settings;
+        let global_alerts = self.editing_tab_alerts;
 
         egui::Window::new("Agents")
             .id(window_id)
@@ -309,6 +657,11 @@ impl WindowManager {
 
                                         ui.horizontal(|ui| {
                                             ui.checkbox(&mut agent.enabled, "Enabled");
+                                            ui.checkbox(&mut agent.preload, "Preload")
+                                                .on_hover_text(
+                                                    "Keep a started tab of this agent ready \
+                                                     for recently used projects",
+                                                );
                                         });
 
                                         ui.horizontal(|ui| {
@@ -319,6 +672,108 @@ impl WindowManager {
                                             ui.label("Command:");
                                             ui.text_edit_singleline(&mut agent.cmd);
                                         });
+                                        egui::CollapsingHeader::new("Resource limits (0 = none)")
+                                            .default_open(!agent.limits.limits().is_empty())
+                                            .show(ui, |ui| {
+                                                egui::Grid::new("agent_limits_grid")
+                                                    .num_columns(2)
+                                                    .spacing([12.0, 6.0])
+                                                    .show(ui, |ui| {
+                                                        ui.label("Memory");
+                                                        ui.add(
+                                                            egui::DragValue::new(
+                                                                &mut agent.limits.memory_mb,
+                                                            )
+                                                            .range(0..=262144)
+                                                            .speed(16)
+                                                            .suffix(" MB"),
+                                                        );
+                                                        ui.end_row();
+                                                        ui.label("CPU time per process");
+                                                        ui.add(
+                                                            egui::DragValue::new(
+                                                                &mut agent.limits.cpu_time_secs,
+                                                            )
+                                                            .range(0..=86400)
+                                                            .speed(10)
+                                                            .suffix(" s"),
+                                                        );
+                                                        ui.end_row();
+                                                        ui.label("Open files per process");
+                                                        ui.add(
+                                                            egui::DragValue::new(
+                                                                &mut agent.limits.open_files,
+                                                            )
+                                                            .range(0..=1048576)
+                                                            .speed(8),
+                                                        );
+                                                        ui.end_row();
+                                                    });
+                                                ui.weak("Changes apply to tabs started afterwards.");
+                                                #[cfg(not(target_os = "linux"))]
+                                                ui.weak(
+                                                    "Only the memory limit is enforced on this platform.",
+                                                );
+                                            });
+                                        egui::CollapsingHeader::new("Usage alerts")
+                                            .default_open(agent.alerts.is_some())
+                                            .show(ui, |ui| {
+                                                let mut custom = agent.alerts.is_some();
+                                                if ui
+                                                    .checkbox(&mut custom, "Own thresholds (0 = off)")
+                                                    .on_hover_text(
+                                                        "Otherwise this agent's tabs use the \
+                                                         thresholds from Settings",
+                                                    )
+                                                    .changed()
+                                                {
+                                                    agent.alerts = custom.then_some(global_alerts);
+                                                }
+                                                if let Some(alerts) = &mut agent.alerts {
+                                                    egui::Grid::new("agent_alerts_grid")
+                                                        .num_columns(2)
+                                                        .spacing([12.0, 6.0])
+                                                        .show(ui, |ui| {
+                                                            ui.label("Memory above");
+                                                            ui.add(
+                                                                egui::DragValue::new(
+                                                                    &mut alerts.memory_mb,
+                                                                )
+                                                                .range(0..=65536)
+                                                                .speed(16)
+                                                                .suffix(" MB"),
+                                                            );
+                                                            ui.end_row();
+                                                            ui.label("CPU above");
+                                                            ui.add(
+                                                                egui::DragValue::new(
+                                                                    &mut alerts.cpu_percent,
+                                                                )
+                                                                .range(0..=6400)
+                                                                .suffix("%"),
+                                                            );
+                                                            ui.end_row();
+                                                            ui.label("For at least");
+                                                            ui.add(
+                                                                egui::DragValue::new(
+                                                                    &mut alerts.duration_secs,
+                                                                )
+                                                                .range(2..=120)
+                                                                .suffix(" s"),
+                                                            );
+                                                            ui.end_row();
+                                                        });
+                                                }
+                                            });
+                                        egui::CollapsingHeader::new("Diagnostics").show(
+                                            ui,
+                                            |ui| {
+                                                self.agent_diagnostics.show(ui, agent);
+                                                if ui.small_button("🔄 Check again").clicked() {
+                                                    self.agent_diagnostics.refresh();
+                                                }
+                                            },
+                                        );
                                     });
                                 });
                                 ui.add_space(8.0);
@@ -375,6 +830,11 @@ impl WindowManager {
                     ui.heading("Theme Settings");
                     ui.add_space(10.0);
 
+                    self.show_theme_library(ui, actions);
+                    ui.add_space(8.0);
+                    self.show_theme_import(ui);
+                    ui.add_space(10.0);
+
                     egui::CollapsingHeader::new("Terminal colors")
                         .default_open(false)
                         .show(ui, |ui| {
@@ -384,6 +844,44 @@ impl WindowManager {
                                 "Terminal foreground",
                                 &mut self.editing_theme.terminal_fg,
                             );
+                            ui.add_space(8.0);
+
+                            let palette = &mut self.editing_theme.terminal_palette;
+                            egui::Grid::new("ansi_colors_grid")
+                                .num_columns(4)
+                                .spacing([12.0, 4.0])
+                                .show(ui, |ui| {
+                                    ui.label("");
+                                    ui.label("Normal");
+                                    ui.label("Bright");
+                                    ui.label("Dim");
+                                    ui.end_row();
+                                    for (i, name) in ANSI_COLOR_NAMES.iter().enumerate() {
+                                        ui.label(*name);
+                                        let columns = [
+                                            &mut palette.normal,
+                                            &mut palette.bright,
+                                            &mut palette.dim,
+                                        ];
+                                        for (column, colors) in columns.into_iter().enumerate() {
+                                            color_swatch_picker(
+                                                ui,
+                                                ("ansi", column, i),
+                                                &mut colors[i],
+                                            );
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                            ui.add_space(6.0);
+                            color_picker_button(ui, "Dim foreground", &mut palette.dim_foreground);
+                            if ui
+                                .button("Derive dim colors")
+                                .on_hover_text("Darken the normal colors and the foreground")
+                                .clicked()
+                            {
+                                palette.derive_dim(self.editing_theme.terminal_fg);
+                            }
                         });
 
                     ui.add_space(8.0);
@@ -541,6 +1039,7 @@ impl WindowManager {
 
         if restore_defaults {
             self.editing_theme = AppTheme::default();
+            self.editing_theme_name = self.theme_library.name_of(&self.editing_theme);
             // Apply preview immediately so the user sees the defaults.
             self.editing_theme.apply_to_visuals(ctx);
         }
@@ -548,14 +1047,236 @@ impl WindowManager {
         if save {
             actions.theme = Some(self.editing_theme);
             self.saved_theme = self.editing_theme;
+            self.saved_theme_name = self.editing_theme_name.clone();
             actions.should_save_settings = true;
             self.show_theme_settings = false;
         }
         if cancel {
             self.editing_theme = self.saved_theme;
+            self.editing_theme_name = self.saved_theme_name.clone();
             self.editing_theme.apply_to_visuals(ctx);
             self.show_theme_settings = false;
         }
+        if !self.show_theme_settings {
+            self.theme_import = None;
+            self.theme_library_status = None;
+        }
+    }
+
+    /// Theme picker plus save-as, rename, delete and export. Picking a theme
+    /// only changes the editing theme; saved themes are written right away.
+    fn show_theme_library(&mut self, ui: &mut egui::Ui, actions: &mut WindowActions) {
+        let current = self
+            .editing_theme_name
+            .as_deref()
+            .and_then(|name| self.theme_library.get(name));
+        let selected_text = match current {
+            Some(entry) if entry.theme == self.editing_theme => entry.name.clone(),
+            Some(entry) => format!("{} (modified)", entry.name),
+            None => "Custom".to_string(),
+        };
+        let is_builtin = current.is_some_and(|entry| entry.builtin);
+        let is_saved = current.is_some_and(|entry| !entry.builtin);
+
+        let mut picked = None;
+        ui.horizontal(|ui| {
+            ui.label("Theme");
+            egui::ComboBox::from_id_salt("theme_library_combo")
+                .selected_text(selected_text)
+                .width(200.0)
+                .show_ui(ui, |ui| {
+                    for entry in &self.theme_library.themes {
+                        let selected = self.editing_theme_name.as_deref() == Some(&entry.name);
+                        if ui.selectable_label(selected, &entry.name).clicked() {
+                            picked = Some((entry.name.clone(), entry.theme));
+                        }
+                    }
+                });
+        });
+        if let Some((name, mut theme)) = picked {
+            // Font sizes belong to the Fonts window.
+            theme.fonts = self.editing_theme.fonts;
+            self.editing_theme = theme;
+            self.theme_name_input = name.clone();
+            self.editing_theme_name = Some(name);
+            self.theme_library_status = None;
+        }
+
+        let mut status = None;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.theme_name_input)
+                    .hint_text("Theme name")
+                    .desired_width(160.0),
+            );
+            if ui.button("Save as").clicked() {
+                let name = self.theme_name_input.trim().to_string();
+                status = Some(
+                    self.theme_library
+                        .save_as(&name, self.editing_theme)
+                        .map(|()| {
+                            self.editing_theme_name = Some(name.clone());
+                            format!("Saved \"{}\"", name)
+                        }),
+                );
+            }
+            if ui
+                .add_enabled(is_saved, egui::Button::new("Rename"))
+                .clicked()
+            {
+                let old = self.editing_theme_name.clone().unwrap_or_default();
+                let new = self.theme_name_input.trim().to_string();
+                status = Some(self.theme_library.rename(&old, &new).map(|()| {
+                    if self.saved_theme_name.as_deref() == Some(old.as_str()) {
+                        self.saved_theme_name = Some(new.clone());
+                        actions.should_save_settings = true;
+                    }
+                    self.editing_theme_name = Some(new.clone());
+                    format!("Renamed to \"{}\"", new)
+                }));
+            }
+            if ui
+                .add_enabled(is_saved, egui::Button::new("Delete"))
+                .clicked()
+            {
+                let name = self.editing_theme_name.clone().unwrap_or_default();
+                status = Some(self.theme_library.delete(&name).map(|()| {
+                    // The deleted theme was being edited, and maybe in use:
+                    // fall back to the first built-in preset.
+                    if let Some(preset) = self.theme_library.themes.iter().find(|t| t.builtin) {
+                        let mut theme = preset.theme;
+                        theme.fonts = self.editing_theme.fonts;
+                        self.editing_theme = theme;
+                        self.editing_theme_name = Some(preset.name.clone());
+                        self.theme_name_input = preset.name.clone();
+                        if self.saved_theme_name.as_deref() == Some(name.as_str()) {
+                            theme.fonts = self.saved_theme.fonts;
+                            self.saved_theme = theme;
+                            self.saved_theme_name = Some(preset.name.clone());
+                            actions.theme = Some(theme);
+                            actions.should_save_settings = true;
+                        }
+                    }
+                    format!("Deleted \"{}\"", name)
+                }));
+            }
+            if ui.button("Export...").clicked() {
+                let name = self
+                    .editing_theme_name
+                    .clone()
+                    .unwrap_or_else(|| "Custom".to_string());
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Theme", &["json"])
+                    .set_file_name(format!("{}.json", name))
+                    .save_file()
+                {
+                    status = Some(
+                        themes::export(&name, &self.editing_theme, &path)
+                            .map(|()| format!("Exported to {}", path.display())),
+                    );
+                }
+            }
+        });
+        if is_builtin {
+            ui.weak("Built-in themes cannot be changed; use \"Save as\" to keep a copy.");
+        }
+        if status.is_some() {
+            self.theme_library_status = status;
+        }
+        match &self.theme_library_status {
+            Some(Ok(message)) => {
+                ui.weak(message);
+            }
+            Some(Err(error)) => {
+                ui.label(
+                    egui::RichText::new(error).color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                );
+            }
+            None => {}
+        }
+    }
+
+    /// "Import..." button, and the preview of an imported scheme with the
+    /// entries it could not use. Applying only changes the editing theme, so
+    /// Save or Cancel still decide.
+    fn show_theme_import(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .button("📥 Import...")
+                .on_hover_text("Alacritty, iTerm2, Windows Terminal or base16 color scheme")
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter(
+                        "Color schemes",
+                        &["toml", "yml", "yaml", "itermcolors", "plist", "json"],
+                    )
+                    .pick_file()
+                {
+                    self.theme_import = Some(theme_import::import_file(&path));
+                }
+            }
+        });
+
+        let mut apply = false;
+        let mut discard = false;
+        match &self.theme_import {
+            None => return,
+            Some(Err(error)) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        egui::RichText::new(error).color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                    );
+                    discard = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                });
+            }
+            Some(Ok(imported)) => {
+                ui.group(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} ({})",
+                            imported.name,
+                            imported.format.label()
+                        ))
+                        .strong(),
+                    );
+                    let mut preview = self.editing_theme;
+                    imported.apply_to(&mut preview);
+                    scheme_preview(ui, &preview);
+
+                    if !imported.unknown.is_empty() {
+                        egui::CollapsingHeader::new(format!(
+                            "⚠ {} entries not imported",
+                            imported.unknown.len()
+                        ))
+                        .id_salt("theme_import_unknown")
+                        .show(ui, |ui| {
+                            egui::ScrollArea::vertical()
+                                .max_height(120.0)
+                                .show(ui, |ui| {
+                                    for entry in &imported.unknown {
+                                        ui.weak(entry);
+                                    }
+                                });
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        apply = ui.button("Apply").clicked();
+                        discard = ui.button("Discard").clicked();
+                    });
+                });
+            }
+        }
+
+        if apply {
+            if let Some(Ok(imported)) = &self.theme_import {
+                imported.apply_to(&mut self.editing_theme);
+            }
+        }
+        if apply || discard {
+            self.theme_import = None;
+        }
     }
 
     fn show_font_settings_window(&mut self, ctx: &egui::Context, actions: &mut WindowActions) {
@@ -595,6 +1316,56 @@ impl WindowManager {
                         "Terminal font size",
                         &mut self.editing_fonts.terminal_font_size,
                     );
+                    ui.horizontal(|ui| {
+                        ui.label("Terminal line height");
+                        ui.add(
+                            egui::Slider::new(&mut self.editing_fonts.line_height, 0.8..=2.0)
+                                .step_by(0.05)
+                                .text("×"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Terminal letter spacing");
+                        ui.add(
+                            egui::Slider::new(&mut self.editing_fonts.letter_spacing, -1.0..=5.0)
+                                .step_by(0.25)
+                                .text("px"),
+                        );
+                    });
+                    ui.weak("Ligatures are not supported: egui draws each character on its own.");
+
+                    ui.add_space(10.0);
+
+                    let system_fonts = self
+                        .system_fonts
+                        .get_or_insert_with(crate::font_setup::system_fonts);
+                    egui::Grid::new("font_faces_grid")
+                        .num_columns(2)
+                        .spacing([12.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.label("Terminal font");
+                            font_face_picker(
+                                ui,
+                                "terminal_font_face",
+                                &mut self.editing_font_faces.terminal,
+                                system_fonts.iter().filter(|f| f.monospace),
+                            );
+                            ui.end_row();
+                            ui.label("UI font");
+                            font_face_picker(
+                                ui,
+                                "ui_font_face",
+                                &mut self.editing_font_faces.ui,
+                                system_fonts.iter(),
+                            );
+                            ui.end_row();
+                        });
+                    if let Some(error) = &self.font_error {
+                        ui.label(
+                            egui::RichText::new(error)
+                                .color(egui::Color32::from_rgb(0xf4, 0x43, 0x36)),
+                        );
+                    }
 
                     ui.add_space(15.0);
 
@@ -608,11 +1379,12 @@ impl WindowManager {
                                 .size(self.editing_fonts.group_name_font_size),
                         );
                         ui.label(egui::RichText::new("Tab").size(self.editing_fonts.tab_font_size));
-                        ui.label(
-                            egui::RichText::new("Terminal").font(egui::FontId::monospace(
-                                self.editing_fonts.terminal_font_size,
-                            )),
-                        );
+                        // Shows the applied terminal font; "Preview" applies
+                        // the edited one.
+                        ui.label(egui::RichText::new("Terminal").font(egui::FontId::new(
+                            self.editing_fonts.terminal_font_size,
+                            egui::FontFamily::Name(TERMINAL_FONT_FAMILY.into()),
+                        )));
                     });
 
                     ui.add_space(15.0);
@@ -640,24 +1412,32 @@ impl WindowManager {
                 });
             });
 
+        // Fonts are applied by `App`, which also rebuilds the terminal font
+        // from `editing_font_faces`.
         if restore_defaults {
             self.editing_fonts = AppFonts::default();
-            self.editing_fonts.apply(ctx);
+            self.editing_font_faces = FontFaces::default();
+            actions.fonts = Some(self.editing_fonts);
         }
 
         if preview {
-            self.editing_fonts.apply(ctx);
+            actions.fonts = Some(self.editing_fonts);
         }
 
         if save {
             actions.fonts = Some(self.editing_fonts);
             self.saved_fonts = self.editing_fonts;
+            self.saved_font_faces = self.editing_font_faces.clone();
+            // Keep the theme window from saving stale font sizes.
+            self.editing_theme.fonts = self.editing_fonts;
+            self.saved_theme.fonts = self.editing_fonts;
             actions.should_save_settings = true;
             self.show_font_settings = false;
         }
         if cancel {
             self.editing_fonts = self.saved_fonts;
-            self.saved_fonts.apply(ctx);
+            self.editing_font_faces = self.saved_font_faces.clone();
+            actions.fonts = Some(self.saved_fonts);
             self.show_font_settings = false;
         }
     }
@@ -678,6 +1458,11 @@ impl WindowManager {
             .show(ctx, |ui| {
                 egui::Frame::NONE.inner_margin(20.0).show(ui, |ui| {
                     ui.heading("Are you sure?");
+                    if !self.exit_commands.is_empty() {
+                        ui.add_space(10.0);
+                        ui.label("These processes will be terminated:");
+                        command_list(ui, &self.exit_commands);
+                    }
                     ui.add_space(15.0);
                     ui.horizontal(|ui| {
                         if ui
@@ -704,6 +1489,67 @@ impl WindowManager {
             self.show_close_confirmation = false;
         }
     }
+
+    fn show_pending_close_window(&mut self, ctx: &egui::Context, actions: &mut WindowActions) {
+        let Some(pending) = &self.pending_close else {
+            return;
+        };
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let title = match pending.target {
+            CloseTarget::Tab(_) => format!("Close tab \"{}\"?", pending.name),
+            CloseTarget::Group(_) => format!("Close project \"{}\"?", pending.name),
+        };
+
+        egui::Window::new(title)
+            .id(egui::Id::new("pending_close_window"))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Frame::NONE.inner_margin(20.0).show(ui, |ui| {
+                    ui.label("These processes are still running and will be terminated:");
+                    command_list(ui, &pending.commands);
+                    ui.add_space(15.0);
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(egui::Button::new("Close").min_size(egui::vec2(80.0, 32.0)))
+                            .clicked()
+                            || ui.input(|i| i.key_pressed(egui::Key::Enter))
+                        {
+                            confirmed = true;
+                        }
+                        if ui
+                            .add(egui::Button::new("Cancel").min_size(egui::vec2(80.0, 32.0)))
+                            .clicked()
+                            || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                        {
+                            cancelled = true;
+                        }
+                    });
+                });
+            });
+
+        if confirmed {
+            actions.close_target_confirmed = Some(pending.target);
+        }
+        if confirmed || cancelled || !open {
+            self.pending_close = None;
+        }
+    }
+}
+
+/// Monospace list of the commands a close would terminate.
+fn command_list(ui: &mut egui::Ui, commands: &[String]) {
+    egui::ScrollArea::vertical()
+        .id_salt("close_command_list")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for command in commands {
+                ui.label(egui::RichText::new(command).monospace());
+            }
+        });
 }
 
 #[derive(Default)]
@@ -714,8 +1560,16 @@ pub struct WindowActions {
     pub run_as_login_shell: Option<bool>,
     pub enable_git_status: Option<bool>,
     pub preload_tabs: Option<bool>,
+    pub preload_limits: Option<PreloadLimits>,
+    pub lazy_tab_spawn: Option<bool>,
+    pub git_fetch_interval_secs: Option<u64>,
+    pub git_nested_depth: Option<usize>,
+    pub tab_alerts: Option<TabAlertSettings>,
+    pub close_confirm_ignore: Option<Vec<String>>,
+    pub close_target_confirmed: Option<CloseTarget>,
     pub theme: Option<AppTheme>,
     pub fonts: Option<AppFonts>,
+    pub refresh_git_path: Option<PathBuf>,
     pub should_save_groups: bool,
     pub should_save_settings: bool,
     pub close_confirmed: bool,
@@ -754,3 +1608,63 @@ fn button_style_group(ui: &mut egui::Ui, style: &mut AppButtonStyle, preview_lab
         ui.add(egui::Button::new(preview_label).min_size(egui::vec2(0.0, 28.0)));
     });
 }
+
+/// Foreground text on the background and the normal and bright ANSI colors
+/// of `theme`, the way the terminal would show them.
+fn scheme_preview(ui: &mut egui::Ui, theme: &AppTheme) {
+    let palette = &theme.terminal_palette;
+    egui::Frame::NONE
+        .fill(theme.app_bg)
+        .inner_margin(8.0)
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new("~/project $ ls")
+                    .monospace()
+                    .color(theme.terminal_fg),
+            );
+            for colors in [&palette.normal, &palette.bright] {
+                ui.horizontal(|ui| {
+                    for color in colors {
+                        ui.label(egui::RichText::new("███").monospace().color(*color));
+                    }
+                });
+            }
+        });
+}
+
+/// Combo box of `fonts` plus a button to load a font file. `None` is egui's
+/// default font.
+fn font_face_picker<'a>(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    face: &mut Option<FontSource>,
+    fonts: impl Iterator<Item = &'a SystemFont>,
+) {
+    ui.horizontal(|ui| {
+        let selected = face
+            .as_ref()
+            .map_or("Default".to_string(), FontSource::label);
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(selected)
+            .width(240.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(face, None, "Default");
+                if let Some(file @ FontSource::File(_)) = face.clone() {
+                    let label = file.label();
+                    ui.selectable_value(face, Some(file), label);
+                }
+                for font in fonts {
+                    let source = FontSource::System(font.name.clone());
+                    ui.selectable_value(face, Some(source), &font.name);
+                }
+            });
+        if ui.button("📂").on_hover_text("Load a font file").clicked() {
+            if let Some(path) = rfd::FileDialog::new()
+                .add_filter("Fonts", &["ttf", "otf", "ttc"])
+                .pick_file()
+            {
+                *face = Some(FontSource::File(path));
+            }
+        }
+    });
+}